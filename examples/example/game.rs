@@ -4,13 +4,11 @@ use glium::{Display, Surface};
 use glium::glutin::event::{Event, VirtualKeyCode};
 use glium::glutin::window::Fullscreen;
 
-use include_dir::*;
-
 use glib::ProgramsCache;
-use glib::input::{VirtualGamepad, VirtualGamepadStick, KeyboardMouseVirtualGamepad};
+use glib::input::{VirtualGamepad, VirtualGamepadStick, KeyboardMouseVirtualGamepad, RealInput};
 use glib::controllers::FirstPersonController;
 use glib::math::Camera;
-use glib::rendering::{Drawable, scene::{Scene, Transformable}};
+use glib::rendering::{Drawable, FrameGlobals, GlobalsDrawer, Screenshot, scene::{Scene, Transformable}};
 use glib::fps::FpsCounter;
 use glib::game::{EventHandler, GameEvent, KeyboardEvent, MouseEvent};
 use glib::math::*;
@@ -28,16 +26,13 @@ impl Game {
     pub fn new(display: &Display) -> Game {
         let mut input = KeyboardMouseVirtualGamepad::new();
         input.bind_default();
+        input.bind_screenshot(RealInput::KeyboardKey(VirtualKeyCode::F12));
 
         let mut controller = FirstPersonController::new();
         let camera =
             Camera::perspective_for_display(display, controller.get_transform(), PI * 0.333);
 
-        let chunks_directory = include_dir!("examples/example/shaders/chunks");
-        let materials_directory = include_dir!("examples/example/shaders/materials");
-
-        let programs_cache =
-            ProgramsCache::new(display, "330", &chunks_directory, &materials_directory);
+        let _programs_cache = ProgramsCache::standard(display, "330");
         let mut scene = Scene::new();
         scene.set_background_color(hex("#87ceeb"));
         //let test_cube = TestCube::new(display, &programs_cache);
@@ -61,6 +56,8 @@ impl Game {
 
 impl EventHandler for Game {
     fn handle_game_event(&mut self, display: &Display, event: &mut GameEvent) {
+        self.scene.handle_game_event(event);
+
         match event {
             GameEvent::Resize { .. } => {
                 self.camera = Camera::perspective_for_display(display, IDENTITY, PI * 0.333);
@@ -89,12 +86,19 @@ impl EventHandler for Game {
                 self.camera.transform = self.controller.get_transform();
                 self.input.on_after_update();
             }
-            GameEvent::Draw => {
+            GameEvent::Draw { frame_time } => {
                 let mut frame = display.draw();
                 frame.clear_color(0.0, 0.0, 0.0, 0.0);
+                let globals = FrameGlobals::for_surface(*frame_time, &frame);
                 self.scene
-                    .draw(&mut frame, &IDENTITY, &self.camera)
+                    .draw(&mut GlobalsDrawer::new(&mut frame, globals), &IDENTITY, &self.camera)
                     .unwrap();
+                if self.input.take_screenshot_request() {
+                    Screenshot::from_surface(display, &frame)
+                        .unwrap()
+                        .save_png(Screenshot::default_file_name())
+                        .unwrap();
+                }
                 frame.finish().unwrap();
             }
             _ => (),
@@ -0,0 +1,3 @@
+in vec3 position;
+in vec3 normal;
+in vec2 texture_coordinates;
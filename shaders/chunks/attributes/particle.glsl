@@ -0,0 +1,2 @@
+in vec4 particle_color;
+in float particle_size;
@@ -0,0 +1,2 @@
+in uvec4 joint_indices;
+in vec4 joint_weights;
@@ -0,0 +1,12 @@
+#require <structs/surface>
+
+vec3 blinn_phong(Surface surface, vec3 light_direction, vec3 light_color, vec3 diffuse, vec3 specular, float shininess) {
+    vec3 to_light = normalize(-light_direction);
+    vec3 half_direction = normalize(to_light + surface.view_direction);
+
+    float n_dot_l = max(dot(surface.normal, to_light), 0.0);
+    float n_dot_h = max(dot(surface.normal, half_direction), 0.0);
+    float specular_term = n_dot_l > 0.0 ? pow(n_dot_h, shininess) : 0.0;
+
+    return (diffuse * n_dot_l + specular * specular_term) * light_color;
+}
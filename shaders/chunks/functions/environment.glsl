@@ -0,0 +1,20 @@
+#require <uniforms/environment>
+#require <structs/surface>
+
+vec3 environment_lighting(Surface surface, vec3 albedo, float metallic, float roughness) {
+    float n_dot_v = max(dot(surface.normal, surface.view_direction), 0.0001);
+    vec3 reflected = reflect(-surface.view_direction, surface.normal);
+
+    vec3 f0 = mix(vec3(0.04), albedo, metallic);
+    vec3 fresnel = f0 + (max(vec3(1.0 - roughness), f0) - f0) * pow(1.0 - n_dot_v, 5.0);
+
+    vec3 irradiance = texture(environment_irradiance, surface.normal).rgb;
+    vec3 diffuse = (vec3(1.0) - fresnel) * (1.0 - metallic) * irradiance * albedo;
+
+    float level = roughness * (environment_specular_levels - 1.0);
+    vec3 prefiltered = textureLod(environment_specular, reflected, level).rgb;
+    vec2 brdf = texture(environment_brdf, vec2(n_dot_v, roughness)).rg;
+    vec3 specular = prefiltered * (fresnel * brdf.x + brdf.y);
+
+    return diffuse + specular;
+}
@@ -0,0 +1,36 @@
+#require <uniforms/common>
+#require <uniforms/fog>
+
+const int FOG_NONE = 0;
+const int FOG_LINEAR = 1;
+const int FOG_EXPONENTIAL = 2;
+const int FOG_EXPONENTIAL_SQUARED = 3;
+const int FOG_HEIGHT = 4;
+
+float fog_factor(vec3 world_position) {
+    vec3 ray = world_position - camera_position_world;
+    float ray_length = length(ray);
+
+    if (fog_mode == FOG_LINEAR) {
+        return clamp((ray_length - fog_start) / max(fog_end - fog_start, 0.0001), 0.0, 1.0);
+    } else if (fog_mode == FOG_EXPONENTIAL) {
+        return 1.0 - exp(-fog_density * ray_length);
+    } else if (fog_mode == FOG_EXPONENTIAL_SQUARED) {
+        float amount = fog_density * ray_length;
+        return 1.0 - exp(-amount * amount);
+    } else if (fog_mode == FOG_HEIGHT) {
+        float camera_height = camera_position_world.y - fog_height;
+        float base = fog_density * exp(-camera_height * fog_height_falloff);
+        float vertical = ray.y * fog_height_falloff;
+        float amount = abs(vertical) > 0.0001
+            ? base * ray_length * (1.0 - exp(-vertical)) / vertical
+            : base * ray_length;
+        return clamp(1.0 - exp(-amount), 0.0, 1.0);
+    }
+
+    return 0.0;
+}
+
+vec3 apply_fog(vec3 color, vec3 world_position) {
+    return mix(color, fog_color.rgb, fog_factor(world_position));
+}
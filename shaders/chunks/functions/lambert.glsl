@@ -0,0 +1,7 @@
+#require <structs/surface>
+
+vec3 lambert(Surface surface, vec3 light_direction, vec3 light_color, vec3 diffuse) {
+    vec3 to_light = normalize(-light_direction);
+    float n_dot_l = max(dot(surface.normal, to_light), 0.0);
+    return diffuse * light_color * n_dot_l;
+}
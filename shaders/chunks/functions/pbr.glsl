@@ -0,0 +1,39 @@
+#require <structs/surface>
+
+const float PBR_PI = 3.14159265359;
+
+float distribution_ggx(float n_dot_h, float roughness) {
+    float alpha = roughness * roughness;
+    float alpha_squared = alpha * alpha;
+    float denominator = n_dot_h * n_dot_h * (alpha_squared - 1.0) + 1.0;
+    return alpha_squared / (PBR_PI * denominator * denominator);
+}
+
+float geometry_schlick_ggx(float n_dot_x, float roughness) {
+    float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    return n_dot_x / (n_dot_x * (1.0 - k) + k);
+}
+
+vec3 fresnel_schlick(float cos_theta, vec3 f0) {
+    return f0 + (1.0 - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+vec3 pbr(Surface surface, vec3 light_direction, vec3 light_color, vec3 albedo, float metallic, float roughness) {
+    vec3 to_light = normalize(-light_direction);
+    vec3 half_direction = normalize(to_light + surface.view_direction);
+
+    float n_dot_l = max(dot(surface.normal, to_light), 0.0);
+    float n_dot_v = max(dot(surface.normal, surface.view_direction), 0.0001);
+    float n_dot_h = max(dot(surface.normal, half_direction), 0.0);
+    float h_dot_v = max(dot(half_direction, surface.view_direction), 0.0);
+
+    vec3 f0 = mix(vec3(0.04), albedo, metallic);
+    vec3 fresnel = fresnel_schlick(h_dot_v, f0);
+    float distribution = distribution_ggx(n_dot_h, roughness);
+    float geometry = geometry_schlick_ggx(n_dot_v, roughness) * geometry_schlick_ggx(n_dot_l, roughness);
+
+    vec3 specular = distribution * geometry * fresnel / (4.0 * n_dot_v * n_dot_l + 0.0001);
+    vec3 diffuse = (vec3(1.0) - fresnel) * (1.0 - metallic) * albedo / PBR_PI;
+
+    return (diffuse + specular) * light_color * n_dot_l;
+}
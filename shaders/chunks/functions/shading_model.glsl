@@ -0,0 +1,11 @@
+const int SHADING_MODEL_PBR = 0;
+const int SHADING_MODEL_LAMBERT = 1;
+const int SHADING_MODEL_BLINN_PHONG = 2;
+
+float encode_shading_model(int shading_model) {
+    return float(shading_model) / 255.0;
+}
+
+int decode_shading_model(float encoded) {
+    return int(encoded * 255.0 + 0.5);
+}
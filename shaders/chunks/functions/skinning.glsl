@@ -0,0 +1,9 @@
+#require <attributes/skinning>
+#require <uniforms/skinning>
+
+mat4 skinning_matrix() {
+    return joint_weights.x * joint_matrices[joint_indices.x]
+        + joint_weights.y * joint_matrices[joint_indices.y]
+        + joint_weights.z * joint_matrices[joint_indices.z]
+        + joint_weights.w * joint_matrices[joint_indices.w];
+}
@@ -0,0 +1,10 @@
+#require <uniforms/common>
+#require <structs/surface>
+
+Surface make_surface(vec3 world_position, vec3 world_normal) {
+    Surface surface;
+    surface.position = world_position;
+    surface.normal = normalize(world_normal);
+    surface.view_direction = normalize(camera_position_world - world_position);
+    return surface;
+}
@@ -0,0 +1,5 @@
+struct Surface {
+    vec3 position;
+    vec3 normal;
+    vec3 view_direction;
+};
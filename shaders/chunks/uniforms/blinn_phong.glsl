@@ -0,0 +1,3 @@
+uniform vec4 diffuse_color;
+uniform vec3 specular_color;
+uniform float shininess;
@@ -0,0 +1 @@
+uniform vec4 color;
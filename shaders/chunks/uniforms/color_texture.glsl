@@ -0,0 +1 @@
+uniform sampler2D color_texture;
@@ -0,0 +1,16 @@
+uniform mat4 matrix_to_world;
+uniform mat4 matrix_to_local;
+uniform mat4 matrix_to_view;
+uniform mat4 matrix_to_projection;
+
+uniform vec3 camera_position_world;
+uniform vec3 camera_position_local;
+
+uniform mat4 inverse_projection;
+uniform float depth_near;
+uniform float depth_far;
+
+uniform float time;
+uniform float delta_time;
+uniform uint frame_index;
+uniform vec2 viewport_size;
@@ -0,0 +1,11 @@
+uniform sampler2D gbuffer_albedo;
+uniform sampler2D gbuffer_normal;
+uniform sampler2D gbuffer_material;
+uniform sampler2D gbuffer_emissive;
+uniform sampler2D gbuffer_depth;
+
+uniform mat4 inverse_view_projection;
+
+uniform int light_type;
+uniform vec3 light_position;
+uniform float light_range;
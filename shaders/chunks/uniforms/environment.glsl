@@ -0,0 +1,5 @@
+uniform bool has_environment;
+uniform samplerCube environment_irradiance;
+uniform samplerCube environment_specular;
+uniform sampler2D environment_brdf;
+uniform float environment_specular_levels;
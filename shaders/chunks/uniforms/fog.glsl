@@ -0,0 +1,7 @@
+uniform int fog_mode;
+uniform vec4 fog_color;
+uniform float fog_start;
+uniform float fog_end;
+uniform float fog_density;
+uniform float fog_height;
+uniform float fog_height_falloff;
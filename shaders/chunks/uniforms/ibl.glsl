@@ -0,0 +1,8 @@
+uniform sampler2D environment_source;
+uniform samplerCube environment_cubemap;
+
+uniform vec3 face_forward;
+uniform vec3 face_right;
+uniform vec3 face_up;
+
+uniform float prefilter_roughness;
@@ -0,0 +1,3 @@
+uniform vec3 ambient_color;
+uniform vec3 light_direction;
+uniform vec3 light_color;
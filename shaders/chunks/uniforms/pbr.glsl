@@ -0,0 +1,10 @@
+uniform vec4 base_color;
+uniform float metallic;
+uniform float roughness;
+uniform vec3 emissive_color;
+
+uniform bool has_base_color_texture;
+uniform sampler2D base_color_texture;
+
+uniform bool has_metallic_roughness_texture;
+uniform sampler2D metallic_roughness_texture;
@@ -0,0 +1,3 @@
+const int MAX_JOINTS = 64;
+
+uniform mat4 joint_matrices[MAX_JOINTS];
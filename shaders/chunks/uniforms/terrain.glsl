@@ -0,0 +1,6 @@
+uniform sampler2D splat_map;
+uniform sampler2D layer_0;
+uniform sampler2D layer_1;
+uniform sampler2D layer_2;
+uniform sampler2D layer_3;
+uniform float layer_scale;
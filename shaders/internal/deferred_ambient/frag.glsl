@@ -0,0 +1,19 @@
+#require <uniforms/deferred>
+#require <uniforms/lights>
+
+in vec2 uv;
+
+out vec4 frag_color;
+
+void main() {
+    float depth = texture(gbuffer_depth, uv).r;
+    if (depth >= 1.0) {
+        discard;
+    }
+
+    vec3 albedo = texture(gbuffer_albedo, uv).rgb;
+    vec3 emissive = texture(gbuffer_emissive, uv).rgb;
+
+    frag_color = vec4(ambient_color * albedo + emissive, 1.0);
+    gl_FragDepth = depth;
+}
@@ -0,0 +1,18 @@
+#require <uniforms/deferred>
+#require <functions/fog>
+
+in vec2 uv;
+
+out vec4 frag_color;
+
+void main() {
+    float depth = texture(gbuffer_depth, uv).r;
+    if (depth >= 1.0) {
+        discard;
+    }
+
+    vec4 clip_position = inverse_view_projection * vec4(uv * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
+    vec3 world_position = clip_position.xyz / clip_position.w;
+
+    frag_color = vec4(fog_color.rgb, fog_factor(world_position));
+}
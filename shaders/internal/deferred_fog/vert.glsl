@@ -0,0 +1,7 @@
+out vec2 uv;
+
+void main() {
+    uv = texture_coordinates;
+
+    gl_Position = vec4(position.xy, 0.0, 1.0);
+}
@@ -0,0 +1,58 @@
+#require <uniforms/deferred>
+#require <uniforms/lights>
+#require <functions/lambert>
+#require <functions/blinn_phong>
+#require <functions/pbr>
+#require <functions/shading_model>
+
+const int LIGHT_DIRECTIONAL = 0;
+const int LIGHT_POINT = 1;
+
+in vec2 uv;
+
+out vec4 frag_color;
+
+void main() {
+    float depth = texture(gbuffer_depth, uv).r;
+    if (depth >= 1.0) {
+        discard;
+    }
+
+    vec4 clip_position = inverse_view_projection * vec4(uv * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
+    vec3 world_position = clip_position.xyz / clip_position.w;
+
+    Surface surface;
+    surface.position = world_position;
+    surface.normal = normalize(texture(gbuffer_normal, uv).xyz);
+    surface.view_direction = normalize(camera_position_world - world_position);
+
+    vec3 albedo = texture(gbuffer_albedo, uv).rgb;
+    vec4 material = texture(gbuffer_material, uv);
+
+    vec3 direction = light_direction;
+    vec3 radiance = light_color;
+    if (light_type == LIGHT_POINT) {
+        vec3 offset = world_position - light_position;
+        float distance_to_light = length(offset);
+        if (distance_to_light >= light_range) {
+            discard;
+        }
+
+        direction = offset / max(distance_to_light, 0.0001);
+        float attenuation = 1.0 - distance_to_light / light_range;
+        radiance *= attenuation * attenuation;
+    }
+
+    vec3 lit;
+    int shading_model = decode_shading_model(material.a);
+    if (shading_model == SHADING_MODEL_LAMBERT) {
+        lit = lambert(surface, direction, radiance, albedo);
+    } else if (shading_model == SHADING_MODEL_BLINN_PHONG) {
+        float shininess = texture(gbuffer_emissive, uv).a;
+        lit = blinn_phong(surface, direction, radiance, albedo, material.rgb, shininess);
+    } else {
+        lit = pbr(surface, direction, radiance, albedo, material.r, material.g);
+    }
+
+    frag_color = vec4(lit, 1.0);
+}
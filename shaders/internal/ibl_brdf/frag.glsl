@@ -0,0 +1,42 @@
+#require <functions/ibl>
+
+const uint SAMPLE_COUNT = 512u;
+
+in vec2 uv;
+
+out vec4 frag_color;
+
+float geometry_schlick_ibl(float n_dot_x, float roughness) {
+    float k = roughness * roughness / 2.0;
+    return n_dot_x / (n_dot_x * (1.0 - k) + k);
+}
+
+void main() {
+    float n_dot_v = max(uv.x, 0.0001);
+    float roughness = uv.y;
+
+    vec3 view_direction = vec3(sqrt(1.0 - n_dot_v * n_dot_v), 0.0, n_dot_v);
+    vec3 normal = vec3(0.0, 0.0, 1.0);
+
+    float scale = 0.0;
+    float bias = 0.0;
+    for (uint i = 0u; i < SAMPLE_COUNT; i++) {
+        vec3 half_direction = importance_sample_ggx(hammersley(i, SAMPLE_COUNT), normal, roughness);
+        vec3 to_light = normalize(2.0 * dot(view_direction, half_direction) * half_direction - view_direction);
+
+        float n_dot_l = max(to_light.z, 0.0);
+        float n_dot_h = max(half_direction.z, 0.0);
+        float v_dot_h = max(dot(view_direction, half_direction), 0.0);
+
+        if (n_dot_l > 0.0) {
+            float geometry = geometry_schlick_ibl(n_dot_v, roughness) * geometry_schlick_ibl(n_dot_l, roughness);
+            float visibility = geometry * v_dot_h / (n_dot_h * n_dot_v);
+            float fresnel = pow(1.0 - v_dot_h, 5.0);
+
+            scale += (1.0 - fresnel) * visibility;
+            bias += fresnel * visibility;
+        }
+    }
+
+    frag_color = vec4(scale / float(SAMPLE_COUNT), bias / float(SAMPLE_COUNT), 0.0, 1.0);
+}
@@ -0,0 +1,12 @@
+#require <functions/ibl>
+
+in vec2 uv;
+
+out vec4 frag_color;
+
+void main() {
+    vec3 direction = face_direction(uv);
+    vec2 source_uv = vec2(atan(direction.z, direction.x) / (2.0 * IBL_PI) + 0.5, asin(clamp(direction.y, -1.0, 1.0)) / IBL_PI + 0.5);
+
+    frag_color = vec4(texture(environment_source, source_uv).rgb, 1.0);
+}
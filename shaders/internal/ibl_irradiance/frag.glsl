@@ -0,0 +1,27 @@
+#require <functions/ibl>
+
+in vec2 uv;
+
+out vec4 frag_color;
+
+void main() {
+    vec3 normal = face_direction(uv);
+    vec3 up = abs(normal.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+    vec3 right = normalize(cross(up, normal));
+    up = cross(normal, right);
+
+    const float sample_delta = 0.025;
+    vec3 irradiance = vec3(0.0);
+    float sample_count = 0.0;
+    for (float phi = 0.0; phi < 2.0 * IBL_PI; phi += sample_delta) {
+        for (float theta = 0.0; theta < 0.5 * IBL_PI; theta += sample_delta) {
+            vec3 tangent_sample = vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            vec3 direction = tangent_sample.x * right + tangent_sample.y * up + tangent_sample.z * normal;
+
+            irradiance += texture(environment_cubemap, direction).rgb * cos(theta) * sin(theta);
+            sample_count += 1.0;
+        }
+    }
+
+    frag_color = vec4(IBL_PI * irradiance / sample_count, 1.0);
+}
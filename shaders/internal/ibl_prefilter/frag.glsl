@@ -0,0 +1,27 @@
+#require <functions/ibl>
+
+const uint SAMPLE_COUNT = 256u;
+
+in vec2 uv;
+
+out vec4 frag_color;
+
+void main() {
+    vec3 normal = face_direction(uv);
+    vec3 view_direction = normal;
+
+    vec3 prefiltered = vec3(0.0);
+    float total_weight = 0.0;
+    for (uint i = 0u; i < SAMPLE_COUNT; i++) {
+        vec3 half_direction = importance_sample_ggx(hammersley(i, SAMPLE_COUNT), normal, prefilter_roughness);
+        vec3 to_light = normalize(2.0 * dot(view_direction, half_direction) * half_direction - view_direction);
+
+        float n_dot_l = dot(normal, to_light);
+        if (n_dot_l > 0.0) {
+            prefiltered += texture(environment_cubemap, to_light).rgb * n_dot_l;
+            total_weight += n_dot_l;
+        }
+    }
+
+    frag_color = vec4(prefiltered / max(total_weight, 0.0001), 1.0);
+}
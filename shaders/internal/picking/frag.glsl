@@ -0,0 +1,9 @@
+uniform uint pick_id;
+
+in vec3 world_position;
+
+out uvec4 pick;
+
+void main() {
+    pick = uvec4(pick_id, floatBitsToUint(world_position));
+}
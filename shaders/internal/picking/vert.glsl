@@ -0,0 +1,7 @@
+out vec3 world_position;
+
+void main() {
+    world_position = (matrix_to_world * vec4(position, 1.0)).xyz;
+
+    gl_Position = matrix_to_projection * vec4(position, 1.0);
+}
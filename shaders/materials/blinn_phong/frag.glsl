@@ -0,0 +1,21 @@
+#require <uniforms/blinn_phong>
+#require <uniforms/lights>
+#require <functions/surface>
+#require <functions/blinn_phong>
+#require <functions/fog>
+
+in vec3 world_position;
+in vec3 world_normal;
+in vec2 uv;
+
+out vec4 frag_color;
+
+void main() {
+    Surface surface = make_surface(world_position, world_normal);
+
+    vec3 diffuse = diffuse_color.rgb;
+    vec3 lit = ambient_color * diffuse
+        + blinn_phong(surface, light_direction, light_color, diffuse, specular_color, shininess);
+
+    frag_color = vec4(apply_fog(lit, world_position), diffuse_color.a);
+}
@@ -0,0 +1,3 @@
+void main() {
+    gl_Position = matrix_to_projection * vec4(position, 1.0);
+}
@@ -0,0 +1,18 @@
+#require <uniforms/color>
+#require <functions/shading_model>
+
+in vec3 world_position;
+in vec3 world_normal;
+in vec2 uv;
+
+out vec4 albedo_output;
+out vec4 normal_output;
+out vec4 material_output;
+out vec4 emissive_output;
+
+void main() {
+    albedo_output = vec4(color.rgb, 1.0);
+    normal_output = vec4(normalize(world_normal), 0.0);
+    material_output = vec4(0.0, 0.0, 0.0, encode_shading_model(SHADING_MODEL_LAMBERT));
+    emissive_output = vec4(0.0);
+}
@@ -0,0 +1,11 @@
+out vec3 world_position;
+out vec3 world_normal;
+out vec2 uv;
+
+void main() {
+    world_position = (matrix_to_world * vec4(position, 1.0)).xyz;
+    world_normal = mat3(transpose(matrix_to_local)) * normal;
+    uv = texture_coordinates;
+
+    gl_Position = matrix_to_projection * vec4(position, 1.0);
+}
@@ -0,0 +1,32 @@
+#require <uniforms/pbr>
+#require <functions/shading_model>
+
+in vec3 world_position;
+in vec3 world_normal;
+in vec2 uv;
+
+out vec4 albedo_output;
+out vec4 normal_output;
+out vec4 material_output;
+out vec4 emissive_output;
+
+void main() {
+    vec4 albedo = base_color;
+    if (has_base_color_texture) {
+        albedo *= texture(base_color_texture, uv);
+    }
+
+    float surface_metallic = metallic;
+    float surface_roughness = roughness;
+    if (has_metallic_roughness_texture) {
+        vec4 metallic_roughness = texture(metallic_roughness_texture, uv);
+        surface_roughness *= metallic_roughness.g;
+        surface_metallic *= metallic_roughness.b;
+    }
+    surface_roughness = clamp(surface_roughness, 0.04, 1.0);
+
+    albedo_output = vec4(albedo.rgb, 1.0);
+    normal_output = vec4(normalize(world_normal), 0.0);
+    material_output = vec4(surface_metallic, surface_roughness, 0.0, encode_shading_model(SHADING_MODEL_PBR));
+    emissive_output = vec4(emissive_color, 0.0);
+}
@@ -0,0 +1,19 @@
+#require <uniforms/color>
+#require <uniforms/lights>
+#require <functions/surface>
+#require <functions/lambert>
+#require <functions/fog>
+
+in vec3 world_position;
+in vec3 world_normal;
+in vec2 uv;
+
+out vec4 frag_color;
+
+void main() {
+    Surface surface = make_surface(world_position, world_normal);
+
+    vec3 lit = ambient_color * color.rgb + lambert(surface, light_direction, light_color, color.rgb);
+
+    frag_color = vec4(apply_fog(lit, world_position), color.a);
+}
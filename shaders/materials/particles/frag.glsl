@@ -0,0 +1,14 @@
+#require <functions/fog>
+
+in vec3 world_position;
+in vec4 tint;
+in vec2 uv;
+
+out vec4 frag_color;
+
+void main() {
+    float radius = length(uv - vec2(0.5)) * 2.0;
+    float alpha = tint.a * (1.0 - smoothstep(0.5, 1.0, radius));
+
+    frag_color = vec4(apply_fog(tint.rgb, world_position), alpha);
+}
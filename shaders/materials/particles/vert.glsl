@@ -0,0 +1,18 @@
+#require <attributes/particle>
+
+out vec3 world_position;
+out vec4 tint;
+out vec2 uv;
+
+void main() {
+    vec3 right = normalize(vec3(matrix_to_view[0][0], matrix_to_view[1][0], matrix_to_view[2][0]));
+    vec3 up = normalize(vec3(matrix_to_view[0][1], matrix_to_view[1][1], matrix_to_view[2][1]));
+    vec2 corner = texture_coordinates - vec2(0.5);
+    vec3 local_position = position + (right * corner.x + up * corner.y) * particle_size;
+
+    world_position = (matrix_to_world * vec4(local_position, 1.0)).xyz;
+    tint = particle_color;
+    uv = texture_coordinates;
+
+    gl_Position = matrix_to_projection * vec4(local_position, 1.0);
+}
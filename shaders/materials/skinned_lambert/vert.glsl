@@ -0,0 +1,17 @@
+#require <functions/skinning>
+
+out vec3 world_position;
+out vec3 world_normal;
+out vec2 uv;
+
+void main() {
+    mat4 skinning = skinning_matrix();
+    vec4 skinned_position = skinning * vec4(position, 1.0);
+    vec3 skinned_normal = mat3(skinning) * normal;
+
+    world_position = (matrix_to_world * skinned_position).xyz;
+    world_normal = mat3(transpose(matrix_to_local)) * skinned_normal;
+    uv = texture_coordinates;
+
+    gl_Position = matrix_to_projection * skinned_position;
+}
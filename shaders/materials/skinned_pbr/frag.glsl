@@ -0,0 +1,41 @@
+#require <uniforms/pbr>
+#require <uniforms/lights>
+#require <functions/surface>
+#require <functions/pbr>
+#require <functions/environment>
+#require <functions/fog>
+
+in vec3 world_position;
+in vec3 world_normal;
+in vec2 uv;
+
+out vec4 frag_color;
+
+void main() {
+    Surface surface = make_surface(world_position, world_normal);
+
+    vec4 albedo = base_color;
+    if (has_base_color_texture) {
+        albedo *= texture(base_color_texture, uv);
+    }
+
+    float surface_metallic = metallic;
+    float surface_roughness = roughness;
+    if (has_metallic_roughness_texture) {
+        vec4 metallic_roughness = texture(metallic_roughness_texture, uv);
+        surface_roughness *= metallic_roughness.g;
+        surface_metallic *= metallic_roughness.b;
+    }
+    surface_roughness = clamp(surface_roughness, 0.04, 1.0);
+
+    vec3 ambient = ambient_color * albedo.rgb;
+    if (has_environment) {
+        ambient = environment_lighting(surface, albedo.rgb, surface_metallic, surface_roughness);
+    }
+
+    vec3 lit = ambient
+        + pbr(surface, light_direction, light_color, albedo.rgb, surface_metallic, surface_roughness)
+        + emissive_color;
+
+    frag_color = vec4(apply_fog(lit, world_position), albedo.a);
+}
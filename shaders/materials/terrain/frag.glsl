@@ -0,0 +1,28 @@
+#require <uniforms/terrain>
+#require <uniforms/lights>
+#require <functions/surface>
+#require <functions/lambert>
+#require <functions/fog>
+
+in vec3 world_position;
+in vec3 world_normal;
+in vec2 uv;
+
+out vec4 frag_color;
+
+void main() {
+    vec4 weights = texture(splat_map, uv);
+    weights /= max(weights.r + weights.g + weights.b + weights.a, 0.0001);
+
+    vec2 tiled = uv * layer_scale;
+    vec3 albedo = texture(layer_0, tiled).rgb * weights.r
+        + texture(layer_1, tiled).rgb * weights.g
+        + texture(layer_2, tiled).rgb * weights.b
+        + texture(layer_3, tiled).rgb * weights.a;
+
+    Surface surface = make_surface(world_position, world_normal);
+
+    vec3 lit = ambient_color * albedo + lambert(surface, light_direction, light_color, albedo);
+
+    frag_color = vec4(apply_fog(lit, world_position), 1.0);
+}
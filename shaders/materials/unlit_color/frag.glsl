@@ -0,0 +1,10 @@
+#require <uniforms/color>
+#require <functions/fog>
+
+in vec3 world_position;
+
+out vec4 frag_color;
+
+void main() {
+    frag_color = vec4(apply_fog(color.rgb, world_position), color.a);
+}
@@ -0,0 +1,14 @@
+#require <uniforms/color>
+#require <uniforms/color_texture>
+#require <functions/fog>
+
+in vec3 world_position;
+in vec2 uv;
+
+out vec4 frag_color;
+
+void main() {
+    vec4 textured = color * texture(color_texture, uv);
+
+    frag_color = vec4(apply_fog(textured.rgb, world_position), textured.a);
+}
@@ -0,0 +1,165 @@
+use std::rc::Rc;
+
+use super::clip::AnimationClip;
+use super::skeleton::{Pose, Skeleton};
+
+#[derive(Clone, Debug)]
+pub struct AnimationState {
+    pub clip: Rc<AnimationClip>,
+    pub time: f32,
+    pub speed: f32,
+    pub looping: bool,
+    pub weight: f32,
+    target_weight: f32,
+    fade_rate: f32,
+}
+
+#[allow(dead_code)]
+impl AnimationState {
+    fn new(clip: Rc<AnimationClip>, weight: f32) -> AnimationState {
+        AnimationState {
+            clip,
+            time: 0.0,
+            speed: 1.0,
+            looping: true,
+            weight,
+            target_weight: weight,
+            fade_rate: 0.0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.time >= self.clip.duration
+    }
+
+    fn fade_to(&mut self, target_weight: f32, duration: f32) {
+        self.target_weight = target_weight;
+        self.fade_rate = if duration > 0.0 {
+            (target_weight - self.weight).abs() / duration
+        } else {
+            self.weight = target_weight;
+            0.0
+        };
+    }
+
+    fn advance(&mut self, delta_time: f32) {
+        self.time += delta_time * self.speed;
+
+        let duration = self.clip.duration;
+        if self.looping && duration > 0.0 {
+            self.time = self.time.rem_euclid(duration);
+        } else {
+            self.time = self.time.clamp(0.0, duration);
+        }
+
+        let step = self.fade_rate * delta_time;
+        if self.weight < self.target_weight {
+            self.weight = (self.weight + step).min(self.target_weight);
+        } else {
+            self.weight = (self.weight - step).max(self.target_weight);
+        }
+    }
+}
+
+pub struct Animator {
+    states: Vec<AnimationState>,
+}
+
+#[allow(dead_code)]
+impl Animator {
+    pub fn new() -> Animator {
+        Animator { states: vec![] }
+    }
+
+    pub fn play(&mut self, clip: Rc<AnimationClip>) -> &mut AnimationState {
+        self.states.clear();
+        self.states.push(AnimationState::new(clip, 1.0));
+        self.states.last_mut().unwrap()
+    }
+
+    pub fn blend(&mut self, clip: Rc<AnimationClip>, weight: f32) -> &mut AnimationState {
+        let index = match self.find(&clip) {
+            Some(index) => index,
+            None => {
+                self.states.push(AnimationState::new(clip, weight));
+                self.states.len() - 1
+            }
+        };
+
+        let state = &mut self.states[index];
+        state.fade_to(weight, 0.0);
+        state
+    }
+
+    pub fn cross_fade(&mut self, clip: Rc<AnimationClip>, duration: f32) -> &mut AnimationState {
+        for state in self.states.iter_mut() {
+            state.fade_to(0.0, duration);
+        }
+
+        let index = match self.find(&clip) {
+            Some(index) => index,
+            None => {
+                self.states.push(AnimationState::new(clip, 0.0));
+                self.states.len() - 1
+            }
+        };
+
+        let state = &mut self.states[index];
+        state.fade_to(1.0, duration);
+        state
+    }
+
+    pub fn stop(&mut self, clip: &Rc<AnimationClip>) {
+        self.states.retain(|state| !Rc::ptr_eq(&state.clip, clip));
+    }
+
+    pub fn stop_all(&mut self) {
+        self.states.clear();
+    }
+
+    pub fn get_states(&self) -> &[AnimationState] {
+        &self.states
+    }
+
+    pub fn get_state_mut(&mut self, clip: &Rc<AnimationClip>) -> Option<&mut AnimationState> {
+        let index = self.find(clip)?;
+        Some(&mut self.states[index])
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for state in self.states.iter_mut() {
+            state.advance(delta_time);
+        }
+
+        self.states
+            .retain(|state| state.weight > 0.0 || state.target_weight > 0.0);
+    }
+
+    pub fn sample(&self, skeleton: &Skeleton) -> Pose {
+        let rest = skeleton.rest_pose();
+        let mut pose = rest.clone();
+        let mut total_weight = 0.0;
+
+        for state in self.states.iter().filter(|state| state.weight > 0.0) {
+            let mut clip_pose = rest.clone();
+            state.clip.sample_into(state.time, &mut clip_pose);
+
+            total_weight += state.weight;
+            pose = pose.blend(&clip_pose, state.weight / total_weight);
+        }
+
+        pose
+    }
+
+    fn find(&self, clip: &Rc<AnimationClip>) -> Option<usize> {
+        self.states
+            .iter()
+            .position(|state| Rc::ptr_eq(&state.clip, clip))
+    }
+}
+
+impl Default for Animator {
+    fn default() -> Animator {
+        Animator::new()
+    }
+}
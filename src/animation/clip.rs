@@ -0,0 +1,82 @@
+use crate::math::*;
+
+use super::keyframe::Track;
+use super::skeleton::Pose;
+
+#[derive(Clone, Debug)]
+pub enum ChannelTrack {
+    Translation(Track<Displacement3>),
+    Rotation(Track<Quaternion>),
+    Scale(Track<Displacement3>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Channel {
+    pub joint: usize,
+    pub track: ChannelTrack,
+}
+
+#[allow(dead_code)]
+impl Channel {
+    pub fn duration(&self) -> f32 {
+        match &self.track {
+            ChannelTrack::Translation(track) => track.duration(),
+            ChannelTrack::Rotation(track) => track.duration(),
+            ChannelTrack::Scale(track) => track.duration(),
+        }
+    }
+
+    pub fn apply(&self, time: f32, pose: &mut Pose) {
+        let joint = match pose.joints.get_mut(self.joint) {
+            Some(joint) => joint,
+            None => return,
+        };
+
+        match &self.track {
+            ChannelTrack::Translation(track) => {
+                if let Some(translation) = track.sample(time) {
+                    joint.translation = translation;
+                }
+            }
+            ChannelTrack::Rotation(track) => {
+                if let Some(rotation) = track.sample(time) {
+                    joint.rotation = rotation;
+                }
+            }
+            ChannelTrack::Scale(track) => {
+                if let Some(scale) = track.sample(time) {
+                    joint.scale = scale;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    pub name: String,
+    pub duration: f32,
+    pub channels: Vec<Channel>,
+}
+
+#[allow(dead_code)]
+impl AnimationClip {
+    pub fn new(name: &str, channels: Vec<Channel>) -> AnimationClip {
+        let duration = channels
+            .iter()
+            .map(|channel| channel.duration())
+            .fold(0.0, f32::max);
+
+        AnimationClip {
+            name: name.to_string(),
+            duration,
+            channels,
+        }
+    }
+
+    pub fn sample_into(&self, time: f32, pose: &mut Pose) {
+        for channel in self.channels.iter() {
+            channel.apply(time, pose);
+        }
+    }
+}
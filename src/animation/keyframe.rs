@@ -0,0 +1,326 @@
+use crate::math::*;
+
+use super::easing::Easing;
+
+pub trait Interpolate: Copy {
+    fn interpolate(self, other: Self, t: f32) -> Self;
+
+    /// Cubic Hermite spline from `self` to `other`, with tangents per second
+    /// scaled by the `duration` of the segment, as glTF's CUBICSPLINE.
+    fn hermite(
+        self,
+        out_tangent: Self,
+        other: Self,
+        in_tangent: Self,
+        t: f32,
+        duration: f32,
+    ) -> Self;
+}
+
+fn hermite_weights(t: f32) -> [f32; 4] {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    [
+        2.0 * t3 - 3.0 * t2 + 1.0,
+        t3 - 2.0 * t2 + t,
+        -2.0 * t3 + 3.0 * t2,
+        t3 - t2,
+    ]
+}
+
+impl Interpolate for f32 {
+    fn interpolate(self, other: f32, t: f32) -> f32 {
+        self + (other - self) * t
+    }
+
+    fn hermite(self, out_tangent: f32, other: f32, in_tangent: f32, t: f32, duration: f32) -> f32 {
+        let [from, leaving, to, arriving] = hermite_weights(t);
+
+        self * from
+            + out_tangent * duration * leaving
+            + other * to
+            + in_tangent * duration * arriving
+    }
+}
+
+impl Interpolate for Displacement3 {
+    fn interpolate(self, other: Displacement3, t: f32) -> Displacement3 {
+        self * (1.0 - t) + other * t
+    }
+
+    fn hermite(
+        self,
+        out_tangent: Displacement3,
+        other: Displacement3,
+        in_tangent: Displacement3,
+        t: f32,
+        duration: f32,
+    ) -> Displacement3 {
+        let [from, leaving, to, arriving] = hermite_weights(t);
+
+        self * from
+            + out_tangent * (duration * leaving)
+            + other * to
+            + in_tangent * (duration * arriving)
+    }
+}
+
+impl Interpolate for Position3 {
+    fn interpolate(self, other: Position3, t: f32) -> Position3 {
+        self + (other - self) * t
+    }
+
+    fn hermite(
+        self,
+        out_tangent: Position3,
+        other: Position3,
+        in_tangent: Position3,
+        t: f32,
+        duration: f32,
+    ) -> Position3 {
+        ORIGIN
+            + (self - ORIGIN).hermite(
+                out_tangent - ORIGIN,
+                other - ORIGIN,
+                in_tangent - ORIGIN,
+                t,
+                duration,
+            )
+    }
+}
+
+impl Interpolate for Quaternion {
+    fn interpolate(self, other: Quaternion, t: f32) -> Quaternion {
+        self.slerp(other, t)
+    }
+
+    /// Splines each component and normalizes the result, as glTF does.
+    fn hermite(
+        self,
+        out_tangent: Quaternion,
+        other: Quaternion,
+        in_tangent: Quaternion,
+        t: f32,
+        duration: f32,
+    ) -> Quaternion {
+        let component = |get: fn(&Quaternion) -> f32| {
+            get(&self).hermite(
+                get(&out_tangent),
+                get(&other),
+                get(&in_tangent),
+                t,
+                duration,
+            )
+        };
+
+        Quaternion::new(
+            component(|q| q.x),
+            component(|q| q.y),
+            component(|q| q.z),
+            component(|q| q.w),
+        )
+        .normalized()
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(self, other: Color, t: f32) -> Color {
+        rgba(
+            self.r.interpolate(other.r, t),
+            self.g.interpolate(other.g, t),
+            self.b.interpolate(other.b, t),
+            self.a.interpolate(other.a, t),
+        )
+    }
+
+    fn hermite(
+        self,
+        out_tangent: Color,
+        other: Color,
+        in_tangent: Color,
+        t: f32,
+        duration: f32,
+    ) -> Color {
+        let component = |get: fn(&Color) -> f32| {
+            get(&self).hermite(
+                get(&out_tangent),
+                get(&other),
+                get(&in_tangent),
+                t,
+                duration,
+            )
+        };
+
+        rgba(
+            component(|c| c.r),
+            component(|c| c.g),
+            component(|c| c.b),
+            component(|c| c.a),
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Interpolation {
+    Step,
+    Linear,
+    /// Catmull-Rom through the neighbouring keyframes.
+    Cubic,
+    /// Hermite spline through each keyframe's own tangents. Segments whose
+    /// keyframes have no tangents are interpolated linearly.
+    CubicSpline,
+    Eased(Easing),
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Keyframe<T: Interpolate> {
+    pub time: f32,
+    pub value: T,
+    /// In and out tangents, used by `Interpolation::CubicSpline`.
+    pub tangents: Option<(T, T)>,
+}
+
+impl<T: Interpolate> Keyframe<T> {
+    pub fn new(time: f32, value: T) -> Keyframe<T> {
+        Keyframe {
+            time,
+            value,
+            tangents: None,
+        }
+    }
+
+    pub fn with_tangents(mut self, in_tangent: T, out_tangent: T) -> Keyframe<T> {
+        self.tangents = Some((in_tangent, out_tangent));
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Track<T: Interpolate> {
+    keyframes: Vec<Keyframe<T>>,
+    pub interpolation: Interpolation,
+}
+
+#[allow(dead_code)]
+impl<T: Interpolate> Track<T> {
+    pub fn new(interpolation: Interpolation) -> Track<T> {
+        Track {
+            keyframes: vec![],
+            interpolation,
+        }
+    }
+
+    pub fn constant(value: T) -> Track<T> {
+        Track {
+            keyframes: vec![Keyframe::new(0.0, value)],
+            interpolation: Interpolation::Step,
+        }
+    }
+
+    pub fn from_keyframes(
+        mut keyframes: Vec<Keyframe<T>>,
+        interpolation: Interpolation,
+    ) -> Track<T> {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        Track {
+            keyframes,
+            interpolation,
+        }
+    }
+
+    pub fn add(&mut self, time: f32, value: T) -> &mut Self {
+        let index = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time)
+            .unwrap_or(self.keyframes.len());
+        self.keyframes.insert(index, Keyframe::new(time, value));
+        self
+    }
+
+    pub fn get_keyframes(&self) -> &[Keyframe<T>] {
+        &self.keyframes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+
+        if time <= first.time {
+            return Some(first.value);
+        }
+        if time >= last.time {
+            return Some(last.value);
+        }
+
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time)
+            .unwrap();
+        let from = self.keyframes[next - 1];
+        let to = self.keyframes[next];
+        let t = (time - from.time) / (to.time - from.time);
+
+        match self.interpolation {
+            Interpolation::Step => Some(from.value),
+            Interpolation::Linear => Some(from.value.interpolate(to.value, t)),
+            Interpolation::Cubic => Some(self.sample_cubic(next, time)),
+            Interpolation::CubicSpline => match (from.tangents, to.tangents) {
+                (Some((_, out_tangent)), Some((in_tangent, _))) => Some(from.value.hermite(
+                    out_tangent,
+                    to.value,
+                    in_tangent,
+                    t,
+                    to.time - from.time,
+                )),
+                _ => Some(from.value.interpolate(to.value, t)),
+            },
+            Interpolation::Eased(easing) => Some(from.value.interpolate(to.value, easing.apply(t))),
+        }
+    }
+
+    fn sample_cubic(&self, next: usize, time: f32) -> T {
+        let from = self.keyframes[next - 1];
+        let to = self.keyframes[next];
+        let before = if next >= 2 {
+            self.keyframes[next - 2]
+        } else {
+            Keyframe::new(2.0 * from.time - to.time, from.value)
+        };
+        let after = self
+            .keyframes
+            .get(next + 1)
+            .copied()
+            .unwrap_or_else(|| Keyframe::new(2.0 * to.time - from.time, to.value));
+
+        let ratio = |start: f32, end: f32| {
+            if end > start {
+                (time - start) / (end - start)
+            } else {
+                1.0
+            }
+        };
+        let a1 = before
+            .value
+            .interpolate(from.value, ratio(before.time, from.time));
+        let a2 = from.value.interpolate(to.value, ratio(from.time, to.time));
+        let a3 = to
+            .value
+            .interpolate(after.value, ratio(to.time, after.time));
+        let b1 = a1.interpolate(a2, ratio(before.time, to.time));
+        let b2 = a2.interpolate(a3, ratio(from.time, after.time));
+
+        b1.interpolate(b2, ratio(from.time, to.time))
+    }
+}
@@ -0,0 +1,15 @@
+pub mod animator;
+pub mod clip;
+pub mod easing;
+pub mod keyframe;
+pub mod property;
+pub mod skeleton;
+pub mod tween;
+
+pub use animator::{AnimationState, Animator};
+pub use clip::{AnimationClip, Channel, ChannelTrack};
+pub use easing::Easing;
+pub use keyframe::{Interpolate, Interpolation, Keyframe, Track};
+pub use property::{PlaybackMode, PropertyAnimation, PropertyTrack};
+pub use skeleton::{Joint, JointTransform, Pose, Skeleton};
+pub use tween::{tween, Tween, TweenTarget};
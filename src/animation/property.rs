@@ -0,0 +1,318 @@
+use std::collections::BTreeMap;
+
+use crate::math::*;
+use crate::rendering::scene::Transformable;
+use crate::rendering::{Behaviour, BehaviourContext, ParameterMaterial, SharedParameter};
+
+use super::keyframe::Track;
+use super::skeleton::JointTransform;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlaybackMode {
+    Once,
+    Loop,
+    PingPong,
+}
+
+impl PlaybackMode {
+    pub fn wrap(self, time: f32, duration: f32) -> f32 {
+        if duration <= 0.0 {
+            return 0.0;
+        }
+
+        match self {
+            PlaybackMode::Once => time.clamp(0.0, duration),
+            PlaybackMode::Loop => time.rem_euclid(duration),
+            PlaybackMode::PingPong => {
+                let time = time.rem_euclid(2.0 * duration);
+                if time > duration {
+                    2.0 * duration - time
+                } else {
+                    time
+                }
+            }
+        }
+    }
+
+    pub(crate) fn period(self, duration: f32) -> f32 {
+        match self {
+            PlaybackMode::PingPong => 2.0 * duration,
+            _ => duration,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum PropertyTrack {
+    Translation(Track<Displacement3>),
+    Rotation(Track<Quaternion>),
+    Scale(Track<Displacement3>),
+    Color(String, Track<Color>),
+    Float(String, Track<f32>),
+}
+
+impl PropertyTrack {
+    pub fn duration(&self) -> f32 {
+        match self {
+            PropertyTrack::Translation(track) => track.duration(),
+            PropertyTrack::Rotation(track) => track.duration(),
+            PropertyTrack::Scale(track) => track.duration(),
+            PropertyTrack::Color(_, track) => track.duration(),
+            PropertyTrack::Float(_, track) => track.duration(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PropertyAnimation {
+    pub name: String,
+    pub tracks: Vec<PropertyTrack>,
+    pub mode: PlaybackMode,
+    pub speed: f32,
+    pub playing: bool,
+    colors: BTreeMap<String, SharedParameter<Color>>,
+    floats: BTreeMap<String, SharedParameter<f32>>,
+    duration: f32,
+    time: f32,
+}
+
+impl PropertyAnimation {
+    pub fn new(name: &str) -> PropertyAnimation {
+        PropertyAnimation {
+            name: name.to_string(),
+            tracks: vec![],
+            mode: PlaybackMode::Once,
+            speed: 1.0,
+            playing: true,
+            colors: BTreeMap::new(),
+            floats: BTreeMap::new(),
+            duration: 0.0,
+            time: 0.0,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: PlaybackMode) -> PropertyAnimation {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_track(mut self, track: PropertyTrack) -> PropertyAnimation {
+        self.add_track(track);
+        self
+    }
+
+    pub fn add_track(&mut self, track: PropertyTrack) {
+        self.duration = self.duration.max(track.duration());
+        self.tracks.push(track);
+    }
+
+    pub fn with_material(mut self, material: &mut ParameterMaterial) -> PropertyAnimation {
+        self.bind_material(material);
+        self
+    }
+
+    /// Binds the color and float tracks to the material's parameters of the
+    /// same name, sharing them so the animation keeps writing to them after
+    /// the material is put behind an `Rc`. Parameters the material does not
+    /// have yet start at the track's first value.
+    pub fn bind_material(&mut self, material: &mut ParameterMaterial) {
+        for track in self.tracks.iter() {
+            match track {
+                PropertyTrack::Color(name, track) => {
+                    if material.get(name).is_none() {
+                        if let Some(color) = track.sample(0.0) {
+                            material.set(name, color);
+                        }
+                    }
+                    if let Some(shared) = material.share_color(name) {
+                        self.colors.insert(name.clone(), shared);
+                    }
+                }
+                PropertyTrack::Float(name, track) => {
+                    if material.get(name).is_none() {
+                        if let Some(value) = track.sample(0.0) {
+                            material.set(name, value);
+                        }
+                    }
+                    if let Some(shared) = material.share_float(name) {
+                        self.floats.insert(name.clone(), shared);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn bind_color(&mut self, name: &str, parameter: SharedParameter<Color>) {
+        self.colors.insert(name.to_string(), parameter);
+    }
+
+    pub fn bind_float(&mut self, name: &str, parameter: SharedParameter<f32>) {
+        self.floats.insert(name.to_string(), parameter);
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub fn get_time(&self) -> f32 {
+        self.mode.wrap(self.time, self.duration)
+    }
+
+    pub fn seek(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.mode == PlaybackMode::Once && self.time >= self.duration
+    }
+
+    pub fn advance(&mut self, delta_time: f32) {
+        if !self.playing {
+            return;
+        }
+
+        self.time += delta_time * self.speed;
+        self.time = match self.mode {
+            PlaybackMode::Once => self.time.clamp(0.0, self.duration),
+            mode if self.duration > 0.0 => self.time.rem_euclid(mode.period(self.duration)),
+            _ => 0.0,
+        };
+    }
+
+    /// Overwrites the components of `current` that have a track, so a
+    /// rotation-only animation keeps the target where it is.
+    pub fn sample_transform(&self, current: &Transform) -> Option<JointTransform> {
+        let time = self.get_time();
+        let mut transform = JointTransform::from_transform(current);
+        let mut animated = false;
+
+        for track in self.tracks.iter() {
+            match track {
+                PropertyTrack::Translation(track) => {
+                    if let Some(translation) = track.sample(time) {
+                        transform.translation = translation;
+                        animated = true;
+                    }
+                }
+                PropertyTrack::Rotation(track) => {
+                    if let Some(rotation) = track.sample(time) {
+                        transform.rotation = rotation;
+                        animated = true;
+                    }
+                }
+                PropertyTrack::Scale(track) => {
+                    if let Some(scale) = track.sample(time) {
+                        transform.scale = scale;
+                        animated = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if animated {
+            Some(transform)
+        } else {
+            None
+        }
+    }
+
+    pub fn apply_transform(&self, target: &mut dyn Transformable) {
+        if let Some(transform) = self.sample_transform(&target.get_transform()) {
+            target.set_transform(transform.to_transform());
+        }
+    }
+
+    /// Writes the color and float tracks to the parameters they are bound to.
+    pub fn apply_parameters(&self) {
+        let time = self.get_time();
+
+        for track in self.tracks.iter() {
+            match track {
+                PropertyTrack::Color(name, track) => {
+                    if let (Some(parameter), Some(color)) =
+                        (self.colors.get(name), track.sample(time))
+                    {
+                        parameter.set(color);
+                    }
+                }
+                PropertyTrack::Float(name, track) => {
+                    if let (Some(parameter), Some(value)) =
+                        (self.floats.get(name), track.sample(time))
+                    {
+                        parameter.set(value);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Behaviour for PropertyAnimation {
+    fn update(&mut self, context: &mut BehaviourContext, delta_time: f32) {
+        self.advance(delta_time);
+        let sampled = context
+            .get_transform()
+            .and_then(|current| self.sample_transform(&current));
+        if let Some(transform) = sampled {
+            context.set_transform(transform.to_transform());
+        }
+        self.apply_parameters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::super::keyframe::Interpolation;
+    use super::*;
+    use crate::rendering::{Node, Parameter, SceneGraph};
+
+    #[test]
+    fn applies_parameter_tracks_from_the_update_loop() {
+        let mut fade = Track::new(Interpolation::Linear);
+        fade.add(0.0, 1.0).add(1.0, 0.0);
+        let mut material = ParameterMaterial::named("lambert");
+        let animation = PropertyAnimation::new("fade")
+            .with_track(PropertyTrack::Float("opacity".to_string(), fade))
+            .with_material(&mut material);
+        let material = Rc::new(material);
+
+        let mut graph = SceneGraph::new();
+        graph.spawn(Node::new("panel").with_behaviour(Box::new(animation)));
+
+        graph.update(0.25);
+
+        match material.get("opacity") {
+            Some(Parameter::SharedFloat(opacity)) => assert_eq!(opacity.get(), 0.75),
+            _ => panic!("opacity is not shared"),
+        }
+    }
+
+    #[test]
+    fn keeps_components_without_a_track() {
+        let half_turn = Quaternion::from_axis_angle(UP, std::f32::consts::PI);
+        let mut rotation = Track::new(Interpolation::Linear);
+        rotation.add(0.0, IDENTITY_ROTATION).add(1.0, half_turn);
+        let animation =
+            PropertyAnimation::new("spin").with_track(PropertyTrack::Rotation(rotation));
+
+        let mut graph = SceneGraph::new();
+        let id = graph.spawn(
+            Node::new("door")
+                .with_transform(Transform::translation(3.0, 0.0, 0.0))
+                .with_behaviour(Box::new(animation)),
+        );
+
+        graph.update(0.5);
+
+        let transform = graph.get_transform(id).unwrap();
+        let position = (transform * ORIGIN).vector;
+        assert!((position.x - 3.0).abs() < 1e-5 && position.y.abs() < 1e-5);
+        let turned = (transform * (ORIGIN + dis3(1.0, 0.0, 0.0))).vector;
+        assert!((turned.x - 3.0).abs() < 1e-5 && (turned.z.abs() - 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::math::raw::matrix::Matrix4;
+use crate::math::raw::vector::Vector3;
+use crate::math::*;
+
+use super::keyframe::Interpolate;
+
+#[derive(Copy, Clone, Debug)]
+pub struct JointTransform {
+    pub translation: Displacement3,
+    pub rotation: Quaternion,
+    pub scale: Displacement3,
+}
+
+pub const IDENTITY_JOINT: JointTransform = JointTransform {
+    translation: Displacement3 {
+        vector: Vector3 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        },
+    },
+    rotation: IDENTITY_ROTATION,
+    scale: Displacement3 {
+        vector: Vector3 {
+            x: 1.0,
+            y: 1.0,
+            z: 1.0,
+        },
+    },
+};
+
+#[allow(dead_code)]
+impl JointTransform {
+    pub fn new(
+        translation: Displacement3,
+        rotation: Quaternion,
+        scale: Displacement3,
+    ) -> JointTransform {
+        JointTransform {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn to_transform(&self) -> Transform {
+        Transform::from_translation_rotation_scale(self.translation, self.rotation, self.scale)
+    }
+
+    /// Splits a transform without shear back into its parts. A mirrored
+    /// transform comes back with a negative X scale.
+    pub fn from_transform(transform: &Transform) -> JointTransform {
+        let elements = transform.matrix.elements;
+        let column = |index: usize| Vector3 {
+            x: elements[index][0],
+            y: elements[index][1],
+            z: elements[index][2],
+        };
+        let (x, y, z) = (column(0), column(1), column(2));
+
+        let mut scale = Vector3 {
+            x: x.length(),
+            y: y.length(),
+            z: z.length(),
+        };
+        let determinant = x.x * (y.y * z.z - y.z * z.y) - y.x * (x.y * z.z - x.z * z.y)
+            + z.x * (x.y * y.z - x.z * y.y);
+        if determinant < 0.0 {
+            scale.x = -scale.x;
+        }
+
+        let mut rotation = crate::math::raw::matrix::IDENTITY;
+        for (index, (axis, length)) in [(x, scale.x), (y, scale.y), (z, scale.z)]
+            .iter()
+            .enumerate()
+        {
+            if *length != 0.0 {
+                rotation.elements[index][0] = axis.x / length;
+                rotation.elements[index][1] = axis.y / length;
+                rotation.elements[index][2] = axis.z / length;
+            }
+        }
+
+        JointTransform {
+            translation: Displacement3 {
+                vector: Vector3 {
+                    x: elements[3][0],
+                    y: elements[3][1],
+                    z: elements[3][2],
+                },
+            },
+            rotation: Quaternion::from_matrix(&rotation),
+            scale: Displacement3 { vector: scale },
+        }
+    }
+}
+
+impl Interpolate for JointTransform {
+    fn interpolate(self, other: JointTransform, t: f32) -> JointTransform {
+        JointTransform {
+            translation: self.translation.interpolate(other.translation, t),
+            rotation: self.rotation.nlerp(other.rotation, t),
+            scale: self.scale.interpolate(other.scale, t),
+        }
+    }
+
+    fn hermite(
+        self,
+        out_tangent: JointTransform,
+        other: JointTransform,
+        in_tangent: JointTransform,
+        t: f32,
+        duration: f32,
+    ) -> JointTransform {
+        JointTransform {
+            translation: self.translation.hermite(
+                out_tangent.translation,
+                other.translation,
+                in_tangent.translation,
+                t,
+                duration,
+            ),
+            rotation: self.rotation.hermite(
+                out_tangent.rotation,
+                other.rotation,
+                in_tangent.rotation,
+                t,
+                duration,
+            ),
+            scale: self.scale.hermite(
+                out_tangent.scale,
+                other.scale,
+                in_tangent.scale,
+                t,
+                duration,
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Joint {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub rest: JointTransform,
+    pub inverse_bind: Matrix4,
+}
+
+#[derive(Clone, Debug)]
+pub struct Skeleton {
+    joints: Vec<Joint>,
+}
+
+#[allow(dead_code)]
+impl Skeleton {
+    pub fn new(joints: Vec<Joint>) -> Skeleton {
+        for (index, joint) in joints.iter().enumerate() {
+            if let Some(parent) = joint.parent {
+                assert!(parent < index, "joint parents must precede their children");
+            }
+        }
+
+        Skeleton { joints }
+    }
+
+    pub fn get_joints(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    pub fn joint_count(&self) -> usize {
+        self.joints.len()
+    }
+
+    pub fn find_joint(&self, name: &str) -> Option<usize> {
+        self.joints.iter().position(|joint| joint.name == name)
+    }
+
+    pub fn rest_pose(&self) -> Pose {
+        Pose {
+            joints: self.joints.iter().map(|joint| joint.rest).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Pose {
+    pub joints: Vec<JointTransform>,
+}
+
+#[allow(dead_code)]
+impl Pose {
+    pub fn blend(&self, other: &Pose, weight: f32) -> Pose {
+        let joints = self
+            .joints
+            .iter()
+            .zip(other.joints.iter())
+            .map(|(&a, &b)| a.interpolate(b, weight))
+            .collect();
+
+        Pose { joints }
+    }
+
+    pub fn world_transforms(&self, skeleton: &Skeleton) -> Vec<Transform> {
+        let mut world: Vec<Transform> = Vec::with_capacity(self.joints.len());
+
+        for (joint, local) in skeleton.joints.iter().zip(self.joints.iter()) {
+            let local = local.to_transform();
+            let transform = match joint.parent {
+                Some(parent) => world[parent] * local,
+                None => local,
+            };
+            world.push(transform);
+        }
+
+        world
+    }
+
+    pub fn joint_matrices(&self, skeleton: &Skeleton) -> Vec<Matrix4> {
+        self.world_transforms(skeleton)
+            .iter()
+            .zip(skeleton.joints.iter())
+            .map(|(world, joint)| world.matrix * joint.inverse_bind)
+            .collect()
+    }
+}
@@ -0,0 +1,177 @@
+use crate::rendering::{Behaviour, BehaviourContext, NodeId, SharedParameter};
+
+use super::easing::Easing;
+use super::keyframe::Interpolate;
+use super::property::PlaybackMode;
+use super::skeleton::JointTransform;
+
+/// Something a tween can read its start value from and write to.
+pub trait TweenTarget {
+    type Value: Interpolate;
+
+    fn read(&self, context: &BehaviourContext) -> Option<Self::Value>;
+
+    fn write(&self, context: &mut BehaviourContext, value: Self::Value);
+}
+
+impl TweenTarget for NodeId {
+    type Value = JointTransform;
+
+    fn read(&self, context: &BehaviourContext) -> Option<JointTransform> {
+        let transform = context.get_graph().get_transform(*self)?;
+
+        Some(JointTransform::from_transform(&transform))
+    }
+
+    fn write(&self, context: &mut BehaviourContext, value: JointTransform) {
+        context
+            .get_graph_mut()
+            .set_transform(*self, value.to_transform());
+    }
+}
+
+impl<T: Interpolate> TweenTarget for SharedParameter<T> {
+    type Value = T;
+
+    fn read(&self, _context: &BehaviourContext) -> Option<T> {
+        Some(self.get())
+    }
+
+    fn write(&self, _context: &mut BehaviourContext, value: T) {
+        self.set(value);
+    }
+}
+
+/// Animates a target from its current value to `to`. The start value is read
+/// from the target when the tween starts, so it has no value before that.
+#[derive(Clone, Debug)]
+pub struct Tween<B: TweenTarget> {
+    pub target: B,
+    pub to: B::Value,
+    pub duration: f32,
+    pub easing: Easing,
+    pub mode: PlaybackMode,
+    from: Option<B::Value>,
+    time: f32,
+}
+
+impl<B: TweenTarget> Tween<B> {
+    pub fn new(target: B, to: B::Value, duration: f32, easing: Easing) -> Tween<B> {
+        Tween {
+            target,
+            to,
+            duration,
+            easing,
+            mode: PlaybackMode::Once,
+            from: None,
+            time: 0.0,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: PlaybackMode) -> Tween<B> {
+        self.mode = mode;
+        self
+    }
+
+    pub fn get_from(&self) -> Option<B::Value> {
+        self.from
+    }
+
+    pub fn get_time(&self) -> f32 {
+        self.mode.wrap(self.time, self.duration)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.mode == PlaybackMode::Once && self.time >= self.duration
+    }
+
+    pub fn value(&self) -> Option<B::Value> {
+        let t = if self.duration > 0.0 {
+            self.get_time() / self.duration
+        } else {
+            1.0
+        };
+
+        Some(self.from?.interpolate(self.to, self.easing.apply(t)))
+    }
+
+    pub fn advance(&mut self, delta_time: f32) -> Option<B::Value> {
+        self.time += delta_time;
+        if self.mode != PlaybackMode::Once && self.duration > 0.0 {
+            self.time = self.time.rem_euclid(self.mode.period(self.duration));
+        } else {
+            self.time = self.time.min(self.duration.max(0.0));
+        }
+
+        self.value()
+    }
+}
+
+impl<B: TweenTarget> Behaviour for Tween<B> {
+    fn start(&mut self, context: &mut BehaviourContext) {
+        self.from = self.target.read(context);
+        self.time = 0.0;
+    }
+
+    fn update(&mut self, context: &mut BehaviourContext, delta_time: f32) {
+        if let Some(value) = self.advance(delta_time) {
+            self.target.write(context, value);
+        }
+    }
+}
+
+pub fn tween<B: TweenTarget>(target: B, to: B::Value, duration: f32, easing: Easing) -> Tween<B> {
+    Tween::new(target, to, duration, easing)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::math::*;
+    use crate::rendering::{Node, Parameter, ParameterMaterial, SceneGraph};
+
+    #[test]
+    fn starts_from_the_current_node_transform() {
+        let mut graph = SceneGraph::new();
+        let id =
+            graph.spawn(Node::new("door").with_transform(Transform::translation(1.0, 0.0, 0.0)));
+        let to = JointTransform::new(dis3(3.0, 0.0, 0.0), IDENTITY_ROTATION, dis3(1.0, 1.0, 1.0));
+        graph
+            .get_mut(id)
+            .unwrap()
+            .add_behaviour(Box::new(tween(id, to, 1.0, Easing::Linear)));
+
+        graph.update(0.5);
+
+        let position = graph.get_transform(id).unwrap() * ORIGIN;
+        assert!((position.vector.x - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn drives_shared_material_parameters() {
+        let mut material =
+            ParameterMaterial::named("lambert").with("color", rgba(0.0, 0.0, 0.0, 1.0));
+        let color = material.share_color("color").unwrap();
+        let material = Rc::new(material);
+
+        let mut graph = SceneGraph::new();
+        graph.spawn(Node::new("panel").with_behaviour(Box::new(tween(
+            color,
+            rgba(1.0, 0.5, 0.0, 1.0),
+            2.0,
+            Easing::Linear,
+        ))));
+
+        graph.update(1.0);
+
+        match material.get("color") {
+            Some(Parameter::SharedColor(color)) => {
+                let color = color.get();
+                assert_eq!([color.r, color.g, color.b, color.a], [0.5, 0.25, 0.0, 1.0]);
+            }
+            _ => panic!("color is not shared"),
+        }
+    }
+}
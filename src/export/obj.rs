@@ -0,0 +1,92 @@
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use super::super::geometry::Geometry;
+
+impl Geometry {
+    pub fn write_obj<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for vertex in self.vertices.iter() {
+            let [x, y, z] = vertex.position.vector.as_array();
+            writeln!(writer, "v {} {} {}", x, y, z)?;
+        }
+        for vertex in self.vertices.iter() {
+            let [u, v] = vertex.texture_coordinates.vector.as_array();
+            writeln!(writer, "vt {} {}", u, v)?;
+        }
+        for vertex in self.vertices.iter() {
+            let [x, y, z] = vertex.normal.vector.as_array();
+            writeln!(writer, "vn {} {} {}", x, y, z)?;
+        }
+        for triangle in self.indices.chunks_exact(3) {
+            let (a, b, c) = (triangle[0] + 1, triangle[1] + 1, triangle[2] + 1);
+            writeln!(writer, "f {0}/{0}/{0} {1}/{1}/{1} {2}/{2}/{2}", a, b, c)?;
+        }
+
+        writer.flush()
+    }
+
+    pub fn save_obj<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_obj(BufWriter::new(File::create(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::geometry::Vertex;
+    use super::super::super::math::*;
+    use super::*;
+
+    #[test]
+    fn round_trips_through_from_obj() {
+        let geometry = Geometry {
+            vertices: vec![
+                Vertex::new(pos3(0.0, 0.0, 0.0), BACKWARDS, pos2(0.0, 0.25)),
+                Vertex::new(pos3(1.0, 0.0, 0.0), BACKWARDS, pos2(1.0, 0.25)),
+                Vertex::new(pos3(0.0, 1.0, 0.0), BACKWARDS, pos2(0.0, 1.0)),
+            ],
+            indices: vec![0, 1, 2],
+        };
+
+        let path = std::env::temp_dir().join(format!("glib-export-{}.obj", std::process::id()));
+        geometry.save_obj(&path).unwrap();
+        let groups = Geometry::from_obj(&path);
+        std::fs::remove_file(&path).unwrap();
+        let groups = groups.unwrap();
+
+        assert_eq!(groups.len(), 1);
+        let loaded = &groups[0].geometry;
+        assert_eq!(loaded.indices, geometry.indices);
+        for (loaded, vertex) in loaded.vertices.iter().zip(geometry.vertices.iter()) {
+            assert_eq!(
+                loaded.position.vector.as_array(),
+                vertex.position.vector.as_array()
+            );
+            assert_eq!(
+                loaded.normal.vector.as_array(),
+                vertex.normal.vector.as_array()
+            );
+            assert_eq!(
+                loaded.texture_coordinates.vector.as_array(),
+                vertex.texture_coordinates.vector.as_array()
+            );
+        }
+    }
+
+    #[test]
+    fn writes_texture_coordinates_bottom_up() {
+        let geometry = Geometry {
+            vertices: vec![Vertex::new(pos3(0.0, 0.0, 0.0), BACKWARDS, pos2(0.5, 0.25))],
+            indices: vec![],
+        };
+
+        let mut bytes = vec![];
+        geometry.write_obj(&mut bytes).unwrap();
+
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            "v 0 0 0\nvt 0.5 0.25\nvn 0 0 1\n"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use super::super::geometry::Geometry;
+
+fn write_header<W: Write>(geometry: &Geometry, writer: &mut W, format: &str) -> io::Result<()> {
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format {} 1.0", format)?;
+    writeln!(writer, "element vertex {}", geometry.vertices.len())?;
+    for property in ["x", "y", "z", "nx", "ny", "nz", "s", "t"].iter() {
+        writeln!(writer, "property float {}", property)?;
+    }
+    writeln!(writer, "element face {}", geometry.indices.len() / 3)?;
+    writeln!(writer, "property list uchar uint vertex_indices")?;
+    writeln!(writer, "end_header")
+}
+
+fn vertex_values(geometry: &Geometry) -> impl Iterator<Item = [f32; 8]> + '_ {
+    geometry.vertices.iter().map(|vertex| {
+        let [x, y, z] = vertex.position.vector.as_array();
+        let [nx, ny, nz] = vertex.normal.vector.as_array();
+        let [s, t] = vertex.texture_coordinates.vector.as_array();
+
+        [x, y, z, nx, ny, nz, s, t]
+    })
+}
+
+impl Geometry {
+    pub fn write_ply_ascii<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write_header(self, &mut writer, "ascii")?;
+        for values in vertex_values(self) {
+            let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+            writeln!(writer, "{}", values.join(" "))?;
+        }
+        for triangle in self.indices.chunks_exact(3) {
+            writeln!(writer, "3 {} {} {}", triangle[0], triangle[1], triangle[2])?;
+        }
+
+        writer.flush()
+    }
+
+    pub fn write_ply_binary<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write_header(self, &mut writer, "binary_little_endian")?;
+        for values in vertex_values(self) {
+            for value in values.iter() {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+        for triangle in self.indices.chunks_exact(3) {
+            writer.write_all(&[3])?;
+            for index in triangle {
+                writer.write_all(&index.to_le_bytes())?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    pub fn save_ply_ascii<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_ply_ascii(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn save_ply_binary<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_ply_binary(BufWriter::new(File::create(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::geometry::Vertex;
+    use super::super::super::math::*;
+    use super::*;
+
+    const HEADER: &str = "element vertex 3\n\
+                          property float x\n\
+                          property float y\n\
+                          property float z\n\
+                          property float nx\n\
+                          property float ny\n\
+                          property float nz\n\
+                          property float s\n\
+                          property float t\n\
+                          element face 1\n\
+                          property list uchar uint vertex_indices\n\
+                          end_header\n";
+
+    fn triangle() -> Geometry {
+        Geometry {
+            vertices: vec![
+                Vertex::new(pos3(0.0, 0.0, 0.0), BACKWARDS, pos2(0.0, 0.0)),
+                Vertex::new(pos3(1.0, 0.0, 0.0), BACKWARDS, pos2(1.0, 0.0)),
+                Vertex::new(pos3(0.0, 1.0, 0.0), BACKWARDS, pos2(0.0, 1.0)),
+            ],
+            indices: vec![0, 1, 2],
+        }
+    }
+
+    #[test]
+    fn writes_ascii() {
+        let mut bytes = vec![];
+        triangle().write_ply_ascii(&mut bytes).unwrap();
+
+        let expected = format!(
+            "ply\nformat ascii 1.0\n{}\
+             0 0 0 0 0 1 0 0\n\
+             1 0 0 0 0 1 1 0\n\
+             0 1 0 0 0 1 0 1\n\
+             3 0 1 2\n",
+            HEADER
+        );
+        assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+    }
+
+    #[test]
+    fn writes_binary_little_endian() {
+        let mut bytes = vec![];
+        triangle().write_ply_binary(&mut bytes).unwrap();
+
+        let mut expected = format!("ply\nformat binary_little_endian 1.0\n{}", HEADER).into_bytes();
+        #[rustfmt::skip]
+        let body: &[u8] = &[
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 128, 63,
+            0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 128, 63, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 128, 63,
+            0, 0, 128, 63, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 128, 63, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 128, 63,
+            0, 0, 0, 0, 0, 0, 128, 63,
+            3, 0, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0,
+        ];
+        expected.extend_from_slice(body);
+        assert_eq!(bytes, expected);
+    }
+}
@@ -0,0 +1,90 @@
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use super::super::geometry::Geometry;
+
+impl Geometry {
+    pub fn write_stl<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let triangles = self.indices.len() / 3;
+        if triangles > u32::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "too many triangles for STL".to_string(),
+            ));
+        }
+
+        writer.write_all(&[0; 80])?;
+        writer.write_all(&(triangles as u32).to_le_bytes())?;
+
+        for triangle in self.indices.chunks_exact(3) {
+            let a = self.vertices[triangle[0] as usize].position;
+            let b = self.vertices[triangle[1] as usize].position;
+            let c = self.vertices[triangle[2] as usize].position;
+            let (u, v) = ((b - a).vector, (c - a).vector);
+            let normal = [
+                u.y * v.z - u.z * v.y,
+                u.z * v.x - u.x * v.z,
+                u.x * v.y - u.y * v.x,
+            ];
+            let length = normal.iter().map(|n| n * n).sum::<f32>().sqrt();
+            let normal = if length > 0.0 {
+                normal.map(|n| n / length)
+            } else {
+                [0.0; 3]
+            };
+
+            for value in normal.iter() {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+            for point in [a, b, c].iter() {
+                for value in point.vector.as_array().iter() {
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            }
+            writer.write_all(&[0; 2])?;
+        }
+
+        writer.flush()
+    }
+
+    pub fn save_stl<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_stl(BufWriter::new(File::create(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::geometry::Vertex;
+    use super::super::super::math::*;
+    use super::*;
+
+    #[test]
+    fn writes_binary_triangles() {
+        let geometry = Geometry {
+            vertices: vec![
+                Vertex::new(pos3(0.0, 0.0, 0.0), BACKWARDS, pos2(0.0, 0.0)),
+                Vertex::new(pos3(1.0, 0.0, 0.0), BACKWARDS, pos2(1.0, 0.0)),
+                Vertex::new(pos3(0.0, 2.0, 0.0), BACKWARDS, pos2(0.0, 1.0)),
+            ],
+            indices: vec![0, 1, 2],
+        };
+
+        let mut bytes = vec![];
+        geometry.write_stl(&mut bytes).unwrap();
+
+        let mut expected = vec![0; 80];
+        #[rustfmt::skip]
+        let body: &[u8] = &[
+            1, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 128, 63,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 128, 63, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 64, 0, 0, 0, 0,
+            0, 0,
+        ];
+        expected.extend_from_slice(body);
+        assert_eq!(bytes, expected);
+    }
+}
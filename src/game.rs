@@ -53,6 +53,13 @@ pub enum MouseEvent {
     },
 }
 
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FrameTime {
+    pub time: f64,
+    pub delta_time: f64,
+    pub frame_index: u64,
+}
+
 pub enum GameEvent {
     Resize {
         new_size: PhysicalSize<u32>,
@@ -64,7 +71,9 @@ pub enum GameEvent {
         needs_redraw: bool,
         wait_for_events: bool,
     },
-    Draw,
+    Draw {
+        frame_time: FrameTime,
+    },
     Close {
         cancel: bool,
     },
@@ -110,7 +119,9 @@ impl GameLoop for EventLoop<()> {
             displays_dictionary.insert(window_id, state);
         }
 
-        let mut now = Instant::now();
+        let start = Instant::now();
+        let mut now = start;
+        let mut frame_index = 0;
         self.run(move |event, _, control_flow| {
             use std::borrow::BorrowMut;
             let mut lock = event_handler.lock().unwrap();
@@ -258,6 +269,13 @@ impl GameLoop for EventLoop<()> {
 
             let delta_time = now.elapsed().as_secs_f64();
             now = Instant::now();
+            let frame_time = FrameTime {
+                time: now.duration_since(start).as_secs_f64(),
+                delta_time,
+                frame_index,
+            };
+            frame_index += 1;
+
             let mut wait_instead_of_polling = true;
             displays_dictionary.retain(|_window_id, display_state| {
                 let mut event = GameEvent::Update {
@@ -278,7 +296,7 @@ impl GameLoop for EventLoop<()> {
                         false
                     } else {
                         if needs_redraw {
-                            let mut event = GameEvent::Draw;
+                            let mut event = GameEvent::Draw { frame_time };
                             event_handler.handle_game_event(&display_state.display, &mut event);
                         }
 
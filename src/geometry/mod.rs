@@ -1,10 +1,15 @@
 pub mod primitives;
+pub mod skinned;
+pub mod terrain;
 
 pub use primitives::Primitive;
+pub use skinned::{GpuSkinnedGeometry, SkinnedGeometry, SkinnedVertex};
+pub use terrain::{Heightmap, Terrain, TerrainChunk};
 
 use super::math::*;
 use glium::backend::Facade;
 use glium::index::PrimitiveType;
+use glium::vertex::VerticesSource;
 use glium::*;
 use std::ops;
 
@@ -12,6 +17,8 @@ use std::ops;
 pub struct Vertex {
     pub position: Position3,
     pub normal: Direction3,
+    /// OpenGL convention: (0, 0) is the bottom-left corner of the image,
+    /// matching OBJ. Formats with a top-left origin flip V on import.
     pub texture_coordinates: Position2,
 }
 
@@ -23,6 +30,16 @@ pub struct GpuVertex {
 }
 implement_vertex!(GpuVertex, position, normal, texture_coordinates);
 
+impl GpuVertex {
+    pub fn to_vertex(&self) -> Vertex {
+        let [x, y, z] = self.position;
+        let [nx, ny, nz] = self.normal;
+        let [u, v] = self.texture_coordinates;
+
+        Vertex::new(pos3(x, y, z), dis3(nx, ny, nz).normalized(), pos2(u, v))
+    }
+}
+
 #[allow(dead_code)]
 impl Vertex {
     pub fn new(position: Position3, normal: Direction3, texture_coordinates: Position2) -> Vertex {
@@ -54,6 +71,7 @@ impl Vertex {
     }
 }
 
+#[derive(Clone)]
 pub struct Geometry {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
@@ -124,3 +142,55 @@ pub struct GpuGeometry {
     pub vertex_buffer: VertexBuffer<GpuVertex>,
     pub index_buffer: IndexBuffer<u32>,
 }
+
+pub trait GeometryBuffers {
+    fn get_buffers(&self) -> Option<(VerticesSource<'_>, &IndexBuffer<u32>)>;
+    fn get_vertex_count(&self) -> usize;
+    fn get_index_count(&self) -> usize;
+
+    fn to_geometry(&self) -> Option<Geometry> {
+        None
+    }
+}
+
+impl GeometryBuffers for GpuGeometry {
+    fn get_buffers(&self) -> Option<(VerticesSource<'_>, &IndexBuffer<u32>)> {
+        Some(((&self.vertex_buffer).into(), &self.index_buffer))
+    }
+
+    fn get_vertex_count(&self) -> usize {
+        self.vertex_buffer.len()
+    }
+
+    fn get_index_count(&self) -> usize {
+        self.index_buffer.len()
+    }
+
+    fn to_geometry(&self) -> Option<Geometry> {
+        let vertices = self.vertex_buffer.read().ok()?;
+        let indices = self.index_buffer.read().ok()?;
+
+        Some(Geometry {
+            vertices: vertices.iter().map(GpuVertex::to_vertex).collect(),
+            indices,
+        })
+    }
+}
+
+impl GeometryBuffers for Geometry {
+    fn get_buffers(&self) -> Option<(VerticesSource<'_>, &IndexBuffer<u32>)> {
+        None
+    }
+
+    fn get_vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    fn get_index_count(&self) -> usize {
+        self.indices.len()
+    }
+
+    fn to_geometry(&self) -> Option<Geometry> {
+        Some(self.clone())
+    }
+}
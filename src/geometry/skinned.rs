@@ -0,0 +1,166 @@
+use super::{Geometry, GeometryBuffers, Vertex};
+use glium::backend::Facade;
+use glium::index::PrimitiveType;
+use glium::vertex::VerticesSource;
+use glium::{implement_vertex, IndexBuffer, VertexBuffer};
+use std::ops;
+
+pub const MAX_JOINT_INFLUENCES: usize = 4;
+
+#[derive(Copy, Clone)]
+pub struct SkinnedVertex {
+    pub vertex: Vertex,
+    pub joint_indices: [u32; MAX_JOINT_INFLUENCES],
+    pub joint_weights: [f32; MAX_JOINT_INFLUENCES],
+}
+
+#[derive(Copy, Clone)]
+pub struct GpuSkinnedVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+    texture_coordinates: [f32; 2],
+    joint_indices: [u32; 4],
+    joint_weights: [f32; 4],
+}
+implement_vertex!(
+    GpuSkinnedVertex,
+    position,
+    normal,
+    texture_coordinates,
+    joint_indices,
+    joint_weights
+);
+
+#[allow(dead_code)]
+impl SkinnedVertex {
+    pub fn new(
+        vertex: Vertex,
+        joint_indices: [u32; MAX_JOINT_INFLUENCES],
+        joint_weights: [f32; MAX_JOINT_INFLUENCES],
+    ) -> SkinnedVertex {
+        let total: f32 = joint_weights.iter().sum();
+        let joint_weights = if total > 0.0 {
+            [
+                joint_weights[0] / total,
+                joint_weights[1] / total,
+                joint_weights[2] / total,
+                joint_weights[3] / total,
+            ]
+        } else {
+            [1.0, 0.0, 0.0, 0.0]
+        };
+
+        SkinnedVertex {
+            vertex,
+            joint_indices,
+            joint_weights,
+        }
+    }
+
+    pub fn rigid(vertex: Vertex, joint_index: u32) -> SkinnedVertex {
+        SkinnedVertex::new(vertex, [joint_index, 0, 0, 0], [1.0, 0.0, 0.0, 0.0])
+    }
+
+    pub fn as_gpu_vertex(&self) -> GpuSkinnedVertex {
+        let vertex = self.vertex.as_gpu_vertex();
+
+        GpuSkinnedVertex {
+            position: vertex.position,
+            normal: vertex.normal,
+            texture_coordinates: vertex.texture_coordinates,
+            joint_indices: self.joint_indices,
+            joint_weights: self.joint_weights,
+        }
+    }
+}
+
+pub struct SkinnedGeometry {
+    pub vertices: Vec<SkinnedVertex>,
+    pub indices: Vec<u32>,
+}
+
+#[allow(dead_code)]
+impl SkinnedGeometry {
+    pub fn empty() -> SkinnedGeometry {
+        SkinnedGeometry {
+            vertices: vec![],
+            indices: vec![],
+        }
+    }
+
+    pub fn rigid(geometry: &Geometry, joint_index: u32) -> SkinnedGeometry {
+        let vertices = geometry
+            .vertices
+            .iter()
+            .map(|&v| SkinnedVertex::rigid(v, joint_index))
+            .collect();
+        let indices = geometry.indices.clone();
+
+        SkinnedGeometry { vertices, indices }
+    }
+
+    pub fn to_gpu_geometry<F>(&self, facade: &F) -> GpuSkinnedGeometry
+    where
+        F: Facade,
+    {
+        let gpu_vertices = self
+            .vertices
+            .iter()
+            .map(|v| v.as_gpu_vertex())
+            .collect::<Vec<GpuSkinnedVertex>>();
+
+        let vertex_buffer = VertexBuffer::new(facade, &gpu_vertices).unwrap();
+        let index_buffer =
+            IndexBuffer::new(facade, PrimitiveType::TrianglesList, &self.indices).unwrap();
+
+        GpuSkinnedGeometry {
+            vertex_buffer,
+            index_buffer,
+        }
+    }
+}
+
+impl ops::AddAssign<&SkinnedGeometry> for SkinnedGeometry {
+    fn add_assign(&mut self, other: &Self) {
+        let vertices_length = self.vertices.len() as u32;
+
+        other.vertices.iter().for_each(|&v| self.vertices.push(v));
+        other
+            .indices
+            .iter()
+            .for_each(|&i| self.indices.push(i + vertices_length));
+    }
+}
+
+pub struct GpuSkinnedGeometry {
+    pub vertex_buffer: VertexBuffer<GpuSkinnedVertex>,
+    pub index_buffer: IndexBuffer<u32>,
+}
+
+impl GeometryBuffers for GpuSkinnedGeometry {
+    fn get_buffers(&self) -> Option<(VerticesSource<'_>, &IndexBuffer<u32>)> {
+        Some(((&self.vertex_buffer).into(), &self.index_buffer))
+    }
+
+    fn get_vertex_count(&self) -> usize {
+        self.vertex_buffer.len()
+    }
+
+    fn get_index_count(&self) -> usize {
+        self.index_buffer.len()
+    }
+}
+
+impl GeometryBuffers for SkinnedGeometry {
+    fn get_buffers(&self) -> Option<(VerticesSource<'_>, &IndexBuffer<u32>)> {
+        None
+    }
+
+    fn get_vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    fn get_index_count(&self) -> usize {
+        self.indices.len()
+    }
+}
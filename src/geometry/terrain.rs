@@ -0,0 +1,447 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use super::super::math::*;
+use super::{Geometry, Vertex};
+
+#[derive(Clone, Debug)]
+pub struct Heightmap {
+    width: usize,
+    depth: usize,
+    heights: Vec<f32>,
+}
+
+#[allow(dead_code)]
+impl Heightmap {
+    pub fn new(width: usize, depth: usize, heights: Vec<f32>) -> Heightmap {
+        assert!(width >= 2 && depth >= 2);
+        assert_eq!(heights.len(), width * depth);
+
+        Heightmap {
+            width,
+            depth,
+            heights,
+        }
+    }
+
+    pub fn flat(width: usize, depth: usize) -> Heightmap {
+        Heightmap::new(width, depth, vec![0.0; width * depth])
+    }
+
+    pub fn from_function<F>(width: usize, depth: usize, height: F) -> Heightmap
+    where
+        F: Fn(usize, usize) -> f32,
+    {
+        let mut heights = Vec::with_capacity(width * depth);
+        for z in 0..depth {
+            for x in 0..width {
+                heights.push(height(x, z));
+            }
+        }
+
+        Heightmap::new(width, depth, heights)
+    }
+
+    pub fn from_png<P: AsRef<Path>>(path: P) -> io::Result<Heightmap> {
+        let mut decoder = png::Decoder::new(File::open(path)?);
+        decoder.set_transformations(png::Transformations::EXPAND);
+
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+
+        let width = info.width as usize;
+        let depth = info.height as usize;
+        let channels = info.color_type.samples();
+        let wide = info.bit_depth == png::BitDepth::Sixteen;
+        let bytes_per_sample = if wide { 2 } else { 1 };
+        if width < 2 || depth < 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "heightmap must be at least 2x2 pixels, got {}x{}",
+                    width, depth
+                ),
+            ));
+        }
+
+        let heightmap = Heightmap::from_function(width, depth, |x, z| {
+            let offset = z * info.line_size + x * channels * bytes_per_sample;
+            if wide {
+                u16::from_be_bytes([buffer[offset], buffer[offset + 1]]) as f32 / 65535.0
+            } else {
+                buffer[offset] as f32 / 255.0
+            }
+        });
+
+        Ok(heightmap)
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn get(&self, x: isize, z: isize) -> f32 {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let z = z.clamp(0, self.depth as isize - 1) as usize;
+
+        self.heights[z * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, z: usize, height: f32) {
+        self.heights[z * self.width + x] = height;
+    }
+}
+
+pub struct TerrainChunk {
+    pub x: usize,
+    pub z: usize,
+    pub lod: u32,
+    pub geometry: Geometry,
+}
+
+#[derive(Clone, Debug)]
+pub struct Terrain {
+    pub heightmap: Heightmap,
+    pub cell_size: f32,
+    pub height_scale: f32,
+}
+
+#[allow(dead_code)]
+impl Terrain {
+    pub fn new(heightmap: Heightmap, cell_size: f32, height_scale: f32) -> Terrain {
+        assert!(cell_size > 0.0);
+
+        Terrain {
+            heightmap,
+            cell_size,
+            height_scale,
+        }
+    }
+
+    pub fn get_size(&self) -> (f32, f32) {
+        (
+            (self.heightmap.width - 1) as f32 * self.cell_size,
+            (self.heightmap.depth - 1) as f32 * self.cell_size,
+        )
+    }
+
+    fn sample(&self, x: isize, z: isize) -> f32 {
+        self.heightmap.get(x, z) * self.height_scale
+    }
+
+    fn sample_normal(&self, x: isize, z: isize) -> Direction3 {
+        let dx = (self.sample(x + 1, z) - self.sample(x - 1, z)) / (2.0 * self.cell_size);
+        let dz = (self.sample(x, z + 1) - self.sample(x, z - 1)) / (2.0 * self.cell_size);
+
+        dis3(-dx, 1.0, -dz).normalized()
+    }
+
+    fn sample_vertex(&self, x: usize, z: usize) -> Vertex {
+        let position = pos3(
+            x as f32 * self.cell_size,
+            self.sample(x as isize, z as isize),
+            z as f32 * self.cell_size,
+        );
+        let normal = self.sample_normal(x as isize, z as isize);
+        let texture_coordinates = pos2(
+            x as f32 / (self.heightmap.width - 1) as f32,
+            z as f32 / (self.heightmap.depth - 1) as f32,
+        );
+
+        Vertex::new(position, normal, texture_coordinates)
+    }
+
+    pub fn height_at(&self, x: f32, z: f32) -> f32 {
+        let grid_x = x / self.cell_size;
+        let grid_z = z / self.cell_size;
+
+        let x0 = grid_x.floor();
+        let z0 = grid_z.floor();
+        let tx = grid_x - x0;
+        let tz = grid_z - z0;
+        let (x0, z0) = (x0 as isize, z0 as isize);
+
+        let h00 = self.sample(x0, z0);
+        let h10 = self.sample(x0 + 1, z0);
+        let h01 = self.sample(x0, z0 + 1);
+        let h11 = self.sample(x0 + 1, z0 + 1);
+
+        let near = h00 + (h10 - h00) * tx;
+        let far = h01 + (h11 - h01) * tx;
+
+        near + (far - near) * tz
+    }
+
+    pub fn normal_at(&self, x: f32, z: f32) -> Direction3 {
+        let epsilon = self.cell_size * 0.5;
+
+        let dx =
+            (self.height_at(x + epsilon, z) - self.height_at(x - epsilon, z)) / (2.0 * epsilon);
+        let dz =
+            (self.height_at(x, z + epsilon) - self.height_at(x, z - epsilon)) / (2.0 * epsilon);
+
+        dis3(-dx, 1.0, -dz).normalized()
+    }
+
+    pub fn position_at(&self, x: f32, z: f32) -> Position3 {
+        pos3(x, self.height_at(x, z), z)
+    }
+
+    pub fn chunk_count(&self, chunk_size: usize) -> (usize, usize) {
+        assert!(chunk_size > 0, "chunk size must not be zero");
+
+        let cells_x = self.heightmap.width - 1;
+        let cells_z = self.heightmap.depth - 1;
+
+        (
+            (cells_x + chunk_size - 1) / chunk_size,
+            (cells_z + chunk_size - 1) / chunk_size,
+        )
+    }
+
+    pub fn chunk_geometry(
+        &self,
+        chunk_x: usize,
+        chunk_z: usize,
+        chunk_size: usize,
+        lod: u32,
+        skirt_depth: f32,
+    ) -> Geometry {
+        assert!(chunk_size > 0, "chunk size must not be zero");
+        let step = lod_step(chunk_size, lod);
+
+        let last_x = self.heightmap.width - 1;
+        let last_z = self.heightmap.depth - 1;
+
+        let start_x = chunk_x * chunk_size;
+        let start_z = chunk_z * chunk_size;
+        let mut xs: Vec<usize> = (start_x..start_x + chunk_size)
+            .step_by(step)
+            .filter(|&x| x < last_x)
+            .collect();
+        xs.push((start_x + chunk_size).min(last_x));
+        let mut zs: Vec<usize> = (start_z..start_z + chunk_size)
+            .step_by(step)
+            .filter(|&z| z < last_z)
+            .collect();
+        zs.push((start_z + chunk_size).min(last_z));
+
+        let columns = xs.len();
+        let rows = zs.len();
+
+        let mut vertices = Vec::with_capacity(columns * rows);
+        for &z in zs.iter() {
+            for &x in xs.iter() {
+                vertices.push(self.sample_vertex(x, z));
+            }
+        }
+
+        let mut indices = Vec::with_capacity((columns - 1) * (rows - 1) * 6);
+        for row in 0..rows - 1 {
+            for column in 0..columns - 1 {
+                let a = (row * columns + column) as u32;
+                let b = a + 1;
+                let c = a + columns as u32;
+                let d = c + 1;
+
+                indices.extend_from_slice(&[a, c, b, b, c, d]);
+            }
+        }
+
+        let mut geometry = Geometry { vertices, indices };
+
+        if skirt_depth > 0.0 {
+            let top: Vec<usize> = (0..columns).collect();
+            let bottom: Vec<usize> = (0..columns).map(|i| (rows - 1) * columns + i).collect();
+            let left: Vec<usize> = (0..rows).map(|i| i * columns).collect();
+            let right: Vec<usize> = (0..rows).map(|i| i * columns + columns - 1).collect();
+
+            for edge in [top, bottom, left, right].iter() {
+                add_skirt(&mut geometry, edge, skirt_depth);
+            }
+        }
+
+        geometry
+    }
+
+    pub fn chunks<F>(&self, chunk_size: usize, skirt_depth: f32, lod: F) -> Vec<TerrainChunk>
+    where
+        F: Fn(usize, usize) -> u32,
+    {
+        let (count_x, count_z) = self.chunk_count(chunk_size);
+        let lods: Vec<u32> = (0..count_z)
+            .flat_map(|z| (0..count_x).map(move |x| (x, z)))
+            .map(|(x, z)| lod(x, z))
+            .collect();
+        for &lod in lods.iter() {
+            lod_step(chunk_size, lod);
+        }
+
+        let mut chunks = Vec::with_capacity(count_x * count_z);
+        for z in 0..count_z {
+            for x in 0..count_x {
+                let lod = lods[z * count_x + x];
+                let geometry = self.chunk_geometry(x, z, chunk_size, lod, skirt_depth);
+
+                chunks.push(TerrainChunk {
+                    x,
+                    z,
+                    lod,
+                    geometry,
+                });
+            }
+        }
+
+        chunks
+    }
+}
+
+/// Grid step for a level of detail; the chunk size has to be a multiple of
+/// it so neighbouring chunks share their edge vertices.
+fn lod_step(chunk_size: usize, lod: u32) -> usize {
+    let step = 1usize.checked_shl(lod).unwrap_or(0);
+    assert!(
+        step > 0 && chunk_size % step == 0,
+        "chunk size {} is not a multiple of 2^{}",
+        chunk_size,
+        lod
+    );
+
+    step
+}
+
+fn add_skirt(geometry: &mut Geometry, edge: &[usize], skirt_depth: f32) {
+    let base = geometry.vertices.len() as u32;
+
+    for &index in edge.iter() {
+        let vertex = geometry.vertices[index];
+        let position = vertex.position + dis3(0.0, -skirt_depth, 0.0);
+
+        geometry.vertices.push(Vertex::new(
+            position,
+            vertex.normal,
+            vertex.texture_coordinates,
+        ));
+    }
+
+    for i in 0..edge.len() - 1 {
+        let top_a = edge[i] as u32;
+        let top_b = edge[i + 1] as u32;
+        let bottom_a = base + i as u32;
+        let bottom_b = bottom_a + 1;
+
+        geometry
+            .indices
+            .extend_from_slice(&[top_a, bottom_a, top_b, top_b, bottom_a, bottom_b]);
+        geometry
+            .indices
+            .extend_from_slice(&[top_a, top_b, bottom_a, top_b, bottom_b, bottom_a]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufWriter;
+
+    use super::*;
+
+    fn slope() -> Terrain {
+        Terrain::new(
+            Heightmap::from_function(9, 9, |x, z| (x + 2 * z) as f32),
+            0.5,
+            2.0,
+        )
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn interpolates_heights_and_normals() {
+        let terrain = slope();
+
+        assert!(close(
+            terrain.height_at(0.75, 0.25),
+            2.0 * (1.5 + 2.0 * 0.5)
+        ));
+        assert!(close(terrain.height_at(1.0, 1.0), 2.0 * (2.0 + 2.0 * 2.0)));
+
+        let [x, y, z] = terrain.normal_at(1.3, 1.7).vector.as_array();
+        let [ex, ey, ez] = dis3(-4.0, 1.0, -8.0).normalized().vector.as_array();
+        assert!(close(x, ex) && close(y, ey) && close(z, ez));
+    }
+
+    #[test]
+    fn neighbouring_chunks_share_their_seam() {
+        let terrain = slope();
+        let fine = terrain.chunk_geometry(0, 0, 4, 0, 0.0);
+        let coarse = terrain.chunk_geometry(1, 0, 4, 1, 0.0);
+
+        assert_eq!(fine.vertices.len(), 5 * 5);
+        assert_eq!(coarse.vertices.len(), 3 * 3);
+
+        let seam: Vec<_> = coarse
+            .vertices
+            .iter()
+            .filter(|vertex| close(vertex.position.vector.x, 2.0))
+            .collect();
+        assert_eq!(seam.len(), 3);
+        for vertex in seam {
+            assert!(
+                fine.vertices
+                    .iter()
+                    .any(|other| other.position.vector.as_array()
+                        == vertex.position.vector.as_array())
+            );
+        }
+    }
+
+    #[test]
+    fn skirts_hang_below_every_edge() {
+        let terrain = slope();
+        let geometry = terrain.chunk_geometry(1, 1, 4, 1, 0.25);
+
+        let grid = 3 * 3;
+        assert_eq!(geometry.vertices.len(), grid + 4 * 3);
+        assert_eq!(geometry.indices.len(), 2 * 2 * 6 + 4 * 2 * 12);
+
+        let edge = [0, 1, 2];
+        for (i, &index) in edge.iter().enumerate() {
+            let top = geometry.vertices[index].position;
+            let bottom = geometry.vertices[grid + i].position;
+            assert!(close(top.vector.y - bottom.vector.y, 0.25));
+            assert!(close(top.vector.x, bottom.vector.x) && close(top.vector.z, bottom.vector.z));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_a_lod_wider_than_usize() {
+        slope().chunks(4, 0.0, |_, _| 64);
+    }
+
+    #[test]
+    fn rejects_single_pixel_pngs() {
+        let path = std::env::temp_dir().join(format!("glib-terrain-{}.png", std::process::id()));
+        {
+            let writer = BufWriter::new(File::create(&path).unwrap());
+            let mut encoder = png::Encoder::new(writer, 1, 1);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&[128]).unwrap();
+        }
+
+        let error = Heightmap::from_png(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
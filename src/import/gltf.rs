@@ -0,0 +1,1890 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use glium::texture::{RawImage2d, SrgbTexture2d, Texture2d};
+use glium::Display;
+use serde::Deserialize;
+
+use super::super::animation::{
+    AnimationClip, Channel, ChannelTrack, Interpolate, Interpolation, Joint, JointTransform,
+    Keyframe, Skeleton, Track,
+};
+use super::super::geometry::{Geometry, GpuGeometry, SkinnedGeometry, SkinnedVertex, Vertex};
+use super::super::math::raw::matrix::{self, Matrix4};
+use super::super::math::raw::vector::Vector3;
+use super::super::math::*;
+use super::super::program::ProgramsCache;
+use super::super::rendering::scene::{Mesh, Scene};
+use super::super::rendering::skinning::MAX_JOINTS;
+use super::super::rendering::texture::decode_png_rgba;
+use super::super::rendering::{
+    Drawable, Light, Node, NodeId, Parameter, ParameterMaterial, SkinnedMesh,
+};
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_JSON_CHUNK: u32 = 0x4E4F_534A;
+const GLB_BIN_CHUNK: u32 = 0x004E_4942;
+
+const SUPPORTED_EXTENSIONS: &[&str] = &["KHR_lights_punctual"];
+
+const COMPONENT_BYTE: u32 = 5120;
+const COMPONENT_UNSIGNED_BYTE: u32 = 5121;
+const COMPONENT_SHORT: u32 = 5122;
+const COMPONENT_UNSIGNED_SHORT: u32 = 5123;
+const COMPONENT_UNSIGNED_INT: u32 = 5125;
+const COMPONENT_FLOAT: u32 = 5126;
+
+const MODE_TRIANGLES: u32 = 4;
+const MODE_TRIANGLE_STRIP: u32 = 5;
+const MODE_TRIANGLE_FAN: u32 = 6;
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn unsupported(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, message)
+}
+
+fn missing(kind: &str, index: usize) -> io::Error {
+    invalid_data(format!("{} {} does not exist", kind, index))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct RawDocument {
+    asset: RawAsset,
+    extensions_required: Vec<String>,
+    extensions: RawDocumentExtensions,
+    scene: Option<usize>,
+    scenes: Vec<RawScene>,
+    nodes: Vec<RawNode>,
+    meshes: Vec<RawMesh>,
+    accessors: Vec<RawAccessor>,
+    buffer_views: Vec<RawBufferView>,
+    buffers: Vec<RawBuffer>,
+    materials: Vec<RawMaterial>,
+    textures: Vec<RawTexture>,
+    images: Vec<RawImage>,
+    cameras: Vec<RawCamera>,
+    skins: Vec<RawSkin>,
+    animations: Vec<RawAnimation>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawAsset {
+    version: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawDocumentExtensions {
+    #[serde(rename = "KHR_lights_punctual")]
+    lights_punctual: RawLights,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawLights {
+    lights: Vec<RawLight>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawLight {
+    #[serde(rename = "type")]
+    kind: String,
+    color: Option<[f32; 3]>,
+    intensity: Option<f32>,
+    range: Option<f32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawScene {
+    nodes: Vec<usize>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawNode {
+    name: Option<String>,
+    children: Vec<usize>,
+    mesh: Option<usize>,
+    skin: Option<usize>,
+    camera: Option<usize>,
+    matrix: Option<[f32; 16]>,
+    translation: Option<[f32; 3]>,
+    rotation: Option<[f32; 4]>,
+    scale: Option<[f32; 3]>,
+    extensions: RawNodeExtensions,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawNodeExtensions {
+    #[serde(rename = "KHR_lights_punctual")]
+    light: Option<RawLightReference>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawLightReference {
+    light: usize,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawMesh {
+    primitives: Vec<RawPrimitive>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawPrimitive {
+    attributes: HashMap<String, usize>,
+    indices: Option<usize>,
+    material: Option<usize>,
+    mode: Option<u32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct RawAccessor {
+    buffer_view: Option<usize>,
+    byte_offset: usize,
+    component_type: u32,
+    normalized: bool,
+    count: usize,
+    #[serde(rename = "type")]
+    kind: String,
+    sparse: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct RawBufferView {
+    buffer: usize,
+    byte_offset: usize,
+    byte_length: usize,
+    byte_stride: Option<usize>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct RawBuffer {
+    uri: Option<String>,
+    byte_length: usize,
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct RawMaterial {
+    pbr_metallic_roughness: RawPbrMetallicRoughness,
+    emissive_factor: Option<[f32; 3]>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct RawPbrMetallicRoughness {
+    base_color_factor: Option<[f32; 4]>,
+    base_color_texture: Option<RawTextureInfo>,
+    metallic_factor: Option<f32>,
+    roughness_factor: Option<f32>,
+    metallic_roughness_texture: Option<RawTextureInfo>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+struct RawTextureInfo {
+    index: usize,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawTexture {
+    source: Option<usize>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct RawImage {
+    uri: Option<String>,
+    buffer_view: Option<usize>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawCamera {
+    perspective: Option<RawPerspective>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct RawPerspective {
+    aspect_ratio: Option<f32>,
+    yfov: f32,
+    znear: f32,
+    zfar: Option<f32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct RawSkin {
+    name: Option<String>,
+    joints: Vec<usize>,
+    inverse_bind_matrices: Option<usize>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawAnimation {
+    name: Option<String>,
+    channels: Vec<RawChannel>,
+    samplers: Vec<RawSampler>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawChannel {
+    sampler: usize,
+    target: RawTarget,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawTarget {
+    node: Option<usize>,
+    path: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawSampler {
+    input: usize,
+    output: usize,
+    interpolation: Option<String>,
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> io::Result<u32> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| invalid_data("unexpected end of GLB data".to_string()))
+}
+
+fn parse_glb(bytes: &[u8]) -> io::Result<(&[u8], Option<&[u8]>)> {
+    if read_u32(bytes, 0)? != GLB_MAGIC {
+        return Err(invalid_data("missing GLB header".to_string()));
+    }
+    let version = read_u32(bytes, 4)?;
+    if version != 2 {
+        return Err(unsupported(format!(
+            "GLB container version {} is not supported",
+            version
+        )));
+    }
+    let length = read_u32(bytes, 8)? as usize;
+    let bytes = bytes
+        .get(..length)
+        .ok_or_else(|| invalid_data("GLB data is truncated".to_string()))?;
+
+    let mut json = None;
+    let mut binary = None;
+    let mut offset = 12;
+    while offset < bytes.len() {
+        let chunk_length = read_u32(bytes, offset)? as usize;
+        let chunk_type = read_u32(bytes, offset + 4)?;
+        let data = bytes
+            .get(offset + 8..offset + 8 + chunk_length)
+            .ok_or_else(|| invalid_data("GLB chunk is truncated".to_string()))?;
+
+        match chunk_type {
+            GLB_JSON_CHUNK if json.is_none() => json = Some(data),
+            GLB_BIN_CHUNK if binary.is_none() => binary = Some(data),
+            _ => {}
+        }
+        offset += 8 + chunk_length;
+    }
+
+    let json = json.ok_or_else(|| invalid_data("GLB has no JSON chunk".to_string()))?;
+    Ok((json, binary))
+}
+
+fn decode_base64(input: &str) -> io::Result<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+
+    for byte in input.bytes().filter(|byte| !byte.is_ascii_whitespace()) {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            _ => {
+                return Err(invalid_data(format!(
+                    "invalid base64 character {:?}",
+                    byte as char
+                )))
+            }
+        };
+
+        accumulator = ((accumulator << 6) | value as u32) & 0xFF_FFFF;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((accumulator >> bits) as u8);
+        }
+    }
+
+    Ok(output)
+}
+
+fn decode_percent(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let value = uri
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            if let Some(value) = value {
+                output.push(value);
+                index += 3;
+                continue;
+            }
+        }
+        output.push(bytes[index]);
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+fn read_uri(base_path: &Path, uri: &str) -> io::Result<Vec<u8>> {
+    match uri.strip_prefix("data:") {
+        Some(data) => {
+            let (header, payload) = data
+                .split_once(',')
+                .ok_or_else(|| invalid_data("malformed data URI".to_string()))?;
+            if !header.ends_with(";base64") {
+                return Err(unsupported(
+                    "only base64 encoded data URIs are supported".to_string(),
+                ));
+            }
+
+            decode_base64(payload)
+        }
+        None => fs::read(base_path.join(decode_percent(uri))),
+    }
+}
+
+fn component_count(kind: &str) -> io::Result<usize> {
+    match kind {
+        "SCALAR" => Ok(1),
+        "VEC2" => Ok(2),
+        "VEC3" => Ok(3),
+        "VEC4" | "MAT2" => Ok(4),
+        "MAT3" => Ok(9),
+        "MAT4" => Ok(16),
+        _ => Err(invalid_data(format!("unknown accessor type \"{}\"", kind))),
+    }
+}
+
+fn component_size(component_type: u32) -> io::Result<usize> {
+    match component_type {
+        COMPONENT_BYTE | COMPONENT_UNSIGNED_BYTE => Ok(1),
+        COMPONENT_SHORT | COMPONENT_UNSIGNED_SHORT => Ok(2),
+        COMPONENT_UNSIGNED_INT | COMPONENT_FLOAT => Ok(4),
+        _ => Err(invalid_data(format!(
+            "unknown accessor component type {}",
+            component_type
+        ))),
+    }
+}
+
+fn read_component(bytes: &[u8], component_type: u32, normalized: bool) -> f32 {
+    match component_type {
+        COMPONENT_BYTE => {
+            let value = bytes[0] as i8 as f32;
+            if normalized {
+                (value / 127.0).max(-1.0)
+            } else {
+                value
+            }
+        }
+        COMPONENT_UNSIGNED_BYTE => {
+            let value = bytes[0] as f32;
+            if normalized {
+                value / 255.0
+            } else {
+                value
+            }
+        }
+        COMPONENT_SHORT => {
+            let value = i16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+            if normalized {
+                (value / 32767.0).max(-1.0)
+            } else {
+                value
+            }
+        }
+        COMPONENT_UNSIGNED_SHORT => {
+            let value = u16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+            if normalized {
+                value / 65535.0
+            } else {
+                value
+            }
+        }
+        COMPONENT_UNSIGNED_INT => {
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32
+        }
+        _ => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    }
+}
+
+fn triangulate(mode: u32, indices: Vec<u32>) -> io::Result<Vec<u32>> {
+    match mode {
+        MODE_TRIANGLES => Ok(indices),
+        MODE_TRIANGLE_STRIP => Ok((2..indices.len())
+            .flat_map(|i| {
+                if i % 2 == 0 {
+                    [indices[i - 2], indices[i - 1], indices[i]]
+                } else {
+                    [indices[i - 1], indices[i - 2], indices[i]]
+                }
+            })
+            .collect()),
+        MODE_TRIANGLE_FAN => Ok((2..indices.len())
+            .flat_map(|i| [indices[0], indices[i - 1], indices[i]])
+            .collect()),
+        _ => Err(unsupported(format!(
+            "primitive mode {} is not supported, only triangles can be imported",
+            mode
+        ))),
+    }
+}
+
+fn cross(a: Vector3, b: Vector3) -> Vector3 {
+    Vector3 {
+        x: a.y * b.z - a.z * b.y,
+        y: a.z * b.x - a.x * b.z,
+        z: a.x * b.y - a.y * b.x,
+    }
+}
+
+fn compute_normals(geometry: &mut Geometry) {
+    let zero = Vector3 {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    let mut normals = vec![zero; geometry.vertices.len()];
+
+    for triangle in geometry.indices.chunks_exact(3) {
+        let a = geometry.vertices[triangle[0] as usize].position;
+        let b = geometry.vertices[triangle[1] as usize].position;
+        let c = geometry.vertices[triangle[2] as usize].position;
+        let normal = cross((b - a).vector, (c - a).vector);
+
+        for &index in triangle {
+            normals[index as usize] = normals[index as usize] + normal;
+        }
+    }
+
+    for (vertex, normal) in geometry.vertices.iter_mut().zip(normals) {
+        vertex.normal = if normal.length_squared() > 0.0 {
+            dis3(normal.x, normal.y, normal.z).normalized()
+        } else {
+            BACKWARDS
+        };
+    }
+}
+
+fn matrix_elements(values: &[f32]) -> [[f32; 4]; 4] {
+    let mut elements = [[0.0; 4]; 4];
+    for (column, values) in elements.iter_mut().zip(values.chunks(4)) {
+        column.copy_from_slice(values);
+    }
+    elements
+}
+
+fn decompose(elements: &[[f32; 4]; 4]) -> JointTransform {
+    let column = |index: usize| Vector3 {
+        x: elements[index][0],
+        y: elements[index][1],
+        z: elements[index][2],
+    };
+    let (x_axis, y_axis, z_axis) = (column(0), column(1), column(2));
+
+    let mut scale = [x_axis.length(), y_axis.length(), z_axis.length()];
+    let determinant = cross(y_axis, z_axis);
+    if x_axis.x * determinant.x + x_axis.y * determinant.y + x_axis.z * determinant.z < 0.0 {
+        scale[0] = -scale[0];
+    }
+
+    let r = |row: usize, column: usize| {
+        if scale[column] == 0.0 {
+            0.0
+        } else {
+            elements[column][row] / scale[column]
+        }
+    };
+    let trace = r(0, 0) + r(1, 1) + r(2, 2);
+    let rotation = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        Quaternion::new(
+            (r(2, 1) - r(1, 2)) / s,
+            (r(0, 2) - r(2, 0)) / s,
+            (r(1, 0) - r(0, 1)) / s,
+            0.25 * s,
+        )
+    } else if r(0, 0) > r(1, 1) && r(0, 0) > r(2, 2) {
+        let s = (1.0 + r(0, 0) - r(1, 1) - r(2, 2)).sqrt() * 2.0;
+        Quaternion::new(
+            0.25 * s,
+            (r(0, 1) + r(1, 0)) / s,
+            (r(0, 2) + r(2, 0)) / s,
+            (r(2, 1) - r(1, 2)) / s,
+        )
+    } else if r(1, 1) > r(2, 2) {
+        let s = (1.0 + r(1, 1) - r(0, 0) - r(2, 2)).sqrt() * 2.0;
+        Quaternion::new(
+            (r(0, 1) + r(1, 0)) / s,
+            0.25 * s,
+            (r(1, 2) + r(2, 1)) / s,
+            (r(0, 2) - r(2, 0)) / s,
+        )
+    } else {
+        let s = (1.0 + r(2, 2) - r(0, 0) - r(1, 1)).sqrt() * 2.0;
+        Quaternion::new(
+            (r(0, 2) + r(2, 0)) / s,
+            (r(1, 2) + r(2, 1)) / s,
+            0.25 * s,
+            (r(1, 0) - r(0, 1)) / s,
+        )
+    };
+
+    JointTransform::new(
+        dis3(elements[3][0], elements[3][1], elements[3][2]),
+        rotation.normalized(),
+        dis3(scale[0], scale[1], scale[2]),
+    )
+}
+
+fn node_components(node: &RawNode) -> JointTransform {
+    if let Some(matrix) = &node.matrix {
+        return decompose(&matrix_elements(matrix));
+    }
+
+    let t = node.translation.unwrap_or([0.0; 3]);
+    let r = node.rotation.unwrap_or([0.0, 0.0, 0.0, 1.0]);
+    let s = node.scale.unwrap_or([1.0; 3]);
+
+    JointTransform::new(
+        dis3(t[0], t[1], t[2]),
+        Quaternion::new(r[0], r[1], r[2], r[3]).normalized(),
+        dis3(s[0], s[1], s[2]),
+    )
+}
+
+fn read_interpolation(sampler: &RawSampler) -> io::Result<(Interpolation, usize)> {
+    match sampler.interpolation.as_deref().unwrap_or("LINEAR") {
+        "STEP" => Ok((Interpolation::Step, 1)),
+        "LINEAR" => Ok((Interpolation::Linear, 1)),
+        "CUBICSPLINE" => Ok((Interpolation::CubicSpline, 3)),
+        other => Err(unsupported(format!(
+            "animation interpolation \"{}\" is not supported",
+            other
+        ))),
+    }
+}
+
+struct GltfFile {
+    document: RawDocument,
+    buffers: Vec<Vec<u8>>,
+    base_path: PathBuf,
+    parents: Vec<Option<usize>>,
+}
+
+impl GltfFile {
+    fn open(path: &Path) -> io::Result<GltfFile> {
+        let bytes = fs::read(path)?;
+        let base_path = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        let (json, binary) = if bytes.starts_with(b"glTF") {
+            parse_glb(&bytes)?
+        } else {
+            (&bytes[..], None)
+        };
+        let document: RawDocument = serde_json::from_slice(json)?;
+
+        if !document.asset.version.starts_with("2.") {
+            return Err(unsupported(format!(
+                "glTF version \"{}\" is not supported",
+                document.asset.version
+            )));
+        }
+        if let Some(extension) = document
+            .extensions_required
+            .iter()
+            .find(|extension| !SUPPORTED_EXTENSIONS.contains(&extension.as_str()))
+        {
+            return Err(unsupported(format!(
+                "glTF extension \"{}\" is required but not supported",
+                extension
+            )));
+        }
+
+        let buffers = document
+            .buffers
+            .iter()
+            .enumerate()
+            .map(|(index, buffer)| {
+                let data = match (&buffer.uri, binary) {
+                    (Some(uri), _) => read_uri(&base_path, uri)?,
+                    (None, Some(binary)) if index == 0 => binary.to_vec(),
+                    (None, _) => return Err(invalid_data(format!("buffer {} has no data", index))),
+                };
+                if data.len() < buffer.byte_length {
+                    return Err(invalid_data(format!("buffer {} is truncated", index)));
+                }
+
+                Ok(data)
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let mut parents = vec![None; document.nodes.len()];
+        for (index, node) in document.nodes.iter().enumerate() {
+            for &child in node.children.iter() {
+                match parents.get_mut(child) {
+                    Some(parent @ None) => *parent = Some(index),
+                    Some(Some(_)) => {
+                        return Err(invalid_data(format!("node {} has several parents", child)))
+                    }
+                    None => return Err(missing("node", child)),
+                }
+            }
+        }
+        for index in 0..parents.len() {
+            let mut depth = 0;
+            let mut current = parents[index];
+            while let Some(parent) = current {
+                depth += 1;
+                if depth > parents.len() {
+                    return Err(invalid_data("node hierarchy contains a cycle".to_string()));
+                }
+                current = parents[parent];
+            }
+        }
+
+        Ok(GltfFile {
+            document,
+            buffers,
+            base_path,
+            parents,
+        })
+    }
+
+    fn node(&self, index: usize) -> io::Result<&RawNode> {
+        self.document
+            .nodes
+            .get(index)
+            .ok_or_else(|| missing("node", index))
+    }
+
+    fn mesh(&self, index: usize) -> io::Result<&RawMesh> {
+        self.document
+            .meshes
+            .get(index)
+            .ok_or_else(|| missing("mesh", index))
+    }
+
+    fn root_nodes(&self) -> io::Result<Vec<usize>> {
+        let scene = self.document.scene.or(if self.document.scenes.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+
+        match scene {
+            Some(scene) => {
+                let scene = self
+                    .document
+                    .scenes
+                    .get(scene)
+                    .ok_or_else(|| missing("scene", scene))?;
+                scene
+                    .nodes
+                    .iter()
+                    .try_for_each(|&node| self.node(node).map(|_| ()))?;
+
+                Ok(scene.nodes.clone())
+            }
+            None => Ok((0..self.parents.len())
+                .filter(|&index| self.parents[index].is_none())
+                .collect()),
+        }
+    }
+
+    fn scene_nodes(&self) -> io::Result<Vec<usize>> {
+        let mut nodes = vec![];
+        let mut pending = self.root_nodes()?;
+        while let Some(index) = pending.pop() {
+            nodes.push(index);
+            pending.extend(self.node(index)?.children.iter().copied());
+        }
+
+        Ok(nodes)
+    }
+
+    fn depth(&self, index: usize) -> usize {
+        let mut depth = 0;
+        let mut current = self.parents[index];
+        while let Some(parent) = current {
+            depth += 1;
+            current = self.parents[parent];
+        }
+        depth
+    }
+
+    fn world_transform(&self, index: usize) -> Transform {
+        let mut transform = node_components(&self.document.nodes[index]).to_transform();
+        let mut current = self.parents[index];
+        while let Some(parent) = current {
+            transform = node_components(&self.document.nodes[parent]).to_transform() * transform;
+            current = self.parents[parent];
+        }
+        transform
+    }
+
+    fn buffer_view(&self, index: usize) -> io::Result<&[u8]> {
+        let view = self
+            .document
+            .buffer_views
+            .get(index)
+            .ok_or_else(|| missing("buffer view", index))?;
+        let buffer = self
+            .buffers
+            .get(view.buffer)
+            .ok_or_else(|| missing("buffer", view.buffer))?;
+
+        buffer
+            .get(view.byte_offset..view.byte_offset + view.byte_length)
+            .ok_or_else(|| invalid_data(format!("buffer view {} is out of range", index)))
+    }
+
+    fn read_accessor(&self, index: usize) -> io::Result<(Vec<f32>, usize)> {
+        let accessor = self
+            .document
+            .accessors
+            .get(index)
+            .ok_or_else(|| missing("accessor", index))?;
+        if accessor.sparse.is_some() {
+            return Err(unsupported(format!(
+                "sparse accessor {} is not supported",
+                index
+            )));
+        }
+
+        let components = component_count(&accessor.kind)?;
+        let size = component_size(accessor.component_type)?;
+        let view = match accessor.buffer_view {
+            Some(view) => view,
+            None => return Ok((vec![0.0; accessor.count * components], components)),
+        };
+        let data = self.buffer_view(view)?;
+        let stride = self.document.buffer_views[view]
+            .byte_stride
+            .unwrap_or(components * size);
+
+        let mut values = Vec::with_capacity(accessor.count * components);
+        for element in 0..accessor.count {
+            let start = accessor.byte_offset + element * stride;
+            for component in 0..components {
+                let offset = start + component * size;
+                let bytes = data.get(offset..offset + size).ok_or_else(|| {
+                    invalid_data(format!(
+                        "accessor {} reads past the end of its buffer view",
+                        index
+                    ))
+                })?;
+                values.push(read_component(
+                    bytes,
+                    accessor.component_type,
+                    accessor.normalized,
+                ));
+            }
+        }
+
+        Ok((values, components))
+    }
+
+    fn read_attribute(
+        &self,
+        primitive: &RawPrimitive,
+        name: &str,
+        components: usize,
+    ) -> io::Result<Option<Vec<f32>>> {
+        let index = match primitive.attributes.get(name) {
+            Some(&index) => index,
+            None => return Ok(None),
+        };
+        let (values, count) = self.read_accessor(index)?;
+        if count != components {
+            return Err(invalid_data(format!(
+                "attribute {} has {} components, expected {}",
+                name, count, components
+            )));
+        }
+
+        Ok(Some(values))
+    }
+
+    fn primitive_geometry(&self, primitive: &RawPrimitive) -> io::Result<Geometry> {
+        let positions = self
+            .read_attribute(primitive, "POSITION", 3)?
+            .ok_or_else(|| invalid_data("primitive has no POSITION attribute".to_string()))?;
+        let normals = self.read_attribute(primitive, "NORMAL", 3)?;
+        let texture_coordinates = self.read_attribute(primitive, "TEXCOORD_0", 2)?;
+        let count = positions.len() / 3;
+
+        let indices: Vec<u32> = match primitive.indices {
+            Some(index) => self
+                .read_accessor(index)?
+                .0
+                .iter()
+                .map(|&index| index as u32)
+                .collect(),
+            None => (0..count as u32).collect(),
+        };
+        if indices.iter().any(|&index| index as usize >= count) {
+            return Err(invalid_data("primitive index is out of range".to_string()));
+        }
+        let indices = triangulate(primitive.mode.unwrap_or(MODE_TRIANGLES), indices)?;
+
+        let vertices = (0..count)
+            .map(|i| {
+                let position = pos3(positions[3 * i], positions[3 * i + 1], positions[3 * i + 2]);
+                let normal = normals.as_ref().map_or(BACKWARDS, |normals| {
+                    dis3(normals[3 * i], normals[3 * i + 1], normals[3 * i + 2]).normalized()
+                });
+                // glTF puts the UV origin at the top-left corner of the image.
+                let texture_coordinates = texture_coordinates
+                    .as_ref()
+                    .map_or(pos2(0.0, 0.0), |uv| pos2(uv[2 * i], 1.0 - uv[2 * i + 1]));
+
+                Vertex::new(position, normal, texture_coordinates)
+            })
+            .collect();
+
+        let mut geometry = Geometry { vertices, indices };
+        if normals.is_none() {
+            compute_normals(&mut geometry);
+        }
+
+        Ok(geometry)
+    }
+
+    fn primitive_skinned_geometry(
+        &self,
+        primitive: &RawPrimitive,
+        joints: &[usize],
+    ) -> io::Result<SkinnedGeometry> {
+        let geometry = self.primitive_geometry(primitive)?;
+        let joint_indices = self
+            .read_attribute(primitive, "JOINTS_0", 4)?
+            .ok_or_else(|| {
+                invalid_data("skinned primitive has no JOINTS_0 attribute".to_string())
+            })?;
+        let joint_weights = self
+            .read_attribute(primitive, "WEIGHTS_0", 4)?
+            .ok_or_else(|| {
+                invalid_data("skinned primitive has no WEIGHTS_0 attribute".to_string())
+            })?;
+
+        let vertices = geometry
+            .vertices
+            .into_iter()
+            .enumerate()
+            .map(|(i, vertex)| {
+                let mut indices = [0; 4];
+                let mut weights = [0.0; 4];
+                for influence in 0..4 {
+                    let joint = joint_indices[4 * i + influence] as usize;
+                    indices[influence] = *joints
+                        .get(joint)
+                        .ok_or_else(|| invalid_data(format!("joint {} is out of range", joint)))?
+                        as u32;
+                    weights[influence] = joint_weights[4 * i + influence];
+                }
+
+                Ok(SkinnedVertex::new(vertex, indices, weights))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(SkinnedGeometry {
+            vertices,
+            indices: geometry.indices,
+        })
+    }
+
+    fn read_track<T: Interpolate>(
+        &self,
+        sampler: &RawSampler,
+        components: usize,
+        convert: impl Fn(&[f32]) -> T,
+    ) -> io::Result<Track<T>> {
+        let (interpolation, stride) = read_interpolation(sampler)?;
+        let (times, _) = self.read_accessor(sampler.input)?;
+        if let Some(time) = times.iter().find(|time| !time.is_finite()) {
+            return Err(invalid_data(format!(
+                "animation input time {} is not finite",
+                time
+            )));
+        }
+        let (values, count) = self.read_accessor(sampler.output)?;
+        if count != components {
+            return Err(invalid_data(format!(
+                "animation output has {} components, expected {}",
+                count, components
+            )));
+        }
+
+        let element = |index: usize| {
+            let offset = index * components;
+            values
+                .get(offset..offset + components)
+                .map(&convert)
+                .ok_or_else(|| {
+                    invalid_data("animation output is shorter than its input".to_string())
+                })
+        };
+
+        // Cubic spline outputs store an in tangent, the value and an out
+        // tangent for every keyframe.
+        let keyframes = times
+            .iter()
+            .enumerate()
+            .map(|(i, &time)| match stride {
+                3 => Ok(Keyframe::new(time, element(3 * i + 1)?)
+                    .with_tangents(element(3 * i)?, element(3 * i + 2)?)),
+                _ => Ok(Keyframe::new(time, element(i)?)),
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(Track::from_keyframes(keyframes, interpolation))
+    }
+
+    fn bind_skin(&self, index: usize) -> io::Result<SkinBinding> {
+        let skin = &self.document.skins[index];
+        if skin.joints.is_empty() {
+            return Err(invalid_data(format!("skin {} has no joints", index)));
+        }
+        if skin.joints.len() > MAX_JOINTS {
+            return Err(unsupported(format!(
+                "skin {} has {} joints, at most {} are supported",
+                index,
+                skin.joints.len(),
+                MAX_JOINTS
+            )));
+        }
+        skin.joints
+            .iter()
+            .try_for_each(|&joint| self.node(joint).map(|_| ()))?;
+
+        let inverse_binds = match skin.inverse_bind_matrices {
+            Some(accessor) => {
+                let (values, components) = self.read_accessor(accessor)?;
+                if components != 16 || values.len() < 16 * skin.joints.len() {
+                    return Err(invalid_data(format!(
+                        "skin {} has invalid inverse bind matrices",
+                        index
+                    )));
+                }
+                values
+                    .chunks(16)
+                    .map(|values| Matrix4 {
+                        elements: matrix_elements(values),
+                    })
+                    .collect()
+            }
+            None => vec![matrix::IDENTITY; skin.joints.len()],
+        };
+
+        let mut order: Vec<usize> = (0..skin.joints.len()).collect();
+        order.sort_by_key(|&joint| self.depth(skin.joints[joint]));
+        let mut joints = vec![0; skin.joints.len()];
+        for (new, &old) in order.iter().enumerate() {
+            joints[old] = new;
+        }
+
+        let joint_parent = |node: usize| {
+            let mut current = self.parents[node];
+            while let Some(parent) = current {
+                if let Some(joint) = skin.joints.iter().position(|&joint| joint == parent) {
+                    return Some(joints[joint]);
+                }
+                current = self.parents[parent];
+            }
+            None
+        };
+
+        let root = match self.parents[skin.joints[order[0]]] {
+            Some(parent) => self.world_transform(parent),
+            None => IDENTITY,
+        };
+
+        let skeleton = order
+            .iter()
+            .map(|&old| {
+                let index = skin.joints[old];
+                let node = &self.document.nodes[index];
+                let parent = joint_parent(index);
+                let rest = match parent {
+                    Some(_) => node_components(node),
+                    None => decompose(
+                        &(root.inverse() * self.world_transform(index))
+                            .matrix
+                            .elements,
+                    ),
+                };
+
+                Joint {
+                    name: node
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("joint{}", index)),
+                    parent,
+                    rest,
+                    inverse_bind: inverse_binds[old],
+                }
+            })
+            .collect();
+
+        Ok(SkinBinding {
+            skeleton: Rc::new(Skeleton::new(skeleton)),
+            joints,
+            root,
+        })
+    }
+
+    fn skin_clips(&self, index: usize, binding: &SkinBinding) -> io::Result<Vec<AnimationClip>> {
+        let skin = &self.document.skins[index];
+
+        let mut clips = vec![];
+        for (animation_index, animation) in self.document.animations.iter().enumerate() {
+            let mut channels = vec![];
+            for channel in animation.channels.iter() {
+                let joint = match channel
+                    .target
+                    .node
+                    .and_then(|node| skin.joints.iter().position(|&joint| joint == node))
+                {
+                    Some(joint) => binding.joints[joint],
+                    None => continue,
+                };
+                let sampler = animation
+                    .samplers
+                    .get(channel.sampler)
+                    .ok_or_else(|| missing("animation sampler", channel.sampler))?;
+
+                let track = match channel.target.path.as_str() {
+                    "translation" => {
+                        ChannelTrack::Translation(
+                            self.read_track(sampler, 3, |v| dis3(v[0], v[1], v[2]))?,
+                        )
+                    }
+                    "rotation" => ChannelTrack::Rotation(self.read_track(sampler, 4, |v| {
+                        Quaternion::new(v[0], v[1], v[2], v[3]).normalized()
+                    })?),
+                    "scale" => ChannelTrack::Scale(
+                        self.read_track(sampler, 3, |v| dis3(v[0], v[1], v[2]))?,
+                    ),
+                    _ => continue,
+                };
+                channels.push(Channel { joint, track });
+            }
+
+            if !channels.is_empty() {
+                let name = animation
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("animation{}", animation_index));
+                clips.push(AnimationClip::new(&name, channels));
+            }
+        }
+
+        Ok(clips)
+    }
+}
+
+struct SkinBinding {
+    skeleton: Rc<Skeleton>,
+    joints: Vec<usize>,
+    root: Transform,
+}
+
+pub struct GltfSkin {
+    pub name: String,
+    pub skeleton: Rc<Skeleton>,
+    pub meshes: Vec<Rc<SkinnedMesh<ParameterMaterial>>>,
+    pub clips: Vec<AnimationClip>,
+}
+
+pub struct GltfScene {
+    pub scene: Scene,
+    pub nodes: Vec<Option<NodeId>>,
+    pub cameras: Vec<(NodeId, Camera)>,
+    pub skins: Vec<GltfSkin>,
+}
+
+struct Importer<'a> {
+    file: GltfFile,
+    display: &'a Display,
+    programs_cache: &'a ProgramsCache,
+    geometries: HashMap<(usize, usize), Rc<GpuGeometry>>,
+    materials: HashMap<Option<usize>, Rc<ParameterMaterial>>,
+    skinned_materials: HashMap<Option<usize>, Rc<ParameterMaterial>>,
+    textures: HashMap<usize, Rc<Texture2d>>,
+    srgb_textures: HashMap<usize, Rc<SrgbTexture2d>>,
+    bindings: Vec<SkinBinding>,
+    result: GltfScene,
+}
+
+impl<'a> Importer<'a> {
+    fn program(&self, name: &str) -> io::Result<ParameterMaterial> {
+        ParameterMaterial::from_cache(self.programs_cache, name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("program \"{}\" not found", name),
+            )
+        })
+    }
+
+    fn raw_material(&self, index: Option<usize>) -> io::Result<RawMaterial> {
+        match index {
+            Some(index) => self
+                .file
+                .document
+                .materials
+                .get(index)
+                .cloned()
+                .ok_or_else(|| missing("material", index)),
+            None => Ok(RawMaterial::default()),
+        }
+    }
+
+    fn image_pixels(&self, index: usize) -> io::Result<(Vec<u8>, (u32, u32))> {
+        let texture = self
+            .file
+            .document
+            .textures
+            .get(index)
+            .ok_or_else(|| missing("texture", index))?;
+        let source = texture.source.ok_or_else(|| {
+            unsupported(format!("texture {} has no supported image source", index))
+        })?;
+        let image = self
+            .file
+            .document
+            .images
+            .get(source)
+            .ok_or_else(|| missing("image", source))?;
+
+        let bytes = match (&image.uri, image.buffer_view) {
+            (Some(uri), _) => read_uri(&self.file.base_path, uri)?,
+            (None, Some(view)) => self.file.buffer_view(view)?.to_vec(),
+            (None, None) => return Err(invalid_data(format!("image {} has no data", source))),
+        };
+        if !bytes.starts_with(b"\x89PNG") {
+            return Err(unsupported(format!(
+                "image {} is not a PNG image, other formats are not supported",
+                source
+            )));
+        }
+
+        decode_png_rgba(&bytes[..])
+    }
+
+    /// The file an image texture was read from, when it is not embedded in
+    /// the glTF file itself.
+    fn image_path(&self, index: usize) -> Option<String> {
+        let texture = self.file.document.textures.get(index)?;
+        let image = self.file.document.images.get(texture.source?)?;
+        let uri = image.uri.as_ref()?;
+        if uri.starts_with("data:") {
+            return None;
+        }
+
+        let path = self.file.base_path.join(decode_percent(uri));
+        Some(path.to_string_lossy().into_owned())
+    }
+
+    fn set_texture<P: Into<Parameter>>(
+        &self,
+        material: &mut ParameterMaterial,
+        name: &str,
+        index: usize,
+        texture: P,
+    ) {
+        match self.image_path(index) {
+            Some(path) => material.set_texture(name, texture, &path),
+            None => material.set(name, texture),
+        }
+    }
+
+    fn texture(&mut self, index: usize) -> io::Result<Rc<Texture2d>> {
+        if let Some(texture) = self.textures.get(&index) {
+            return Ok(Rc::clone(texture));
+        }
+
+        let (pixels, dimensions) = self.image_pixels(index)?;
+        let image = RawImage2d::from_raw_rgba_reversed(&pixels, dimensions);
+        let texture = Texture2d::new(self.display, image)
+            .map_err(|error| invalid_data(format!("{:?}", error)))?;
+
+        let texture = Rc::new(texture);
+        self.textures.insert(index, Rc::clone(&texture));
+        Ok(texture)
+    }
+
+    fn srgb_texture(&mut self, index: usize) -> io::Result<Rc<SrgbTexture2d>> {
+        if let Some(texture) = self.srgb_textures.get(&index) {
+            return Ok(Rc::clone(texture));
+        }
+
+        let (pixels, dimensions) = self.image_pixels(index)?;
+        let image = RawImage2d::from_raw_rgba_reversed(&pixels, dimensions);
+        let texture = SrgbTexture2d::new(self.display, image)
+            .map_err(|error| invalid_data(format!("{:?}", error)))?;
+
+        let texture = Rc::new(texture);
+        self.srgb_textures.insert(index, Rc::clone(&texture));
+        Ok(texture)
+    }
+
+    fn pbr_material(
+        &mut self,
+        index: Option<usize>,
+        program: &str,
+    ) -> io::Result<ParameterMaterial> {
+        let raw = self.raw_material(index)?;
+        let pbr = &raw.pbr_metallic_roughness;
+        let base_color = pbr.base_color_factor.unwrap_or([1.0; 4]);
+
+        let mut material = self.program(program)?;
+        material.set(
+            "base_color",
+            rgba(base_color[0], base_color[1], base_color[2], base_color[3]),
+        );
+        material.set("metallic", pbr.metallic_factor.unwrap_or(1.0));
+        material.set("roughness", pbr.roughness_factor.unwrap_or(1.0));
+        material.set("emissive_color", raw.emissive_factor.unwrap_or([0.0; 3]));
+
+        material.set("has_base_color_texture", pbr.base_color_texture.is_some());
+        if let Some(texture) = &pbr.base_color_texture {
+            let value = self.srgb_texture(texture.index)?;
+            self.set_texture(&mut material, "base_color_texture", texture.index, value);
+        }
+        material.set(
+            "has_metallic_roughness_texture",
+            pbr.metallic_roughness_texture.is_some(),
+        );
+        if let Some(texture) = &pbr.metallic_roughness_texture {
+            let value = self.texture(texture.index)?;
+            self.set_texture(
+                &mut material,
+                "metallic_roughness_texture",
+                texture.index,
+                value,
+            );
+        }
+
+        Ok(material)
+    }
+
+    fn material(&mut self, index: Option<usize>) -> io::Result<Rc<ParameterMaterial>> {
+        if let Some(material) = self.materials.get(&index) {
+            return Ok(Rc::clone(material));
+        }
+
+        let material = Rc::new(self.pbr_material(index, "pbr")?);
+        self.materials.insert(index, Rc::clone(&material));
+        Ok(material)
+    }
+
+    fn skinned_material(&mut self, index: Option<usize>) -> io::Result<Rc<ParameterMaterial>> {
+        if let Some(material) = self.skinned_materials.get(&index) {
+            return Ok(Rc::clone(material));
+        }
+
+        let material = Rc::new(self.pbr_material(index, "skinned_pbr")?);
+        self.skinned_materials.insert(index, Rc::clone(&material));
+        Ok(material)
+    }
+
+    fn geometry(&mut self, mesh: usize, primitive: usize) -> io::Result<Rc<GpuGeometry>> {
+        if let Some(geometry) = self.geometries.get(&(mesh, primitive)) {
+            return Ok(Rc::clone(geometry));
+        }
+
+        let raw = &self.file.mesh(mesh)?.primitives[primitive];
+        let geometry = Rc::new(
+            self.file
+                .primitive_geometry(raw)?
+                .to_gpu_geometry(self.display),
+        );
+        self.geometries
+            .insert((mesh, primitive), Rc::clone(&geometry));
+        Ok(geometry)
+    }
+
+    fn import_light(&mut self, index: usize, light: usize) -> io::Result<()> {
+        let light = self
+            .file
+            .document
+            .extensions
+            .lights_punctual
+            .lights
+            .get(light)
+            .ok_or_else(|| missing("light", light))?;
+        let world = self.file.world_transform(index);
+
+        let intensity = light.intensity.unwrap_or(1.0);
+        let color = light.color.unwrap_or([1.0; 3]);
+        let color = rgb(
+            color[0] * intensity,
+            color[1] * intensity,
+            color[2] * intensity,
+        );
+
+        match light.kind.as_str() {
+            "directional" => {
+                self.result.scene.add_light(Light::Directional {
+                    direction: world * -BACKWARDS,
+                    color,
+                });
+            }
+            "point" => {
+                self.result.scene.add_light(Light::Point {
+                    position: world * ORIGIN,
+                    color,
+                    range: light
+                        .range
+                        .unwrap_or_else(|| (intensity * 256.0).sqrt().max(1.0)),
+                });
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn import_camera(&mut self, index: usize, id: NodeId, camera: usize) -> io::Result<()> {
+        let camera = self
+            .file
+            .document
+            .cameras
+            .get(camera)
+            .ok_or_else(|| missing("camera", camera))?;
+        let perspective = match &camera.perspective {
+            Some(perspective) => perspective,
+            None => return Ok(()),
+        };
+
+        let aspect_ratio = perspective.aspect_ratio.unwrap_or(1.0);
+        let depth_far = perspective.zfar.unwrap_or(perspective.znear * 10000.0);
+        if !(perspective.yfov > 0.0 && perspective.yfov < PI)
+            || aspect_ratio <= 0.0
+            || perspective.znear <= 0.0
+            || depth_far <= perspective.znear
+        {
+            return Err(invalid_data("camera has an invalid projection".to_string()));
+        }
+
+        let projection = Projection::perspective_fov(
+            perspective.yfov,
+            aspect_ratio,
+            perspective.znear,
+            depth_far,
+        );
+        let camera = Camera::new(self.file.world_transform(index), projection);
+        self.result.cameras.push((id, camera));
+
+        Ok(())
+    }
+
+    fn import_node(&mut self, index: usize, parent: Option<NodeId>) -> io::Result<()> {
+        let node = self.file.node(index)?;
+        let name = node
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("node{}", index));
+        let (mesh, skin, camera) = (node.mesh, node.skin, node.camera);
+        let light = node.extensions.light.as_ref().map(|light| light.light);
+        let children = node.children.clone();
+
+        let transform = match skin {
+            Some(skin) => {
+                let binding = self
+                    .bindings
+                    .get(skin)
+                    .ok_or_else(|| missing("skin", skin))?;
+                match self.file.parents[index] {
+                    Some(parent) => self.file.world_transform(parent).inverse() * binding.root,
+                    None => binding.root,
+                }
+            }
+            None => node_components(node).to_transform(),
+        };
+
+        let mut drawables: Vec<Rc<dyn Drawable>> = vec![];
+        if let Some(mesh) = mesh {
+            let primitives = self.file.mesh(mesh)?.primitives.len();
+            for primitive in 0..primitives {
+                let material = self.file.mesh(mesh)?.primitives[primitive].material;
+                match skin {
+                    Some(skin) => {
+                        let raw = &self.file.mesh(mesh)?.primitives[primitive];
+                        let geometry = self
+                            .file
+                            .primitive_skinned_geometry(raw, &self.bindings[skin].joints)?
+                            .to_gpu_geometry(self.display);
+                        let skinned = Rc::new(SkinnedMesh::new(
+                            Rc::new(geometry),
+                            self.skinned_material(material)?,
+                            Rc::clone(&self.bindings[skin].skeleton),
+                        ));
+                        self.result.skins[skin].meshes.push(Rc::clone(&skinned));
+                        drawables.push(skinned);
+                    }
+                    None => {
+                        let geometry = self.geometry(mesh, primitive)?;
+                        let material = self.material(material)?;
+                        drawables.push(Rc::new(Mesh::new(geometry, material)));
+                    }
+                }
+            }
+        }
+
+        let mut node = Node::new(&name).with_transform(transform);
+        if drawables.len() == 1 {
+            node.drawable = drawables.pop();
+        }
+        let graph = self.result.scene.get_graph_mut();
+        let missing_parent = || invalid_data(format!("parent of node {} was not imported", index));
+        let id = match parent {
+            Some(parent) => graph.spawn_child(parent, node).ok_or_else(missing_parent)?,
+            None => graph.spawn(node),
+        };
+        for (primitive, drawable) in drawables.into_iter().enumerate() {
+            let node = Node::new(&format!("primitive{}", primitive)).with_drawable(drawable);
+            graph.spawn_child(id, node).ok_or_else(missing_parent)?;
+        }
+        self.result.nodes[index] = Some(id);
+
+        if let Some(light) = light {
+            self.import_light(index, light)?;
+        }
+        if let Some(camera) = camera {
+            self.import_camera(index, id, camera)?;
+        }
+        for child in children {
+            self.import_node(child, Some(id))?;
+        }
+
+        Ok(())
+    }
+
+    fn import(mut self) -> io::Result<GltfScene> {
+        for skin in 0..self.file.document.skins.len() {
+            let binding = self.file.bind_skin(skin)?;
+            self.result.skins.push(GltfSkin {
+                name: self.file.document.skins[skin]
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("skin{}", skin)),
+                skeleton: Rc::clone(&binding.skeleton),
+                meshes: vec![],
+                clips: vec![],
+            });
+            self.bindings.push(binding);
+        }
+        for skin in 0..self.bindings.len() {
+            self.result.skins[skin].clips = self.file.skin_clips(skin, &self.bindings[skin])?;
+        }
+
+        for root in self.file.root_nodes()? {
+            self.import_node(root, None)?;
+        }
+
+        Ok(self.result)
+    }
+}
+
+pub fn import<P: AsRef<Path>>(
+    display: &Display,
+    programs_cache: &ProgramsCache,
+    path: P,
+) -> io::Result<GltfScene> {
+    let file = GltfFile::open(path.as_ref())?;
+    let nodes = vec![None; file.document.nodes.len()];
+
+    let importer = Importer {
+        file,
+        display,
+        programs_cache,
+        geometries: HashMap::new(),
+        materials: HashMap::new(),
+        skinned_materials: HashMap::new(),
+        textures: HashMap::new(),
+        srgb_textures: HashMap::new(),
+        bindings: vec![],
+        result: GltfScene {
+            scene: Scene::new(),
+            nodes,
+            cameras: vec![],
+            skins: vec![],
+        },
+    };
+
+    importer.import()
+}
+
+pub fn load_geometry<P: AsRef<Path>>(path: P) -> io::Result<Geometry> {
+    let file = GltfFile::open(path.as_ref())?;
+
+    let mut geometries = vec![];
+    for index in file.scene_nodes()? {
+        let mesh = match file.node(index)?.mesh {
+            Some(mesh) => mesh,
+            None => continue,
+        };
+        let world = file.world_transform(index);
+        for primitive in file.mesh(mesh)?.primitives.iter() {
+            geometries.push(file.primitive_geometry(primitive)?.transformed(world));
+        }
+    }
+
+    Ok(Geometry::merge(&geometries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_base64(bytes: &[u8]) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        let mut output = String::new();
+        for chunk in bytes.chunks(3) {
+            let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+                group | (byte as u32) << (16 - 8 * i)
+            });
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    output.push(ALPHABET[(group >> (18 - 6 * i) & 63) as usize] as char);
+                } else {
+                    output.push('=');
+                }
+            }
+        }
+        output
+    }
+
+    fn float_bytes(views: &[&[f32]]) -> (Vec<u8>, String) {
+        let mut bytes = vec![];
+        let mut buffer_views = vec![];
+        for view in views.iter() {
+            buffer_views.push(format!(
+                r#"{{"buffer": 0, "byteOffset": {}, "byteLength": {}}}"#,
+                bytes.len(),
+                view.len() * 4
+            ));
+            for value in view.iter() {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        (bytes, buffer_views.join(", "))
+    }
+
+    /// A glTF document from `body`, with every slice of `views` in its own
+    /// buffer view of one embedded buffer.
+    fn document(body: &str, views: &[&[f32]]) -> String {
+        let (bytes, buffer_views) = float_bytes(views);
+
+        format!(
+            r#"{{
+                "asset": {{"version": "2.0"}},
+                {},
+                "bufferViews": [{}],
+                "buffers": [{{
+                    "byteLength": {},
+                    "uri": "data:application/octet-stream;base64,{}"
+                }}]
+            }}"#,
+            body,
+            buffer_views,
+            bytes.len(),
+            encode_base64(&bytes)
+        )
+    }
+
+    fn open(name: &str, contents: &[u8]) -> io::Result<GltfFile> {
+        let path = std::env::temp_dir().join(format!("glib-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        let file = GltfFile::open(&path);
+        fs::remove_file(&path).unwrap();
+        file
+    }
+
+    fn assert_close(actual: [f32; 3], expected: [f32; 3]) {
+        for (actual, expected) in actual.iter().zip(expected.iter()) {
+            assert!(
+                (actual - expected).abs() < 1e-5,
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    const SKIN: &str = r#"
+        "nodes": [
+            {"name": "root", "translation": [0, 1, 0], "children": [1]},
+            {"name": "tip", "translation": [0, 2, 0]}
+        ],
+        "skins": [{"joints": [1, 0], "inverseBindMatrices": 0}],
+        "accessors": [
+            {"bufferView": 0, "componentType": 5126, "count": 2, "type": "MAT4"},
+            {"bufferView": 1, "componentType": 5126, "count": 2, "type": "SCALAR"},
+            {"bufferView": 2, "componentType": 5126, "count": 2, "type": "VEC3"},
+            {"bufferView": 3, "componentType": 5126, "count": 6, "type": "VEC3"}
+        ],
+        "animations": [{
+            "name": "wave",
+            "channels": [
+                {"sampler": 0, "target": {"node": 0, "path": "translation"}},
+                {"sampler": 1, "target": {"node": 1, "path": "translation"}},
+                {"sampler": 0, "target": {"node": 1, "path": "weights"}}
+            ],
+            "samplers": [
+                {"input": 1, "output": 2},
+                {"input": 1, "output": 3, "interpolation": "CUBICSPLINE"}
+            ]
+        }]
+    "#;
+
+    fn skin_document() -> String {
+        let translation = |y: f32| {
+            [
+                1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, y, 0.0, 1.0,
+            ]
+        };
+        let inverse_binds: Vec<f32> = translation(-3.0)
+            .iter()
+            .chain(translation(-1.0).iter())
+            .copied()
+            .collect();
+        // In tangent, value and out tangent for each of the two keyframes.
+        let cubic = [
+            0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        ];
+
+        document(
+            SKIN,
+            &[
+                &inverse_binds,
+                &[0.0, 1.0],
+                &[0.0, 0.0, 0.0, 2.0, 0.0, 0.0],
+                &cubic,
+            ],
+        )
+    }
+
+    #[test]
+    fn flips_texture_coordinates_to_a_bottom_left_origin() {
+        // Three positions followed by three texture coordinates, with V
+        // measured from the top of the image: (0, 0.25), (1, 0.25), (0, 1).
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "scenes": [{"nodes": [0]}],
+            "nodes": [{"mesh": 0}],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0, "TEXCOORD_0": 1}}]}],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"},
+                {"bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2"}
+            ],
+            "bufferViews": [
+                {"buffer": 0, "byteOffset": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 24}
+            ],
+            "buffers": [{
+                "byteLength": 60,
+                "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAAAAAAAgD4AAIA/AACAPgAAAAAAAIA/"
+            }]
+        }"#;
+
+        let path = std::env::temp_dir().join(format!("glib-uv-{}.gltf", std::process::id()));
+        fs::write(&path, json).unwrap();
+        let geometry = load_geometry(&path);
+        fs::remove_file(&path).unwrap();
+        let geometry = geometry.unwrap();
+
+        let texture_coordinates: Vec<[f32; 2]> = geometry
+            .vertices
+            .iter()
+            .map(|vertex| vertex.texture_coordinates.vector.as_array())
+            .collect();
+        assert_eq!(
+            texture_coordinates,
+            vec![[0.0, 0.75], [1.0, 0.75], [0.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn composes_node_transforms_down_the_hierarchy() {
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "nodes": [
+                {"translation": [1, 0, 0], "children": [1]},
+                {
+                    "translation": [0, 0, 1],
+                    "rotation": [0, 0.70710677, 0, 0.70710677],
+                    "scale": [2, 2, 2],
+                    "children": [2]
+                },
+                {"matrix": [1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 1, 0, 0, 1]}
+            ]
+        }"#;
+        let file = open("hierarchy.gltf", json.as_bytes()).unwrap();
+
+        assert_eq!(file.root_nodes().unwrap(), vec![0]);
+        assert_eq!(file.parents, vec![None, Some(0), Some(1)]);
+        assert_eq!(file.depth(2), 2);
+        assert_close(
+            (file.world_transform(2) * ORIGIN).vector.as_array(),
+            [1.0, 0.0, -1.0],
+        );
+    }
+
+    #[test]
+    fn binds_skins_parents_first_with_their_inverse_binds() {
+        let file = open("skin.gltf", skin_document().as_bytes()).unwrap();
+        let binding = file.bind_skin(0).unwrap();
+        let joints = binding.skeleton.get_joints();
+
+        assert_eq!(binding.joints, vec![1, 0]);
+        assert_eq!(joints[0].name, "root");
+        assert_eq!(joints[0].parent, None);
+        assert_eq!(joints[0].inverse_bind.elements[3][1], -1.0);
+        assert_close(
+            joints[0].rest.translation.vector.as_array(),
+            [0.0, 1.0, 0.0],
+        );
+        assert_eq!(joints[1].name, "tip");
+        assert_eq!(joints[1].parent, Some(0));
+        assert_eq!(joints[1].inverse_bind.elements[3][1], -3.0);
+    }
+
+    #[test]
+    fn reads_animation_channels_and_cubic_splines() {
+        let file = open("animation.gltf", skin_document().as_bytes()).unwrap();
+        let binding = file.bind_skin(0).unwrap();
+        let clips = file.skin_clips(0, &binding).unwrap();
+
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].name, "wave");
+        assert_eq!(clips[0].duration, 1.0);
+
+        let sample = |channel: &Channel| match &channel.track {
+            ChannelTrack::Translation(track) => track.sample(0.5).unwrap().vector.as_array(),
+            track => panic!("expected a translation track, got {:?}", track),
+        };
+        let channels = &clips[0].channels;
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].joint, 0);
+        assert_close(sample(&channels[0]), [1.0, 0.0, 0.0]);
+        // Hermite with an out tangent of 2: 0.125 * 2 + 0.5 * 1.
+        assert_eq!(channels[1].joint, 1);
+        assert_close(sample(&channels[1]), [0.75, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn reads_binary_gltf() {
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "nodes": [{"mesh": 0, "translation": [0, 0, 5]}],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}}]}],
+            "accessors": [{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"}],
+            "bufferViews": [{"buffer": 0, "byteLength": 36}],
+            "buffers": [{"byteLength": 36}]
+        }"#;
+        let mut json = json.as_bytes().to_vec();
+        while json.len() % 4 != 0 {
+            json.push(b' ');
+        }
+        let (binary, _) = float_bytes(&[&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]]);
+
+        let mut glb = vec![];
+        let length = 12 + 8 + json.len() + 8 + binary.len();
+        for word in [
+            GLB_MAGIC,
+            2,
+            length as u32,
+            json.len() as u32,
+            GLB_JSON_CHUNK,
+        ]
+        .iter()
+        {
+            glb.extend_from_slice(&word.to_le_bytes());
+        }
+        glb.extend_from_slice(&json);
+        glb.extend_from_slice(&(binary.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&GLB_BIN_CHUNK.to_le_bytes());
+        glb.extend_from_slice(&binary);
+
+        let path = std::env::temp_dir().join(format!("glib-binary-{}.glb", std::process::id()));
+        fs::write(&path, &glb).unwrap();
+        let geometry = load_geometry(&path);
+        fs::remove_file(&path).unwrap();
+
+        let positions: Vec<[f32; 3]> = geometry
+            .unwrap()
+            .vertices
+            .iter()
+            .map(|vertex| vertex.position.vector.as_array())
+            .collect();
+        assert_eq!(
+            positions,
+            vec![[0.0, 0.0, 5.0], [1.0, 0.0, 5.0], [0.0, 1.0, 5.0]]
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_required_extensions() {
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "extensionsUsed": ["KHR_draco_mesh_compression"],
+            "extensionsRequired": ["KHR_draco_mesh_compression"]
+        }"#;
+        let error = open("extensions.gltf", json.as_bytes()).err().unwrap();
+
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        assert!(error.to_string().contains("KHR_draco_mesh_compression"));
+    }
+}
@@ -0,0 +1,489 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::super::geometry::{Geometry, Vertex};
+use super::super::math::raw::vector::Vector3;
+use super::super::math::*;
+use super::super::program::ProgramsCache;
+use super::super::rendering::ParameterMaterial;
+
+type Corner = (usize, Option<usize>, Option<usize>);
+
+fn invalid_data(line: usize, message: String) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", line, message),
+    )
+}
+
+fn logical_lines(source: &str) -> Vec<(usize, String)> {
+    let mut lines = vec![];
+    let mut pending: Option<(usize, String)> = None;
+
+    for (index, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim_end();
+        let (number, mut text) = pending.take().unwrap_or((index + 1, String::new()));
+
+        match line.strip_suffix('\\') {
+            Some(line) => {
+                text.push_str(line);
+                text.push(' ');
+                pending = Some((number, text));
+            }
+            None => {
+                text.push_str(line);
+                lines.push((number, text));
+            }
+        }
+    }
+    lines.extend(pending);
+
+    lines
+}
+
+fn parse_floats(line: usize, tokens: &[&str], min: usize) -> io::Result<Vec<f32>> {
+    let values = tokens
+        .iter()
+        .map(|token| {
+            token
+                .parse::<f32>()
+                .map_err(|_| invalid_data(line, format!("invalid number \"{}\"", token)))
+        })
+        .collect::<io::Result<Vec<f32>>>()?;
+    if values.len() < min {
+        return Err(invalid_data(
+            line,
+            format!("expected at least {} values", min),
+        ));
+    }
+
+    Ok(values)
+}
+
+fn resolve_index(line: usize, token: &str, count: usize) -> io::Result<usize> {
+    let index = token
+        .parse::<i64>()
+        .map_err(|_| invalid_data(line, format!("invalid index \"{}\"", token)))?;
+    let resolved = match index {
+        0 => None,
+        i if i > 0 => Some(i - 1),
+        i => Some(count as i64 + i),
+    };
+
+    resolved
+        .filter(|&i| i >= 0 && (i as usize) < count)
+        .map(|i| i as usize)
+        .ok_or_else(|| invalid_data(line, format!("index {} is out of range", index)))
+}
+
+fn direction(vector: Vector3) -> Direction3 {
+    if vector.length_squared() > 0.0 {
+        dis3(vector.x, vector.y, vector.z).normalized()
+    } else {
+        BACKWARDS
+    }
+}
+
+fn texture_path(base_path: &Path, tokens: &[&str]) -> Option<PathBuf> {
+    tokens.last().map(|name| base_path.join(name))
+}
+
+#[derive(Clone, Debug)]
+pub struct ObjMaterial {
+    pub name: String,
+    pub ambient_color: Color,
+    pub diffuse_color: Color,
+    pub specular_color: Color,
+    pub emissive_color: Color,
+    pub shininess: f32,
+    pub opacity: f32,
+    pub diffuse_texture: Option<PathBuf>,
+    pub specular_texture: Option<PathBuf>,
+    pub normal_texture: Option<PathBuf>,
+}
+
+impl ObjMaterial {
+    pub fn new(name: &str) -> ObjMaterial {
+        ObjMaterial {
+            name: name.to_string(),
+            ambient_color: rgb(0.0, 0.0, 0.0),
+            diffuse_color: rgb(0.8, 0.8, 0.8),
+            specular_color: rgb(0.0, 0.0, 0.0),
+            emissive_color: rgb(0.0, 0.0, 0.0),
+            shininess: 10.0,
+            opacity: 1.0,
+            diffuse_texture: None,
+            specular_texture: None,
+            normal_texture: None,
+        }
+    }
+
+    /// Builds a `blinn_phong` material from the colors. That program has no
+    /// texture inputs, so the texture maps are not loaded: they are left as
+    /// paths for callers that bind them to a textured program themselves.
+    pub fn to_material(&self, programs_cache: &ProgramsCache) -> io::Result<ParameterMaterial> {
+        let mut material = ParameterMaterial::from_cache(programs_cache, "blinn_phong")
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "program \"blinn_phong\" not found".to_string(),
+                )
+            })?;
+
+        let diffuse = self.diffuse_color;
+        let specular = self.specular_color;
+        material.set(
+            "diffuse_color",
+            rgba(diffuse.r, diffuse.g, diffuse.b, self.opacity),
+        );
+        material.set("specular_color", [specular.r, specular.g, specular.b]);
+        material.set("shininess", self.shininess);
+
+        Ok(material)
+    }
+}
+
+pub fn read_mtl<P: AsRef<Path>>(path: P) -> io::Result<Vec<ObjMaterial>> {
+    let path = path.as_ref();
+    let base_path = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let source = fs::read_to_string(path)?;
+
+    let mut materials: Vec<ObjMaterial> = vec![];
+    for (line, text) in logical_lines(&source) {
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        let (keyword, arguments) = match tokens.split_first() {
+            Some((keyword, arguments)) => (*keyword, arguments),
+            None => continue,
+        };
+
+        if keyword == "newmtl" {
+            materials.push(ObjMaterial::new(&arguments.join(" ")));
+            continue;
+        }
+        let material = match materials.last_mut() {
+            Some(material) => material,
+            None => return Err(invalid_data(line, format!("\"{}\" before newmtl", keyword))),
+        };
+        let color = |arguments: &[&str]| -> io::Result<Color> {
+            let values = parse_floats(line, arguments, 1)?;
+            Ok(match values.len() {
+                1 | 2 => rgb(values[0], values[0], values[0]),
+                _ => rgb(values[0], values[1], values[2]),
+            })
+        };
+
+        match keyword {
+            "Ka" => material.ambient_color = color(arguments)?,
+            "Kd" => material.diffuse_color = color(arguments)?,
+            "Ks" => material.specular_color = color(arguments)?,
+            "Ke" => material.emissive_color = color(arguments)?,
+            "Ns" => material.shininess = parse_floats(line, arguments, 1)?[0],
+            "d" => material.opacity = parse_floats(line, arguments, 1)?[0],
+            "Tr" => material.opacity = 1.0 - parse_floats(line, arguments, 1)?[0],
+            "map_Kd" => material.diffuse_texture = texture_path(&base_path, arguments),
+            "map_Ks" => material.specular_texture = texture_path(&base_path, arguments),
+            "map_Bump" | "map_bump" | "bump" | "norm" => {
+                material.normal_texture = texture_path(&base_path, arguments)
+            }
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+pub struct ObjGroup {
+    pub material: Option<ObjMaterial>,
+    pub geometry: Geometry,
+}
+
+struct FaceGroup {
+    material: Option<String>,
+    triangles: Vec<[Corner; 3]>,
+}
+
+impl Geometry {
+    pub fn from_obj<P: AsRef<Path>>(path: P) -> io::Result<Vec<ObjGroup>> {
+        let path = path.as_ref();
+        let base_path = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let source = fs::read_to_string(path)?;
+
+        let mut positions: Vec<Position3> = vec![];
+        let mut texture_coordinates: Vec<Position2> = vec![];
+        let mut normals: Vec<Direction3> = vec![];
+        let mut materials: HashMap<String, ObjMaterial> = HashMap::new();
+        let mut groups: Vec<FaceGroup> = vec![];
+        let mut current: Option<usize> = None;
+
+        for (line, text) in logical_lines(&source) {
+            let tokens: Vec<&str> = text.split_whitespace().collect();
+            let (keyword, arguments) = match tokens.split_first() {
+                Some((keyword, arguments)) => (*keyword, arguments),
+                None => continue,
+            };
+
+            match keyword {
+                "v" => {
+                    let v = parse_floats(line, arguments, 3)?;
+                    positions.push(pos3(v[0], v[1], v[2]));
+                }
+                "vt" => {
+                    let v = parse_floats(line, arguments, 1)?;
+                    texture_coordinates.push(pos2(v[0], v.get(1).copied().unwrap_or(0.0)));
+                }
+                "vn" => {
+                    let v = parse_floats(line, arguments, 3)?;
+                    normals.push(direction(Vector3 {
+                        x: v[0],
+                        y: v[1],
+                        z: v[2],
+                    }));
+                }
+                "f" => {
+                    let corners = arguments
+                        .iter()
+                        .map(|argument| {
+                            let mut parts = argument.split('/');
+                            let position = match parts.next() {
+                                Some(part) if !part.is_empty() => {
+                                    resolve_index(line, part, positions.len())?
+                                }
+                                _ => {
+                                    return Err(invalid_data(
+                                        line,
+                                        format!("face vertex \"{}\" has no position", argument),
+                                    ))
+                                }
+                            };
+                            let optional = |part: Option<&str>, count: usize| match part {
+                                Some(part) if !part.is_empty() => {
+                                    resolve_index(line, part, count).map(Some)
+                                }
+                                _ => Ok(None),
+                            };
+                            let uv = optional(parts.next(), texture_coordinates.len())?;
+                            let normal = optional(parts.next(), normals.len())?;
+
+                            Ok((position, uv, normal))
+                        })
+                        .collect::<io::Result<Vec<Corner>>>()?;
+                    if corners.len() < 3 {
+                        return Err(invalid_data(
+                            line,
+                            "face has fewer than three vertices".to_string(),
+                        ));
+                    }
+
+                    let group = match current {
+                        Some(group) => group,
+                        None => {
+                            groups.push(FaceGroup {
+                                material: None,
+                                triangles: vec![],
+                            });
+                            current = Some(groups.len() - 1);
+                            groups.len() - 1
+                        }
+                    };
+                    for i in 2..corners.len() {
+                        groups[group]
+                            .triangles
+                            .push([corners[0], corners[i - 1], corners[i]]);
+                    }
+                }
+                "usemtl" => {
+                    let name = arguments.join(" ");
+                    current = match groups
+                        .iter()
+                        .position(|group| group.material.as_deref() == Some(name.as_str()))
+                    {
+                        Some(group) => Some(group),
+                        None => {
+                            groups.push(FaceGroup {
+                                material: Some(name),
+                                triangles: vec![],
+                            });
+                            Some(groups.len() - 1)
+                        }
+                    };
+                }
+                "mtllib" => {
+                    // A missing or broken library leaves its materials to the
+                    // defaults below, the same as an unknown usemtl name.
+                    for name in arguments {
+                        let library = read_mtl(base_path.join(name)).unwrap_or_default();
+                        for material in library {
+                            materials.insert(material.name.clone(), material);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let zero = Vector3 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        let mut generated = vec![zero; positions.len()];
+        for triangle in groups.iter().flat_map(|group| group.triangles.iter()) {
+            if triangle.iter().all(|corner| corner.2.is_some()) {
+                continue;
+            }
+            let a = positions[triangle[0].0];
+            let b = positions[triangle[1].0];
+            let c = positions[triangle[2].0];
+            let (u, v) = ((b - a).vector, (c - a).vector);
+            let normal = Vector3 {
+                x: u.y * v.z - u.z * v.y,
+                y: u.z * v.x - u.x * v.z,
+                z: u.x * v.y - u.y * v.x,
+            };
+
+            for corner in triangle.iter() {
+                generated[corner.0] = generated[corner.0] + normal;
+            }
+        }
+
+        let groups = groups
+            .into_iter()
+            .filter(|group| !group.triangles.is_empty())
+            .map(|group| {
+                let mut geometry = Geometry::empty();
+                let mut indices: HashMap<Corner, u32> = HashMap::new();
+
+                for &corner in group.triangles.iter().flatten() {
+                    let index = *indices.entry(corner).or_insert_with(|| {
+                        let (position, uv, normal) = corner;
+                        geometry.vertices.push(Vertex::new(
+                            positions[position],
+                            normal.map_or_else(|| direction(generated[position]), |n| normals[n]),
+                            uv.map_or(pos2(0.0, 0.0), |uv| texture_coordinates[uv]),
+                        ));
+                        geometry.vertices.len() as u32 - 1
+                    });
+                    geometry.indices.push(index);
+                }
+
+                let material = group.material.map(|name| {
+                    materials
+                        .get(&name)
+                        .cloned()
+                        .unwrap_or_else(|| ObjMaterial::new(&name))
+                });
+
+                ObjGroup { material, geometry }
+            })
+            .collect();
+
+        Ok(groups)
+    }
+}
+
+pub fn load_geometry<P: AsRef<Path>>(path: P) -> io::Result<Geometry> {
+    let groups = Geometry::from_obj(path)?;
+    let geometries: Vec<Geometry> = groups.into_iter().map(|group| group.geometry).collect();
+
+    Ok(Geometry::merge(&geometries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(name: &str, source: &str) -> Vec<ObjGroup> {
+        let path = std::env::temp_dir().join(format!("glib-{}-{}.obj", name, std::process::id()));
+        fs::write(&path, source).unwrap();
+        let groups = Geometry::from_obj(&path);
+        fs::remove_file(&path).unwrap();
+        groups.unwrap()
+    }
+
+    fn normals(geometry: &Geometry) -> Vec<[f32; 3]> {
+        geometry
+            .vertices
+            .iter()
+            .map(|vertex| vertex.normal.vector.as_array())
+            .collect()
+    }
+
+    #[test]
+    fn fans_polygons_with_negative_indices() {
+        let groups = parse(
+            "polygon",
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf -4 -3 -2 -1\n",
+        );
+
+        assert_eq!(groups.len(), 1);
+        let geometry = &groups[0].geometry;
+        assert!(groups[0].material.is_none());
+        assert_eq!(geometry.vertices.len(), 4);
+        assert_eq!(geometry.indices, vec![0, 1, 2, 0, 2, 3]);
+        assert_eq!(normals(geometry), vec![[0.0, 0.0, 1.0]; 4]);
+    }
+
+    #[test]
+    fn reads_normal_only_and_texture_only_corners() {
+        let groups = parse(
+            "corners",
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\n\
+             vt 0 0\nvt 1 0\nvt 0 0.5\n\
+             vn 0 1 0\n\
+             f 1//1 2//1 3//1\n\
+             f 1/1 2/2 3/3\n",
+        );
+        let geometry = &groups[0].geometry;
+
+        assert_eq!(geometry.vertices.len(), 6);
+        assert_eq!(geometry.indices, vec![0, 1, 2, 3, 4, 5]);
+
+        let normals = normals(geometry);
+        assert_eq!(normals[..3], [[0.0, 1.0, 0.0]; 3]);
+        assert_eq!(normals[3..], [[0.0, 0.0, 1.0]; 3]);
+
+        let texture_coordinates: Vec<[f32; 2]> = geometry
+            .vertices
+            .iter()
+            .map(|vertex| vertex.texture_coordinates.vector.as_array())
+            .collect();
+        assert_eq!(texture_coordinates[..3], [[0.0, 0.0]; 3]);
+        assert_eq!(
+            texture_coordinates[3..],
+            [[0.0, 0.0], [1.0, 0.0], [0.0, 0.5]]
+        );
+    }
+
+    #[test]
+    fn splits_groups_by_material_and_survives_missing_libraries() {
+        let library = format!("glib-materials-{}.mtl", std::process::id());
+        let library_path = std::env::temp_dir().join(&library);
+        fs::write(&library_path, "newmtl red\nKd 1 0 0\n").unwrap();
+
+        let groups = parse(
+            "materials",
+            &format!(
+                "mtllib glib-missing.mtl {}\n\
+                 v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\n\
+                 usemtl red\nf 1 2 3\n\
+                 usemtl blue\nf 2 4 3\n\
+                 usemtl red\nf 3 2 4\n",
+                library
+            ),
+        );
+        fs::remove_file(&library_path).unwrap();
+
+        assert_eq!(groups.len(), 2);
+        let red = groups[0].material.as_ref().unwrap();
+        assert_eq!(red.name, "red");
+        assert_eq!(red.diffuse_color.to_array(), [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(groups[0].geometry.indices.len(), 6);
+
+        let blue = groups[1].material.as_ref().unwrap();
+        assert_eq!(blue.name, "blue");
+        assert_eq!(blue.diffuse_color.to_array(), [0.8, 0.8, 0.8, 1.0]);
+        assert_eq!(groups[1].geometry.indices.len(), 3);
+    }
+}
@@ -69,6 +69,8 @@ pub struct KeyboardMouseVirtualGamepad {
     left_stick_bindings: Vec<StickBinding>,
     right_stick_bindings: Vec<StickBinding>,
     button_bindings: HashMap<VirtualGamepadButton, Vec<RealInput>>,
+    screenshot_bindings: Vec<RealInput>,
+    screenshot_requested: bool,
     currently_pressed: HashSet<RealInput>,
 }
 
@@ -104,6 +106,8 @@ impl KeyboardMouseVirtualGamepad {
             left_stick_bindings: vec![],
             right_stick_bindings: vec![],
             button_bindings,
+            screenshot_bindings: vec![],
+            screenshot_requested: false,
             currently_pressed: HashSet::new(),
         }
     }
@@ -143,6 +147,27 @@ impl KeyboardMouseVirtualGamepad {
         self.bind_stick(VirtualGamepadStick::Right, StickBinding::Mouse);
     }
 
+    pub fn bind_screenshot(&mut self, input: RealInput) {
+        self.screenshot_bindings.push(input);
+    }
+
+    pub fn clear_screenshot_binding(&mut self) {
+        self.screenshot_bindings.clear();
+    }
+
+    pub fn take_screenshot_request(&mut self) -> bool {
+        let requested = self.screenshot_requested;
+        self.screenshot_requested = false;
+        requested
+    }
+
+    fn press(&mut self, input: RealInput) {
+        if self.screenshot_bindings.contains(&input) && !self.currently_pressed.contains(&input) {
+            self.screenshot_requested = true;
+        }
+        self.currently_pressed.insert(input);
+    }
+
     pub fn clear_stick_binding(&mut self, stick: VirtualGamepadStick) {
         match stick {
             VirtualGamepadStick::Left => self.left_stick_bindings.clear(),
@@ -234,8 +259,7 @@ impl KeyboardMouseVirtualGamepad {
         match event {
             KeyboardEvent::KeyDown { input, .. } => {
                 if let Some(virtual_keycode) = input.virtual_keycode {
-                    self.currently_pressed
-                        .insert(RealInput::KeyboardKey(virtual_keycode));
+                    self.press(RealInput::KeyboardKey(virtual_keycode));
                 }
             }
             KeyboardEvent::KeyUp { input, .. } => {
@@ -250,8 +274,7 @@ impl KeyboardMouseVirtualGamepad {
     pub fn on_mouse_event(&mut self, event: MouseEvent) {
         match event {
             MouseEvent::MouseDown { button, .. } => {
-                self.currently_pressed
-                    .insert(RealInput::MouseButton(button));
+                self.press(RealInput::MouseButton(button));
             }
             MouseEvent::MouseUp { button, .. } => {
                 self.currently_pressed
@@ -263,18 +286,18 @@ impl KeyboardMouseVirtualGamepad {
             MouseEvent::MouseWheel { delta, .. } => match delta {
                 MouseScrollDelta::LineDelta(_x, y) => {
                     if y > 0.0 {
-                        self.currently_pressed.insert(RealInput::MouseWheelUp);
+                        self.press(RealInput::MouseWheelUp);
                     }
                     if y < 0.0 {
-                        self.currently_pressed.insert(RealInput::MouseWheelDown);
+                        self.press(RealInput::MouseWheelDown);
                     }
                 }
                 MouseScrollDelta::PixelDelta(delta) => {
                     if delta.y > 0.0 {
-                        self.currently_pressed.insert(RealInput::MouseWheelUp);
+                        self.press(RealInput::MouseWheelUp);
                     }
                     if delta.y < 0.0 {
-                        self.currently_pressed.insert(RealInput::MouseWheelDown);
+                        self.press(RealInput::MouseWheelDown);
                     }
                 }
             },
@@ -4,6 +4,9 @@ pub mod math;
 pub mod geometry;
 
 pub mod rendering;
+pub mod animation;
+pub mod import;
+pub mod export;
 
 
 #[macro_use]
@@ -3,10 +3,17 @@ use super::*;
 
 use glium::Display;
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RenderPath {
+    Forward,
+    Deferred,
+}
+
 #[derive(Copy, Clone)]
 pub struct Camera {
     pub transform: Transform,
     pub projection: Projection,
+    pub render_path: RenderPath,
 }
 
 #[allow(dead_code)]
@@ -15,9 +22,15 @@ impl Camera {
         Camera {
             transform,
             projection,
+            render_path: RenderPath::Forward,
         }
     }
 
+    pub fn with_render_path(mut self, render_path: RenderPath) -> Camera {
+        self.render_path = render_path;
+        self
+    }
+
     pub fn perspective_for_display(display: &Display, transform: Transform, fov_y: f32) -> Camera {
         let gl_window = display.gl_window();
         let window = gl_window.window();
@@ -39,6 +52,10 @@ impl Camera {
         self.projection.matrix * self.transform.inverse
     }
 
+    pub fn get_inverse_view_projection_matrix(&self) -> Matrix4 {
+        self.transform.matrix * self.projection.inverse
+    }
+
     pub fn get_world_position(&self) -> Position3 {
         self.transform * pos3(0.0, 0.0, 0.0)
     }
@@ -27,7 +27,7 @@ pub fn rgb(r: f32, g: f32, b: f32) -> Color {
 const SRGB_ALPHA: f32 = 0.055;
 
 #[allow(dead_code)]
-fn srgb_to_linear(channel: f32) -> f32 {
+pub(crate) fn srgb_to_linear(channel: f32) -> f32 {
     if channel <= 0.04045 {
         channel / 12.92
     } else {
@@ -35,6 +35,15 @@ fn srgb_to_linear(channel: f32) -> f32 {
     }
 }
 
+#[allow(dead_code)]
+pub(crate) fn linear_to_srgb(channel: f32) -> f32 {
+    if channel <= 0.0031308 {
+        channel * 12.92
+    } else {
+        (1.0 + SRGB_ALPHA) * channel.powf(1.0 / 2.4) - SRGB_ALPHA
+    }
+}
+
 #[allow(dead_code)]
 pub fn srgba(r: f32, g: f32, b: f32, a: f32) -> Color {
     Color {
@@ -1,6 +1,7 @@
 pub mod camera;
 pub mod color;
 pub mod projection;
+pub mod quaternion;
 pub mod raw;
 pub mod transform;
 pub mod vector;
@@ -8,5 +9,6 @@ pub mod vector;
 pub use camera::*;
 pub use color::*;
 pub use projection::*;
+pub use quaternion::*;
 pub use transform::*;
 pub use vector::*;
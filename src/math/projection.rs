@@ -4,6 +4,9 @@ use std::f32::consts::PI;
 #[derive(Copy, Clone)]
 pub struct Projection {
     pub matrix: Matrix4,
+    pub inverse: Matrix4,
+    pub depth_near: f32,
+    pub depth_far: f32,
     _private: (),
 }
 
@@ -56,8 +59,20 @@ impl Projection {
             ],
         };
 
+        let inverse = Matrix4 {
+            elements: [
+                [1.0 / x, 0.0, 0.0, 0.0],
+                [0.0, 1.0 / y, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0 / d],
+                [a / x, b / y, -1.0, c / d],
+            ],
+        };
+
         Projection {
             matrix,
+            inverse,
+            depth_near,
+            depth_far,
             _private: (),
         }
     }
@@ -0,0 +1,243 @@
+use super::raw::matrix::*;
+use super::raw::vector::*;
+use super::vector::*;
+use std::ops;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quaternion {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+pub const IDENTITY_ROTATION: Quaternion = Quaternion {
+    x: 0.0,
+    y: 0.0,
+    z: 0.0,
+    w: 1.0,
+};
+
+#[allow(dead_code)]
+impl Quaternion {
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Quaternion {
+        Quaternion { x, y, z, w }
+    }
+
+    pub fn from_axis_angle(axis: Direction3, angle: f32) -> Quaternion {
+        let (sin, cos) = (0.5 * angle).sin_cos();
+        let axis = axis.vector;
+
+        Quaternion {
+            x: axis.x * sin,
+            y: axis.y * sin,
+            z: axis.z * sin,
+            w: cos,
+        }
+    }
+
+    /// Extracts the rotation from the upper 3x3 of a matrix, which must be
+    /// orthonormal.
+    pub fn from_matrix(matrix: &Matrix4) -> Quaternion {
+        let m = |row: usize, column: usize| matrix.elements[column][row];
+        let trace = m(0, 0) + m(1, 1) + m(2, 2);
+
+        let quaternion = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Quaternion {
+                x: (m(2, 1) - m(1, 2)) / s,
+                y: (m(0, 2) - m(2, 0)) / s,
+                z: (m(1, 0) - m(0, 1)) / s,
+                w: 0.25 * s,
+            }
+        } else if m(0, 0) > m(1, 1) && m(0, 0) > m(2, 2) {
+            let s = (1.0 + m(0, 0) - m(1, 1) - m(2, 2)).sqrt() * 2.0;
+            Quaternion {
+                x: 0.25 * s,
+                y: (m(0, 1) + m(1, 0)) / s,
+                z: (m(0, 2) + m(2, 0)) / s,
+                w: (m(2, 1) - m(1, 2)) / s,
+            }
+        } else if m(1, 1) > m(2, 2) {
+            let s = (1.0 + m(1, 1) - m(0, 0) - m(2, 2)).sqrt() * 2.0;
+            Quaternion {
+                x: (m(0, 1) + m(1, 0)) / s,
+                y: 0.25 * s,
+                z: (m(1, 2) + m(2, 1)) / s,
+                w: (m(0, 2) - m(2, 0)) / s,
+            }
+        } else {
+            let s = (1.0 + m(2, 2) - m(0, 0) - m(1, 1)).sqrt() * 2.0;
+            Quaternion {
+                x: (m(0, 2) + m(2, 0)) / s,
+                y: (m(1, 2) + m(2, 1)) / s,
+                z: 0.25 * s,
+                w: (m(1, 0) - m(0, 1)) / s,
+            }
+        };
+
+        quaternion.normalized()
+    }
+
+    pub fn dot(self, other: Quaternion) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalized(self) -> Quaternion {
+        let length = self.length();
+        if length == 0.0 {
+            return IDENTITY_ROTATION;
+        }
+
+        self * (1.0 / length)
+    }
+
+    pub fn conjugate(self) -> Quaternion {
+        Quaternion {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+            w: self.w,
+        }
+    }
+
+    pub fn nlerp(self, other: Quaternion, t: f32) -> Quaternion {
+        let other = if self.dot(other) < 0.0 { -other } else { other };
+
+        (self * (1.0 - t) + other * t).normalized()
+    }
+
+    pub fn slerp(self, other: Quaternion, t: f32) -> Quaternion {
+        let mut cos = self.dot(other);
+        let other = if cos < 0.0 {
+            cos = -cos;
+            -other
+        } else {
+            other
+        };
+
+        if cos > 0.9995 {
+            return self.nlerp(other, t);
+        }
+
+        let angle = cos.acos();
+        let sin = angle.sin();
+        let a = ((1.0 - t) * angle).sin() / sin;
+        let b = (t * angle).sin() / sin;
+
+        (self * a + other * b).normalized()
+    }
+
+    pub fn rotate(self, vector: Vector3) -> Vector3 {
+        let q = Vector3 {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+        };
+        let t = cross(q, vector) * 2.0;
+
+        vector + t * self.w + cross(q, t)
+    }
+
+    pub fn to_matrix(self) -> Matrix4 {
+        let Quaternion { x, y, z, w } = self;
+
+        Matrix4 {
+            elements: [
+                [
+                    1.0 - 2.0 * (y * y + z * z),
+                    2.0 * (x * y + w * z),
+                    2.0 * (x * z - w * y),
+                    0.0,
+                ],
+                [
+                    2.0 * (x * y - w * z),
+                    1.0 - 2.0 * (x * x + z * z),
+                    2.0 * (y * z + w * x),
+                    0.0,
+                ],
+                [
+                    2.0 * (x * z + w * y),
+                    2.0 * (y * z - w * x),
+                    1.0 - 2.0 * (x * x + y * y),
+                    0.0,
+                ],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+}
+
+fn cross(a: Vector3, b: Vector3) -> Vector3 {
+    Vector3 {
+        x: a.y * b.z - a.z * b.y,
+        y: a.z * b.x - a.x * b.z,
+        z: a.x * b.y - a.y * b.x,
+    }
+}
+
+impl ops::Mul<Quaternion> for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, rhs: Quaternion) -> Quaternion {
+        Quaternion {
+            x: self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            y: self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            z: self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            w: self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        }
+    }
+}
+
+impl ops::Mul<f32> for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, rhs: f32) -> Quaternion {
+        Quaternion {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            z: self.z * rhs,
+            w: self.w * rhs,
+        }
+    }
+}
+
+impl ops::Add<Quaternion> for Quaternion {
+    type Output = Quaternion;
+
+    fn add(self, rhs: Quaternion) -> Quaternion {
+        Quaternion {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+            w: self.w + rhs.w,
+        }
+    }
+}
+
+impl ops::Neg for Quaternion {
+    type Output = Quaternion;
+
+    fn neg(self) -> Quaternion {
+        Quaternion {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+            w: -self.w,
+        }
+    }
+}
+
+impl ops::Mul<Displacement3> for Quaternion {
+    type Output = Displacement3;
+
+    fn mul(self, rhs: Displacement3) -> Displacement3 {
+        let vector = self.rotate(rhs.vector);
+
+        Displacement3 { vector }
+    }
+}
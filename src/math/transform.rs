@@ -1,3 +1,4 @@
+use super::quaternion::*;
 use super::raw::matrix::*;
 use super::vector::*;
 use std::ops;
@@ -130,6 +131,27 @@ impl Transform {
 
         Transform { matrix, inverse }
     }
+
+    pub fn rotation(rotation: Quaternion) -> Transform {
+        let rotation = rotation.normalized();
+
+        let matrix = rotation.to_matrix();
+        let inverse = matrix.transposed();
+
+        Transform { matrix, inverse }
+    }
+
+    pub fn from_translation_rotation_scale(
+        translation: Displacement3,
+        rotation: Quaternion,
+        scale: Displacement3,
+    ) -> Transform {
+        let scale = scale.vector;
+
+        Transform::from(translation)
+            * Transform::rotation(rotation)
+            * Transform::scale(scale.x, scale.y, scale.z)
+    }
 }
 
 #[allow(dead_code)]
@@ -41,23 +41,37 @@ fn get_file_from_dir<'a>(dir: &'a Dir<'a>, try_paths_in_order: &[&str]) -> Optio
     None
 }
 
+pub static STANDARD_CHUNKS_DIRECTORY: Dir = include_dir!("shaders/chunks");
+pub static STANDARD_MATERIALS_DIRECTORY: Dir = include_dir!("shaders/materials");
+pub(crate) static INTERNAL_MATERIALS_DIRECTORY: Dir = include_dir!("shaders/internal");
+
+fn get_file_from_dirs<'a>(dirs: &[&'a Dir<'a>], try_paths_in_order: &[&str]) -> Option<File<'a>> {
+    for dir in dirs {
+        if let Some(file) = get_file_from_dir(dir, try_paths_in_order) {
+            return Some(file);
+        }
+    }
+
+    None
+}
+
 struct ChunksCache<'a> {
-    chunks_directory: &'a Dir<'a>,
+    chunks_directories: Vec<&'a Dir<'a>>,
     chunks_cache: HashMap<String, ShaderFile>,
     glsl_version: String,
 }
 
 impl<'a> ChunksCache<'a> {
-    fn new(chunks_directory: &'a Dir, glsl_version: &'a str) -> ChunksCache<'a> {
+    fn new(chunks_directories: &[&'a Dir<'a>], glsl_version: &str) -> ChunksCache<'a> {
         ChunksCache {
-            chunks_directory,
+            chunks_directories: chunks_directories.to_vec(),
             chunks_cache: HashMap::new(),
             glsl_version: glsl_version.replace(" ", ""),
         }
     }
 
     fn get_chunk(&mut self, chunk_path: String) -> ShaderFile {
-        let chunks_directory = self.chunks_directory;
+        let chunks_directories = &self.chunks_directories;
         let glsl_version = &self.glsl_version;
 
         self.chunks_cache
@@ -68,8 +82,8 @@ impl<'a> ChunksCache<'a> {
                 let full_name_functions = format!("functions/{}.{}.glsl", chunk_path, glsl_version);
                 let full_name_functions_no_version = format!("functions/{}.glsl", chunk_path);
 
-                let file = get_file_from_dir(
-                    chunks_directory,
+                let file = get_file_from_dirs(
+                    chunks_directories,
                     &[
                         &full_name,
                         &full_name_no_version,
@@ -147,16 +161,24 @@ fn create_program_part(
 
 fn load_program<F: Facade>(
     facade: &F,
-    materials_directory: &Dir,
+    materials_directories: &[&Dir],
     chunks_cache: &mut ChunksCache,
     material_name: &str,
     glsl_version: &str,
 ) -> Program {
+    let material_directory = match materials_directories
+        .iter()
+        .find(|directory| directory.get_dir(material_name).is_some())
+    {
+        Some(directory) => *directory,
+        None => panic!("No directory found for \"{}\" material!", material_name),
+    };
+
     let vertex_file_path = format!("{}/vert.{}.glsl", material_name, glsl_version);
     let vertex_file_path_no_version = format!("{}/vert.glsl", material_name);
 
     let vertex_file = get_file_from_dir(
-        materials_directory,
+        material_directory,
         &[&vertex_file_path, &vertex_file_path_no_version],
     );
     if vertex_file == None {
@@ -180,7 +202,7 @@ fn load_program<F: Facade>(
     let fragment_file_path_no_version = format!("{}/frag.glsl", material_name);
 
     let fragment_file = get_file_from_dir(
-        materials_directory,
+        material_directory,
         &[&fragment_file_path, &fragment_file_path_no_version],
     );
 
@@ -213,6 +235,7 @@ fn load_program<F: Facade>(
 
 pub struct ProgramsCache {
     programs: HashMap<String, Rc<Program>>,
+    glsl_version: String,
 }
 
 impl ProgramsCache {
@@ -222,24 +245,73 @@ impl ProgramsCache {
         chunks_directory: &Dir,
         material_directory: &Dir,
     ) -> ProgramsCache {
-        let mut chunks_cache = ChunksCache::new(&chunks_directory, glsl_version);
+        ProgramsCache::layered(
+            facade,
+            glsl_version,
+            &[chunks_directory],
+            &[material_directory],
+        )
+    }
+
+    pub fn standard<F: Facade>(facade: &F, glsl_version: &str) -> ProgramsCache {
+        ProgramsCache::layered(
+            facade,
+            glsl_version,
+            &[&STANDARD_CHUNKS_DIRECTORY],
+            &[&STANDARD_MATERIALS_DIRECTORY],
+        )
+    }
+
+    pub fn with_standard_library<F: Facade>(
+        facade: &F,
+        glsl_version: &str,
+        chunks_directory: &Dir,
+        material_directory: &Dir,
+    ) -> ProgramsCache {
+        ProgramsCache::layered(
+            facade,
+            glsl_version,
+            &[chunks_directory, &STANDARD_CHUNKS_DIRECTORY],
+            &[material_directory, &STANDARD_MATERIALS_DIRECTORY],
+        )
+    }
+
+    pub fn layered<F: Facade>(
+        facade: &F,
+        glsl_version: &str,
+        chunks_directories: &[&Dir],
+        material_directories: &[&Dir],
+    ) -> ProgramsCache {
+        let mut chunks_cache = ChunksCache::new(chunks_directories, glsl_version);
 
         let mut cache = HashMap::new();
-        for directory in material_directory.dirs().iter() {
-            let program_name = directory.path().file_name().unwrap().to_str().unwrap();
-
-            let program = load_program(
-                facade,
-                &material_directory,
-                &mut chunks_cache,
-                program_name,
-                glsl_version,
-            );
-
-            cache.insert(program_name.to_string(), Rc::new(program));
+        for material_directory in material_directories.iter() {
+            for directory in material_directory.dirs().iter() {
+                let program_name = directory.path().file_name().unwrap().to_str().unwrap();
+                if cache.contains_key(program_name) {
+                    continue;
+                }
+
+                let program = load_program(
+                    facade,
+                    material_directories,
+                    &mut chunks_cache,
+                    program_name,
+                    glsl_version,
+                );
+
+                cache.insert(program_name.to_string(), Rc::new(program));
+            }
         }
 
-        ProgramsCache { programs: cache }
+        ProgramsCache {
+            programs: cache,
+            glsl_version: glsl_version.to_string(),
+        }
+    }
+
+    pub fn get_glsl_version(&self) -> &str {
+        &self.glsl_version
     }
 
     #[allow(dead_code)]
@@ -0,0 +1,170 @@
+use std::f32::consts::PI;
+
+use super::graph::{NodeId, SceneGraph};
+use super::*;
+
+pub trait Behaviour {
+    fn start(&mut self, _context: &mut BehaviourContext) {}
+
+    fn update(&mut self, _context: &mut BehaviourContext, _delta_time: f32) {}
+
+    fn on_destroy(&mut self, _context: &mut BehaviourContext) {}
+}
+
+pub(crate) struct BehaviourSlot {
+    pub(crate) behaviour: Box<dyn Behaviour>,
+    pub(crate) started: bool,
+}
+
+pub struct BehaviourContext<'a> {
+    id: NodeId,
+    graph: &'a mut SceneGraph,
+}
+
+impl<'a> BehaviourContext<'a> {
+    pub(crate) fn new(graph: &'a mut SceneGraph, id: NodeId) -> BehaviourContext<'a> {
+        BehaviourContext { id, graph }
+    }
+
+    pub fn get_id(&self) -> NodeId {
+        self.id
+    }
+
+    pub fn get_graph(&self) -> &SceneGraph {
+        self.graph
+    }
+
+    pub fn get_graph_mut(&mut self) -> &mut SceneGraph {
+        self.graph
+    }
+
+    pub fn get_transform(&self) -> Option<Transform> {
+        self.graph.get_transform(self.id)
+    }
+
+    pub fn set_transform(&mut self, transform: Transform) -> bool {
+        self.graph.set_transform(self.id, transform)
+    }
+
+    pub fn prepend_transform(&mut self, transform: Transform) -> bool {
+        self.graph.prepend_transform(self.id, transform)
+    }
+
+    pub fn append_transform(&mut self, transform: Transform) -> bool {
+        self.graph.append_transform(self.id, transform)
+    }
+
+    pub fn world_transform(&self) -> Option<Transform> {
+        self.graph.world_transform(self.id)
+    }
+
+    pub fn find(&self, path: &str) -> Option<NodeId> {
+        self.graph.find(path)
+    }
+
+    pub fn find_relative(&self, path: &str) -> Option<NodeId> {
+        self.graph.find_from(Some(self.id), path)
+    }
+}
+
+pub struct Spin {
+    pub axis: Direction3,
+    pub speed: f32,
+}
+
+impl Spin {
+    pub fn new(axis: Direction3, speed: f32) -> Spin {
+        Spin { axis, speed }
+    }
+}
+
+impl Behaviour for Spin {
+    fn update(&mut self, context: &mut BehaviourContext, delta_time: f32) {
+        let rotation = Quaternion::from_axis_angle(self.axis, self.speed * delta_time);
+        context.prepend_transform(Transform::rotation(rotation));
+    }
+}
+
+pub struct Bob {
+    pub direction: Displacement3,
+    pub frequency: f32,
+    time: f32,
+    offset: f32,
+}
+
+impl Bob {
+    pub fn new(direction: Displacement3, frequency: f32) -> Bob {
+        Bob {
+            direction,
+            frequency,
+            time: 0.0,
+            offset: 0.0,
+        }
+    }
+}
+
+impl Behaviour for Bob {
+    fn start(&mut self, _context: &mut BehaviourContext) {
+        self.time = 0.0;
+        self.offset = 0.0;
+    }
+
+    fn update(&mut self, context: &mut BehaviourContext, delta_time: f32) {
+        self.time += delta_time;
+        let offset = (2.0 * PI * self.frequency * self.time).sin();
+
+        context.append_transform(Transform::from(self.direction * (offset - self.offset)));
+        self.offset = offset;
+    }
+}
+
+pub struct Patrol {
+    pub points: Vec<Position3>,
+    pub speed: f32,
+    target: usize,
+}
+
+impl Patrol {
+    pub fn new(points: Vec<Position3>, speed: f32) -> Patrol {
+        Patrol {
+            points,
+            speed,
+            target: 0,
+        }
+    }
+
+    pub fn get_target(&self) -> Option<Position3> {
+        self.points.get(self.target).copied()
+    }
+}
+
+impl Behaviour for Patrol {
+    fn update(&mut self, context: &mut BehaviourContext, delta_time: f32) {
+        let transform = match context.get_transform() {
+            Some(transform) => transform,
+            None => return,
+        };
+
+        let mut position = transform * ORIGIN;
+        let mut distance = self.speed * delta_time;
+        for _ in 0..=self.points.len() {
+            let target = match self.get_target() {
+                Some(target) => target,
+                None => return,
+            };
+            let offset = position.to(target);
+            let length = offset.length();
+
+            if length > distance {
+                position = position + offset * (distance / length);
+                break;
+            }
+
+            position = target;
+            distance -= length;
+            self.target = (self.target + 1) % self.points.len();
+        }
+
+        context.append_transform(Transform::from((transform * ORIGIN).to(position)));
+    }
+}
@@ -0,0 +1,380 @@
+use std::rc::Rc;
+
+use glium::framebuffer::MultiOutputFrameBuffer;
+use glium::texture::{
+    DepthFormat, DepthTexture2d, MipmapsOption, Texture2d, UncompressedFloatFormat,
+};
+use glium::uniforms::{
+    AsUniformValue, MagnifySamplerFilter, MinifySamplerFilter, SamplerBehavior, UniformValue,
+};
+use glium::*;
+
+use super::super::geometry::{GeometryBuffers, GpuGeometry, Primitive};
+use super::super::program::{
+    ProgramsCache, INTERNAL_MATERIALS_DIRECTORY, STANDARD_CHUNKS_DIRECTORY,
+};
+use super::fog::FogUniforms;
+use super::material::gbuffer_program_name;
+use super::scene::Scene;
+use super::*;
+
+#[derive(Copy, Clone, Debug)]
+pub enum Light {
+    Directional {
+        direction: Direction3,
+        color: Color,
+    },
+    Point {
+        position: Position3,
+        color: Color,
+        range: f32,
+    },
+}
+
+fn is_deferred(material: &dyn Material) -> bool {
+    material.get_gbuffer_program().is_some()
+        && material.get_draw_parameters().blend == Default::default()
+}
+
+struct GBuffer {
+    albedo: Texture2d,
+    normal: Texture2d,
+    material: Texture2d,
+    emissive: Texture2d,
+    depth: DepthTexture2d,
+}
+
+impl GBuffer {
+    fn new(display: &Display, width: u32, height: u32) -> Result<GBuffer, RenderError> {
+        let (width, height) = (width.max(1), height.max(1));
+        let texture = |format| {
+            Texture2d::empty_with_format(display, format, MipmapsOption::NoMipmap, width, height)
+        };
+
+        Ok(GBuffer {
+            albedo: texture(UncompressedFloatFormat::U8U8U8U8)?,
+            normal: texture(UncompressedFloatFormat::F16F16F16F16)?,
+            material: texture(UncompressedFloatFormat::U8U8U8U8)?,
+            emissive: texture(UncompressedFloatFormat::F16F16F16F16)?,
+            depth: DepthTexture2d::empty_with_format(
+                display,
+                DepthFormat::F32,
+                MipmapsOption::NoMipmap,
+                width,
+                height,
+            )?,
+        })
+    }
+
+    fn dimensions(&self) -> (u32, u32) {
+        self.albedo.dimensions()
+    }
+}
+
+struct GBufferDrawer<'a> {
+    framebuffer: &'a mut MultiOutputFrameBuffer<'a>,
+}
+
+impl<'a> Drawer for GBufferDrawer<'a> {
+    fn clear_scene(&mut self, _color: Color, depth: f32) {
+        self.framebuffer
+            .clear_color_and_depth((0.0, 0.0, 0.0, 0.0), depth);
+    }
+
+    fn draw_single(
+        &mut self,
+        geometry: &dyn GeometryBuffers,
+        transform: &Transform,
+        camera: &Camera,
+        material: &dyn Material,
+    ) -> Result<(), RenderError> {
+        if !is_deferred(material) {
+            return Ok(());
+        }
+        let (vertex_buffer, index_buffer) = get_buffers(geometry)?;
+        let program = material.get_gbuffer_program().ok_or_else(|| {
+            RenderError::MissingProgram(material.get_program_name().map(gbuffer_program_name))
+        })?;
+        let uniforms = MergedUniforms::new(transform, camera, material);
+
+        self.framebuffer
+            .draw(
+                vertex_buffer,
+                index_buffer,
+                program,
+                &uniforms,
+                &material.get_draw_parameters(),
+            )
+            .map_err(RenderError::from)
+    }
+}
+
+struct ForwardDrawer<'a, S: Surface> {
+    surface: &'a mut S,
+}
+
+impl<'a, S: Surface> Drawer for ForwardDrawer<'a, S> {
+    fn clear_scene(&mut self, _color: Color, _depth: f32) {}
+
+    fn draw_single(
+        &mut self,
+        geometry: &dyn GeometryBuffers,
+        transform: &Transform,
+        camera: &Camera,
+        material: &dyn Material,
+    ) -> Result<(), RenderError> {
+        if is_deferred(material) {
+            return Ok(());
+        }
+
+        self.surface
+            .draw_single(geometry, transform, camera, material)
+    }
+}
+
+enum PassUniforms {
+    Ambient {
+        ambient_color: [f32; 3],
+    },
+    Light {
+        light_type: i32,
+        direction: [f32; 3],
+        color: [f32; 3],
+        position: [f32; 3],
+        range: f32,
+    },
+    Fog(FogUniforms),
+}
+
+struct PassMaterial<'a> {
+    program: &'a Program,
+    gbuffer: &'a GBuffer,
+    inverse_view_projection: [[f32; 4]; 4],
+    draw_parameters: DrawParameters<'static>,
+    uniforms: PassUniforms,
+}
+
+impl<'a> Material for PassMaterial<'a> {
+    fn get_program(&self) -> Option<&Program> {
+        Some(self.program)
+    }
+
+    fn get_draw_parameters(&self) -> DrawParameters<'_> {
+        self.draw_parameters.clone()
+    }
+
+    fn visit_uniforms<'n>(&'n self, visitor: &mut dyn FnMut(&str, UniformValue<'n>)) {
+        let sampler = Some(SamplerBehavior {
+            minify_filter: MinifySamplerFilter::Nearest,
+            magnify_filter: MagnifySamplerFilter::Nearest,
+            ..Default::default()
+        });
+
+        visitor(
+            "gbuffer_albedo",
+            UniformValue::Texture2d(&self.gbuffer.albedo, sampler),
+        );
+        visitor(
+            "gbuffer_normal",
+            UniformValue::Texture2d(&self.gbuffer.normal, sampler),
+        );
+        visitor(
+            "gbuffer_material",
+            UniformValue::Texture2d(&self.gbuffer.material, sampler),
+        );
+        visitor(
+            "gbuffer_emissive",
+            UniformValue::Texture2d(&self.gbuffer.emissive, sampler),
+        );
+        visitor(
+            "gbuffer_depth",
+            UniformValue::DepthTexture2d(&self.gbuffer.depth, sampler),
+        );
+        visitor(
+            "inverse_view_projection",
+            self.inverse_view_projection.as_uniform_value(),
+        );
+
+        match &self.uniforms {
+            PassUniforms::Ambient { ambient_color } => {
+                visitor("ambient_color", ambient_color.as_uniform_value());
+            }
+            PassUniforms::Light {
+                light_type,
+                direction,
+                color,
+                position,
+                range,
+            } => {
+                visitor("light_type", light_type.as_uniform_value());
+                visitor("light_direction", direction.as_uniform_value());
+                visitor("light_color", color.as_uniform_value());
+                visitor("light_position", position.as_uniform_value());
+                visitor("light_range", range.as_uniform_value());
+            }
+            PassUniforms::Fog(fog) => fog.visit_uniforms(visitor),
+        }
+    }
+}
+
+/// Lights the scene from its G-buffer with the scene's ambient color and
+/// lights. Image-based lighting from the scene environment and reflection
+/// probes is only applied on the forward path.
+pub struct DeferredRenderer {
+    ambient_program: Rc<Program>,
+    light_program: Rc<Program>,
+    fog_program: Rc<Program>,
+    quad: GpuGeometry,
+    gbuffer: GBuffer,
+}
+
+impl DeferredRenderer {
+    /// Builds the resolve passes with the same GLSL version as `programs`,
+    /// the cache the scene's materials were compiled from.
+    pub fn new(
+        display: &Display,
+        programs: &ProgramsCache,
+    ) -> Result<DeferredRenderer, RenderError> {
+        let programs_cache = ProgramsCache::layered(
+            display,
+            programs.get_glsl_version(),
+            &[&STANDARD_CHUNKS_DIRECTORY],
+            &[&INTERNAL_MATERIALS_DIRECTORY],
+        );
+
+        let quad = Primitive::quad(
+            [
+                pos3(1.0, 1.0, 0.0),
+                pos3(-1.0, 1.0, 0.0),
+                pos3(-1.0, -1.0, 0.0),
+                pos3(1.0, -1.0, 0.0),
+            ],
+            BACKWARDS,
+        )
+        .to_gpu_geometry(display);
+
+        let (width, height) = display.get_framebuffer_dimensions();
+
+        Ok(DeferredRenderer {
+            ambient_program: internal_program(&programs_cache, "deferred_ambient")?,
+            light_program: internal_program(&programs_cache, "deferred_light")?,
+            fog_program: internal_program(&programs_cache, "deferred_fog")?,
+            quad,
+            gbuffer: GBuffer::new(display, width, height)?,
+        })
+    }
+
+    fn pass<'a>(
+        &'a self,
+        program: &'a Program,
+        camera: &Camera,
+        draw_parameters: DrawParameters<'static>,
+        uniforms: PassUniforms,
+    ) -> PassMaterial<'a> {
+        PassMaterial {
+            program,
+            gbuffer: &self.gbuffer,
+            inverse_view_projection: camera.get_inverse_view_projection_matrix().elements,
+            draw_parameters,
+            uniforms,
+        }
+    }
+
+    pub fn render<S: Surface>(
+        &mut self,
+        display: &Display,
+        surface: &mut S,
+        scene: &Scene,
+        camera: &Camera,
+    ) -> Result<(), RenderError> {
+        let dimensions = surface.get_dimensions();
+        if dimensions != self.gbuffer.dimensions() {
+            self.gbuffer = GBuffer::new(display, dimensions.0, dimensions.1)?;
+        }
+
+        {
+            let outputs = [
+                ("albedo_output", &self.gbuffer.albedo),
+                ("normal_output", &self.gbuffer.normal),
+                ("material_output", &self.gbuffer.material),
+                ("emissive_output", &self.gbuffer.emissive),
+            ];
+            let mut framebuffer = MultiOutputFrameBuffer::with_depth_buffer(
+                display,
+                outputs.iter().cloned(),
+                &self.gbuffer.depth,
+            )?;
+            let mut drawer = GBufferDrawer {
+                framebuffer: &mut framebuffer,
+            };
+            scene.draw(&mut drawer, &IDENTITY, camera)?;
+        }
+
+        let background_color = scene.get_background_color();
+        surface.clear_scene(background_color, 1.0);
+
+        let ambient_color = scene.get_ambient_color();
+        let ambient = self.pass(
+            &self.ambient_program,
+            camera,
+            DrawParameters {
+                depth: Depth {
+                    test: DepthTest::Overwrite,
+                    write: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            PassUniforms::Ambient {
+                ambient_color: [ambient_color.r, ambient_color.g, ambient_color.b],
+            },
+        );
+        surface.draw_single(&self.quad, &IDENTITY, camera, &ambient)?;
+
+        let additive = DrawParameters {
+            blend: BlendMode::Additive.to_blend(),
+            ..Default::default()
+        };
+        for light in scene.get_lights().iter() {
+            let uniforms = match *light {
+                Light::Directional { direction, color } => PassUniforms::Light {
+                    light_type: 0,
+                    direction: direction.vector.as_array(),
+                    color: [color.r, color.g, color.b],
+                    position: [0.0, 0.0, 0.0],
+                    range: 0.0,
+                },
+                Light::Point {
+                    position,
+                    color,
+                    range,
+                } => PassUniforms::Light {
+                    light_type: 1,
+                    direction: [0.0, 0.0, 0.0],
+                    color: [color.r, color.g, color.b],
+                    position: position.vector.as_array(),
+                    range,
+                },
+            };
+            let pass = self.pass(&self.light_program, camera, additive.clone(), uniforms);
+            surface.draw_single(&self.quad, &IDENTITY, camera, &pass)?;
+        }
+
+        if scene.get_fog().is_some() {
+            let fog = FogUniforms::new(scene.get_fog(), background_color);
+            let pass = self.pass(
+                &self.fog_program,
+                camera,
+                DrawParameters {
+                    blend: Blend::alpha_blending(),
+                    ..Default::default()
+                },
+                PassUniforms::Fog(fog),
+            );
+            surface.draw_single(&self.quad, &IDENTITY, camera, &pass)?;
+        }
+
+        let mut drawer = ForwardDrawer { surface };
+        scene.draw(&mut drawer, &IDENTITY, camera)
+    }
+}
@@ -0,0 +1,963 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use glium::texture::{SrgbTexture2d, Texture2d};
+use glium::Display;
+use serde::{Deserialize, Serialize};
+
+use super::super::animation::JointTransform;
+use super::super::geometry::{Geometry, GpuGeometry, Primitive, Vertex};
+use super::super::math::*;
+use super::super::program::ProgramsCache;
+use super::deferred::Light;
+use super::environment::{load_hdr, EnvironmentBaker, EnvironmentMap};
+use super::fog::Fog;
+use super::graph::{Node, NodeId, SceneGraph};
+use super::material::{Parameter, ParameterMaterial};
+use super::scene::{Mesh, Scene, Transformable};
+use super::texture::{load_png, load_png_srgb};
+use super::Drawable;
+
+pub const SCENE_FORMAT_VERSION: u32 = 1;
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn unsupported(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, message.to_string())
+}
+
+fn default_rotation() -> [f32; 4] {
+    [0.0, 0.0, 0.0, 1.0]
+}
+
+fn default_scale() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+fn is_visible(visible: &bool) -> bool {
+    *visible
+}
+
+fn default_ambient_color() -> [f32; 4] {
+    [0.0, 0.0, 0.0, 1.0]
+}
+
+fn default_environment_size() -> u32 {
+    512
+}
+
+fn color(value: [f32; 4]) -> Color {
+    rgba(value[0], value[1], value[2], value[3])
+}
+
+/// A transform saved as its parts, so the inverse is always recomputed on
+/// load. Shear cannot be described and is dropped when saving.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransformDescription {
+    #[serde(default)]
+    pub translation: [f32; 3],
+    #[serde(default = "default_rotation")]
+    pub rotation: [f32; 4],
+    #[serde(default = "default_scale")]
+    pub scale: [f32; 3],
+}
+
+impl Default for TransformDescription {
+    fn default() -> TransformDescription {
+        TransformDescription {
+            translation: [0.0, 0.0, 0.0],
+            rotation: default_rotation(),
+            scale: default_scale(),
+        }
+    }
+}
+
+impl TransformDescription {
+    pub fn from_transform(transform: &Transform) -> TransformDescription {
+        let parts = JointTransform::from_transform(transform);
+        let rotation = parts.rotation;
+
+        TransformDescription {
+            translation: parts.translation.vector.as_array(),
+            rotation: [rotation.x, rotation.y, rotation.z, rotation.w],
+            scale: parts.scale.vector.as_array(),
+        }
+    }
+
+    pub fn to_transform(&self) -> Transform {
+        let [x, y, z, w] = self.rotation;
+
+        Transform::from_translation_rotation_scale(
+            dis3(
+                self.translation[0],
+                self.translation[1],
+                self.translation[2],
+            ),
+            Quaternion { x, y, z, w },
+            dis3(self.scale[0], self.scale[1], self.scale[2]),
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GeometryDescription {
+    Asset {
+        path: String,
+    },
+    Quad {
+        points: [[f32; 3]; 4],
+        normal: [f32; 3],
+    },
+    Cuboid {
+        min: [f32; 3],
+        max: [f32; 3],
+    },
+    Inline {
+        positions: Vec<[f32; 3]>,
+        normals: Vec<[f32; 3]>,
+        texture_coordinates: Vec<[f32; 2]>,
+        indices: Vec<u32>,
+    },
+}
+
+impl GeometryDescription {
+    pub fn from_geometry(geometry: &Geometry) -> GeometryDescription {
+        let vertices = &geometry.vertices;
+
+        GeometryDescription::Inline {
+            positions: vertices
+                .iter()
+                .map(|vertex| vertex.position.vector.as_array())
+                .collect(),
+            normals: vertices
+                .iter()
+                .map(|vertex| vertex.normal.vector.as_array())
+                .collect(),
+            texture_coordinates: vertices
+                .iter()
+                .map(|vertex| vertex.texture_coordinates.vector.as_array())
+                .collect(),
+            indices: geometry.indices.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FogDescription {
+    Linear {
+        start: f32,
+        end: f32,
+    },
+    Exponential {
+        density: f32,
+    },
+    ExponentialSquared {
+        density: f32,
+    },
+    Height {
+        density: f32,
+        base_height: f32,
+        falloff: f32,
+    },
+}
+
+impl FogDescription {
+    pub fn from_fog(fog: &Fog) -> FogDescription {
+        match *fog {
+            Fog::Linear { start, end } => FogDescription::Linear { start, end },
+            Fog::Exponential { density } => FogDescription::Exponential { density },
+            Fog::ExponentialSquared { density } => FogDescription::ExponentialSquared { density },
+            Fog::Height {
+                density,
+                base_height,
+                falloff,
+            } => FogDescription::Height {
+                density,
+                base_height,
+                falloff,
+            },
+        }
+    }
+
+    pub fn to_fog(&self) -> Fog {
+        match *self {
+            FogDescription::Linear { start, end } => Fog::Linear { start, end },
+            FogDescription::Exponential { density } => Fog::Exponential { density },
+            FogDescription::ExponentialSquared { density } => Fog::ExponentialSquared { density },
+            FogDescription::Height {
+                density,
+                base_height,
+                falloff,
+            } => Fog::Height {
+                density,
+                base_height,
+                falloff,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LightDescription {
+    Directional {
+        direction: [f32; 3],
+        color: [f32; 4],
+    },
+    Point {
+        position: [f32; 3],
+        color: [f32; 4],
+        range: f32,
+    },
+}
+
+impl LightDescription {
+    pub fn from_light(light: &Light) -> LightDescription {
+        match *light {
+            Light::Directional { direction, color } => LightDescription::Directional {
+                direction: direction.vector.as_array(),
+                color: color.to_array(),
+            },
+            Light::Point {
+                position,
+                color,
+                range,
+            } => LightDescription::Point {
+                position: position.vector.as_array(),
+                color: color.to_array(),
+                range,
+            },
+        }
+    }
+
+    pub fn to_light(&self) -> Light {
+        match *self {
+            LightDescription::Directional {
+                direction,
+                color: value,
+            } => Light::Directional {
+                direction: dis3(direction[0], direction[1], direction[2]).normalized(),
+                color: color(value),
+            },
+            LightDescription::Point {
+                position,
+                color: value,
+                range,
+            } => Light::Point {
+                position: pos3(position[0], position[1], position[2]),
+                color: color(value),
+                range,
+            },
+        }
+    }
+}
+
+/// An equirectangular HDR image, baked into an environment map of `size`
+/// pixels per cube face when the scene is loaded.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentDescription {
+    pub path: String,
+    #[serde(default = "default_environment_size")]
+    pub size: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ParameterDescription {
+    Bool(bool),
+    Int(i32),
+    Float(f32),
+    Vector2([f32; 2]),
+    Vector3([f32; 3]),
+    Vector4([f32; 4]),
+    Matrix4([[f32; 4]; 4]),
+    Color([f32; 4]),
+    Texture(String),
+    SrgbTexture(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MaterialDescription {
+    pub program: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub parameters: BTreeMap<String, ParameterDescription>,
+}
+
+impl MaterialDescription {
+    /// Stands in for a material that cannot describe itself, so its mesh is
+    /// still saved and the loader can report which node lost its material.
+    pub fn unnamed() -> MaterialDescription {
+        MaterialDescription {
+            program: String::new(),
+            parameters: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MeshDescription {
+    pub geometry: GeometryDescription,
+    pub material: MaterialDescription,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NodeDescription {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub transform: TransformDescription,
+    #[serde(default = "default_visible", skip_serializing_if = "is_visible")]
+    pub visible: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mesh: Option<MeshDescription>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<NodeDescription>,
+}
+
+impl Default for NodeDescription {
+    fn default() -> NodeDescription {
+        NodeDescription {
+            name: String::new(),
+            tags: vec![],
+            transform: Default::default(),
+            visible: true,
+            mesh: None,
+            children: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneDescription {
+    pub version: u32,
+    #[serde(default)]
+    pub background_color: [f32; 4],
+    #[serde(default = "default_ambient_color")]
+    pub ambient_color: [f32; 4],
+    #[serde(default)]
+    pub transform: TransformDescription,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fog: Option<FogDescription>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lights: Vec<LightDescription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<EnvironmentDescription>,
+    #[serde(default)]
+    pub nodes: Vec<NodeDescription>,
+}
+
+impl Default for SceneDescription {
+    fn default() -> SceneDescription {
+        SceneDescription {
+            version: SCENE_FORMAT_VERSION,
+            background_color: [0.0, 0.0, 0.0, 0.0],
+            ambient_color: default_ambient_color(),
+            transform: Default::default(),
+            fog: None,
+            lights: vec![],
+            environment: None,
+            nodes: vec![],
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl SceneDescription {
+    fn check_version(self) -> io::Result<SceneDescription> {
+        if self.version > SCENE_FORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "scene format version {} is newer than supported version {}",
+                self.version, SCENE_FORMAT_VERSION
+            )));
+        }
+
+        Ok(self)
+    }
+
+    pub fn from_json(json: &str) -> io::Result<SceneDescription> {
+        let description: SceneDescription = serde_json::from_str(json)?;
+        description.check_version()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<SceneDescription> {
+        let reader = BufReader::new(File::open(path)?);
+        let description: SceneDescription = serde_json::from_reader(reader)?;
+        description.check_version()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// Describes the scene from its live state. Meshes are described through
+    /// `Drawable::describe`, so drawables that cannot describe themselves
+    /// (skinned meshes, particles) are saved as empty nodes, and an
+    /// environment is only saved when it was baked from a file.
+    pub fn from_scene(scene: &Scene) -> SceneDescription {
+        let graph = scene.get_graph();
+
+        SceneDescription {
+            version: SCENE_FORMAT_VERSION,
+            background_color: scene.get_background_color().to_array(),
+            ambient_color: scene.get_ambient_color().to_array(),
+            transform: TransformDescription::from_transform(&scene.get_transform()),
+            fog: scene.get_fog().as_ref().map(FogDescription::from_fog),
+            lights: scene
+                .get_lights()
+                .iter()
+                .map(LightDescription::from_light)
+                .collect(),
+            environment: scene
+                .get_environment()
+                .and_then(|environment| environment.get_source().cloned()),
+            nodes: graph
+                .get_roots()
+                .iter()
+                .map(|&root| describe_node(graph, root))
+                .collect(),
+        }
+    }
+}
+
+fn describe_node(graph: &SceneGraph, id: NodeId) -> NodeDescription {
+    let node = graph.get(id).unwrap();
+
+    NodeDescription {
+        name: node.name.clone(),
+        tags: node.get_tags().to_vec(),
+        transform: TransformDescription::from_transform(&node.get_transform()),
+        visible: node.visible,
+        mesh: node
+            .drawable
+            .as_ref()
+            .and_then(|drawable| drawable.describe()),
+        children: node
+            .get_children()
+            .iter()
+            .map(|&child| describe_node(graph, child))
+            .collect(),
+    }
+}
+
+pub type GeometryLoader = Box<dyn Fn(&Path) -> io::Result<Geometry>>;
+
+pub struct SceneLoader<'a> {
+    display: Option<&'a Display>,
+    programs_cache: Option<&'a ProgramsCache>,
+    environment_baker: Option<&'a EnvironmentBaker>,
+    base_path: PathBuf,
+    geometry_loaders: HashMap<String, GeometryLoader>,
+    geometries: HashMap<String, Rc<GpuGeometry>>,
+    headless_geometries: HashMap<String, Rc<Geometry>>,
+    textures: HashMap<String, Rc<Texture2d>>,
+    srgb_textures: HashMap<String, Rc<SrgbTexture2d>>,
+}
+
+#[allow(dead_code)]
+impl<'a> SceneLoader<'a> {
+    pub fn new<P: AsRef<Path>>(
+        display: &'a Display,
+        programs_cache: &'a ProgramsCache,
+        base_path: P,
+    ) -> SceneLoader<'a> {
+        SceneLoader {
+            display: Some(display),
+            programs_cache: Some(programs_cache),
+            ..SceneLoader::headless(base_path)
+        }
+    }
+
+    /// A loader without a GL context. Meshes keep their geometry on the CPU
+    /// and materials only carry their program name, like
+    /// `ParameterMaterial::named`, which is enough to record or re-save a
+    /// scene. Texture and environment files cannot be loaded.
+    pub fn headless<P: AsRef<Path>>(base_path: P) -> SceneLoader<'a> {
+        SceneLoader {
+            display: None,
+            programs_cache: None,
+            environment_baker: None,
+            base_path: base_path.as_ref().to_path_buf(),
+            geometry_loaders: HashMap::new(),
+            geometries: HashMap::new(),
+            headless_geometries: HashMap::new(),
+            textures: HashMap::new(),
+            srgb_textures: HashMap::new(),
+        }
+    }
+
+    pub fn with_environment_baker(mut self, baker: &'a EnvironmentBaker) -> SceneLoader<'a> {
+        self.environment_baker = Some(baker);
+        self
+    }
+
+    pub fn register_geometry_loader<F>(&mut self, extension: &str, loader: F)
+    where
+        F: Fn(&Path) -> io::Result<Geometry> + 'static,
+    {
+        self.geometry_loaders
+            .insert(extension.to_lowercase(), Box::new(loader));
+    }
+
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> io::Result<Scene> {
+        let description = SceneDescription::load(self.base_path.join(path))?;
+        self.build(&description)
+    }
+
+    pub fn build(&mut self, description: &SceneDescription) -> io::Result<Scene> {
+        let mut scene = Scene::new();
+        scene.set_background_color(color(description.background_color));
+        scene.set_ambient_color(color(description.ambient_color));
+        scene.set_transform(description.transform.to_transform());
+        scene.set_fog(description.fog.as_ref().map(FogDescription::to_fog));
+        for light in description.lights.iter() {
+            scene.add_light(light.to_light());
+        }
+        if let Some(environment) = &description.environment {
+            let environment = self.load_environment(environment)?;
+            scene.set_environment(Some(Rc::new(environment)));
+        }
+
+        for node in description.nodes.iter() {
+            self.build_node(scene.get_graph_mut(), None, node)?;
+        }
+
+        Ok(scene)
+    }
+
+    fn build_node(
+        &mut self,
+        graph: &mut SceneGraph,
+        parent: Option<NodeId>,
+        description: &NodeDescription,
+    ) -> io::Result<NodeId> {
+        let mut node = Node::new(&description.name)
+            .with_transform(description.transform.to_transform())
+            .with_visible(description.visible);
+        for tag in description.tags.iter() {
+            node.add_tag(tag);
+        }
+        if let Some(mesh) = &description.mesh {
+            if mesh.material.program.is_empty() {
+                return Err(invalid_data(format!(
+                    "mesh of node \"{}\" has no material program",
+                    description.name
+                )));
+            }
+            node.drawable = Some(self.build_mesh(mesh)?);
+        }
+
+        let id = match parent {
+            Some(parent) => graph.spawn_child(parent, node).ok_or_else(|| {
+                invalid_data(format!(
+                    "parent of node \"{}\" is missing",
+                    description.name
+                ))
+            })?,
+            None => graph.spawn(node),
+        };
+        for child in description.children.iter() {
+            self.build_node(graph, Some(id), child)?;
+        }
+
+        Ok(id)
+    }
+
+    fn build_mesh(&mut self, description: &MeshDescription) -> io::Result<Rc<dyn Drawable>> {
+        let key = serde_json::to_string(&description.geometry)?;
+        let material = Rc::new(self.load_material(&description.material)?);
+        let source = description.geometry.clone();
+
+        match self.display {
+            Some(display) => {
+                let geometry = match self.geometries.get(&key) {
+                    Some(geometry) => Rc::clone(geometry),
+                    None => {
+                        let geometry = self.load_geometry(&description.geometry)?;
+                        let geometry = Rc::new(geometry.to_gpu_geometry(display));
+                        self.geometries.insert(key, Rc::clone(&geometry));
+                        geometry
+                    }
+                };
+
+                Ok(Rc::new(
+                    Mesh::new(geometry, material).with_geometry_source(source),
+                ))
+            }
+            None => {
+                let geometry = match self.headless_geometries.get(&key) {
+                    Some(geometry) => Rc::clone(geometry),
+                    None => {
+                        let geometry = Rc::new(self.load_geometry(&description.geometry)?);
+                        self.headless_geometries.insert(key, Rc::clone(&geometry));
+                        geometry
+                    }
+                };
+
+                Ok(Rc::new(
+                    Mesh::new(geometry, material).with_geometry_source(source),
+                ))
+            }
+        }
+    }
+
+    fn load_geometry(&self, description: &GeometryDescription) -> io::Result<Geometry> {
+        match description {
+            GeometryDescription::Asset { path } => {
+                let path = self.base_path.join(path);
+                let extension = path
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .unwrap_or("")
+                    .to_lowercase();
+                let loader = self.geometry_loaders.get(&extension).ok_or_else(|| {
+                    invalid_data(format!("no geometry loader for \"{}\"", path.display()))
+                })?;
+
+                loader(&path)
+            }
+            GeometryDescription::Quad { points, normal } => {
+                let point = |p: [f32; 3]| pos3(p[0], p[1], p[2]);
+
+                Ok(Primitive::quad(
+                    [
+                        point(points[0]),
+                        point(points[1]),
+                        point(points[2]),
+                        point(points[3]),
+                    ],
+                    dis3(normal[0], normal[1], normal[2]).normalized(),
+                ))
+            }
+            GeometryDescription::Cuboid { min, max } => Ok(Primitive::cuboid(
+                pos3(min[0], min[1], min[2]),
+                pos3(max[0], max[1], max[2]),
+            )),
+            GeometryDescription::Inline {
+                positions,
+                normals,
+                texture_coordinates,
+                indices,
+            } => {
+                if normals.len() != positions.len() || texture_coordinates.len() != positions.len()
+                {
+                    return Err(invalid_data(
+                        "inline geometry attributes differ in length".to_string(),
+                    ));
+                }
+                if let Some(index) = indices.iter().find(|&&i| i as usize >= positions.len()) {
+                    return Err(invalid_data(format!(
+                        "inline geometry index {} is out of range",
+                        index
+                    )));
+                }
+
+                let vertices = positions
+                    .iter()
+                    .zip(normals.iter())
+                    .zip(texture_coordinates.iter())
+                    .map(|((p, n), t)| {
+                        Vertex::new(
+                            pos3(p[0], p[1], p[2]),
+                            dis3(n[0], n[1], n[2]).normalized(),
+                            pos2(t[0], t[1]),
+                        )
+                    })
+                    .collect();
+
+                Ok(Geometry {
+                    vertices,
+                    indices: indices.clone(),
+                })
+            }
+        }
+    }
+
+    fn load_texture(&mut self, path: &str) -> io::Result<Rc<Texture2d>> {
+        if let Some(texture) = self.textures.get(path) {
+            return Ok(Rc::clone(texture));
+        }
+
+        let display = self
+            .display
+            .ok_or_else(|| unsupported("loading textures needs a display"))?;
+        let texture = Rc::new(load_png(display, self.base_path.join(path))?);
+        self.textures.insert(path.to_string(), Rc::clone(&texture));
+        Ok(texture)
+    }
+
+    fn load_srgb_texture(&mut self, path: &str) -> io::Result<Rc<SrgbTexture2d>> {
+        if let Some(texture) = self.srgb_textures.get(path) {
+            return Ok(Rc::clone(texture));
+        }
+
+        let display = self
+            .display
+            .ok_or_else(|| unsupported("loading textures needs a display"))?;
+        let texture = Rc::new(load_png_srgb(display, self.base_path.join(path))?);
+        self.srgb_textures
+            .insert(path.to_string(), Rc::clone(&texture));
+        Ok(texture)
+    }
+
+    fn load_environment(
+        &mut self,
+        description: &EnvironmentDescription,
+    ) -> io::Result<EnvironmentMap> {
+        let (display, baker) = match (self.display, self.environment_baker) {
+            (Some(display), Some(baker)) => (display, baker),
+            _ => {
+                return Err(unsupported(
+                    "loading an environment needs a display and an environment baker",
+                ))
+            }
+        };
+
+        let source = load_hdr(display, self.base_path.join(&description.path))?;
+        let environment = baker
+            .bake_equirectangular(display, &source, description.size)
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+
+        Ok(environment.with_source(description.clone()))
+    }
+
+    fn load_material(
+        &mut self,
+        description: &MaterialDescription,
+    ) -> io::Result<ParameterMaterial> {
+        let mut material = match self.programs_cache {
+            Some(programs_cache) => {
+                ParameterMaterial::from_cache(programs_cache, &description.program).ok_or_else(
+                    || {
+                        io::Error::new(
+                            io::ErrorKind::NotFound,
+                            format!("program \"{}\" not found", description.program),
+                        )
+                    },
+                )?
+            }
+            None => ParameterMaterial::named(&description.program),
+        };
+
+        for (name, description) in description.parameters.iter() {
+            let parameter = match description {
+                ParameterDescription::Bool(value) => Parameter::Bool(*value),
+                ParameterDescription::Int(value) => Parameter::Int(*value),
+                ParameterDescription::Float(value) => Parameter::Float(*value),
+                ParameterDescription::Vector2(value) => Parameter::Vector2(*value),
+                ParameterDescription::Vector3(value) => Parameter::Vector3(*value),
+                ParameterDescription::Vector4(value) => Parameter::Vector4(*value),
+                ParameterDescription::Matrix4(value) => Parameter::Matrix4(*value),
+                ParameterDescription::Color(value) => Parameter::Color(color(*value)),
+                ParameterDescription::Texture(path) => Parameter::Texture(self.load_texture(path)?),
+                ParameterDescription::SrgbTexture(path) => {
+                    Parameter::SrgbTexture(self.load_srgb_texture(path)?)
+                }
+            };
+
+            match description {
+                ParameterDescription::Texture(path) | ParameterDescription::SrgbTexture(path) => {
+                    material.set_texture(name, parameter, path)
+                }
+                _ => material.set(name, parameter),
+            }
+        }
+
+        Ok(material)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glium::uniforms::UniformValue;
+    use glium::{DrawParameters, Program};
+
+    use super::super::Material;
+    use super::*;
+
+    fn scene() -> Scene {
+        let mut scene = Scene::new();
+        scene.set_background_color(rgba(0.1, 0.2, 0.3, 1.0));
+        scene.set_ambient_color(rgba(0.25, 0.25, 0.25, 1.0));
+        scene.set_transform(Transform::translation(0.0, -1.0, 0.0));
+        scene.set_fog(Some(Fog::Exponential { density: 0.05 }));
+        scene.add_light(Light::Directional {
+            direction: dis3(0.0, -1.0, 0.0).normalized(),
+            color: rgba(1.0, 0.9, 0.8, 1.0),
+        });
+        scene.add_light(Light::Point {
+            position: pos3(1.0, 2.0, 3.0),
+            color: rgba(0.0, 0.0, 1.0, 1.0),
+            range: 4.0,
+        });
+
+        let mut material =
+            ParameterMaterial::named("lambert").with("color", rgba(1.0, 0.0, 0.0, 1.0));
+        material.set("color", rgba(0.0, 1.0, 0.0, 1.0));
+        let geometry = Rc::new(Primitive::cuboid(pos3(0.0, 0.0, 0.0), pos3(1.0, 1.0, 1.0)));
+        let inline: Rc<dyn Drawable> = Rc::new(Mesh::new(geometry, Rc::new(material)));
+
+        let geometry = Rc::new(Primitive::cuboid(
+            pos3(-1.0, -1.0, -1.0),
+            pos3(1.0, 1.0, 1.0),
+        ));
+        let cuboid: Rc<dyn Drawable> = Rc::new(
+            Mesh::new(
+                geometry,
+                Rc::new(ParameterMaterial::named("pbr").with("roughness", 0.5)),
+            )
+            .with_geometry_source(GeometryDescription::Cuboid {
+                min: [-1.0, -1.0, -1.0],
+                max: [1.0, 1.0, 1.0],
+            }),
+        );
+
+        let mut parent = Node::new("parent")
+            .with_transform(Transform::translation(1.0, 0.0, 0.0))
+            .with_drawable(inline);
+        parent.add_tag("solid");
+        let parent = scene.spawn(parent);
+        scene
+            .get_graph_mut()
+            .spawn_child(
+                parent,
+                Node::new("hidden")
+                    .with_visible(false)
+                    .with_drawable(cuboid),
+            )
+            .unwrap();
+
+        scene
+    }
+
+    #[test]
+    fn describes_live_scene_state() {
+        let description = SceneDescription::from_scene(&scene());
+
+        assert_eq!(description.ambient_color, [0.25, 0.25, 0.25, 1.0]);
+        assert_eq!(
+            description.fog,
+            Some(FogDescription::Exponential { density: 0.05 })
+        );
+        assert_eq!(description.lights.len(), 2);
+        assert_eq!(
+            description.transform.to_transform().matrix.elements[3][..3],
+            [0.0, -1.0, 0.0]
+        );
+
+        let parent = &description.nodes[0];
+        let mesh = parent.mesh.as_ref().unwrap();
+        assert_eq!(parent.tags, vec!["solid".to_string()]);
+        assert_eq!(mesh.material.program, "lambert");
+        assert_eq!(
+            mesh.material.parameters.get("color"),
+            Some(&ParameterDescription::Color([0.0, 1.0, 0.0, 1.0]))
+        );
+        match &mesh.geometry {
+            GeometryDescription::Inline {
+                positions, indices, ..
+            } => {
+                assert_eq!(positions.len(), 24);
+                assert_eq!(indices.len(), 36);
+            }
+            geometry => panic!("expected inline geometry, got {:?}", geometry),
+        }
+
+        let hidden = &parent.children[0];
+        assert!(!hidden.visible);
+        assert_eq!(
+            hidden.mesh.as_ref().unwrap().geometry,
+            GeometryDescription::Cuboid {
+                min: [-1.0, -1.0, -1.0],
+                max: [1.0, 1.0, 1.0],
+            }
+        );
+    }
+
+    #[test]
+    fn round_trips_through_save_and_load() {
+        let saved = SceneDescription::from_scene(&scene());
+
+        let loaded = SceneDescription::from_json(&saved.to_json()).unwrap();
+        let scene = SceneLoader::headless(".").build(&loaded).unwrap();
+        let resaved = SceneDescription::from_scene(&scene);
+
+        assert_eq!(loaded, saved);
+        assert_eq!(resaved, saved);
+    }
+
+    #[test]
+    fn reports_meshes_whose_material_has_no_program() {
+        struct Unnamed;
+
+        impl Material for Unnamed {
+            fn get_program(&self) -> Option<&Program> {
+                None
+            }
+
+            fn get_draw_parameters(&self) -> DrawParameters<'_> {
+                Default::default()
+            }
+
+            fn visit_uniforms<'n>(&'n self, _visitor: &mut dyn FnMut(&str, UniformValue<'n>)) {}
+        }
+
+        let mut scene = Scene::new();
+        let geometry = Rc::new(Primitive::cuboid(pos3(0.0, 0.0, 0.0), pos3(1.0, 1.0, 1.0)));
+        scene.spawn(
+            Node::new("crate").with_drawable(Rc::new(Mesh::new(geometry, Rc::new(Unnamed)))),
+        );
+
+        let description = SceneDescription::from_scene(&scene);
+        let mesh = description.nodes[0].mesh.as_ref().unwrap();
+        assert_eq!(mesh.material, MaterialDescription::unnamed());
+
+        let error = match SceneLoader::headless(".").build(&description) {
+            Ok(_) => panic!("loaded a mesh without a material program"),
+            Err(error) => error,
+        };
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("\"crate\""));
+    }
+
+    #[test]
+    fn saves_transforms_as_parts_and_recomputes_the_inverse() {
+        let transform = Transform::translation(1.0, 2.0, 3.0) * Transform::scale(2.0, 2.0, 2.0);
+        let description = TransformDescription::from_transform(&transform);
+        assert_eq!(description.translation, [1.0, 2.0, 3.0]);
+        assert_eq!(description.rotation, [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(description.scale, [2.0, 2.0, 2.0]);
+
+        let json = r#"{"translation": [0, 4, 0], "scale": [2, 2, 2], "inverse": "ignored"}"#;
+        let loaded = serde_json::from_str::<TransformDescription>(json)
+            .unwrap()
+            .to_transform();
+        let position = loaded.inverse() * pos3(0.0, 6.0, 0.0);
+        assert_eq!(position.vector.as_array(), [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn rejects_newer_format_versions() {
+        let json = format!("{{\"version\": {}}}", SCENE_FORMAT_VERSION + 1);
+
+        assert_eq!(
+            SceneDescription::from_json(&json).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -0,0 +1,676 @@
+use std::cell::RefCell;
+use std::f32::consts::PI;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use glium::backend::Facade;
+use glium::framebuffer::{DepthRenderBuffer, SimpleFrameBuffer};
+use glium::texture::{
+    CubeLayer, Cubemap, DepthFormat, MipmapsOption, RawImage2d, Texture2d, UncompressedFloatFormat,
+};
+use glium::uniforms::{MinifySamplerFilter, SamplerBehavior, SamplerWrapFunction, UniformValue};
+use glium::*;
+
+use super::super::geometry::{GeometryBuffers, GpuGeometry, Primitive};
+use super::super::math::raw::matrix::Matrix4;
+use super::super::program::{
+    ProgramsCache, INTERNAL_MATERIALS_DIRECTORY, STANDARD_CHUNKS_DIRECTORY,
+};
+use super::description::EnvironmentDescription;
+use super::layer::{DrawerLayer, LayeredDrawer};
+use super::scene::Scene;
+use super::*;
+
+const IRRADIANCE_SIZE: u32 = 32;
+const SPECULAR_SIZE: u32 = 128;
+const SPECULAR_LEVELS: u32 = 5;
+const BRDF_LUT_SIZE: u32 = 256;
+
+const CUBE_FACES: [(CubeLayer, [f32; 3], [f32; 3]); 6] = [
+    (CubeLayer::PositiveX, [1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+    (CubeLayer::NegativeX, [-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+    (CubeLayer::PositiveY, [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+    (CubeLayer::NegativeY, [0.0, -1.0, 0.0], [0.0, 0.0, -1.0]),
+    (CubeLayer::PositiveZ, [0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+    (CubeLayer::NegativeZ, [0.0, 0.0, -1.0], [0.0, -1.0, 0.0]),
+];
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn face_transform(position: Position3, forward: [f32; 3], up: [f32; 3]) -> Transform {
+    let right = cross(forward, up);
+    let back = [-forward[0], -forward[1], -forward[2]];
+
+    let rotation = Matrix4 {
+        elements: [
+            [right[0], right[1], right[2], 0.0],
+            [up[0], up[1], up[2], 0.0],
+            [back[0], back[1], back[2], 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+    let rotation = Transform {
+        matrix: rotation,
+        inverse: rotation.transposed(),
+    };
+    let position = position.vector;
+
+    Transform::translation(position.x, position.y, position.z) * rotation
+}
+
+fn cubemap_sampler(minify_filter: MinifySamplerFilter) -> Option<SamplerBehavior> {
+    Some(SamplerBehavior {
+        wrap_function: (
+            SamplerWrapFunction::Clamp,
+            SamplerWrapFunction::Clamp,
+            SamplerWrapFunction::Clamp,
+        ),
+        minify_filter,
+        ..Default::default()
+    })
+}
+
+pub fn load_hdr<P: AsRef<Path>>(display: &Display, path: P) -> io::Result<Texture2d> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+    let bytes = fs::read(path)?;
+    if !bytes.starts_with(b"#?") {
+        return Err(invalid("missing Radiance header"));
+    }
+
+    let mut offset = 0;
+    let mut next_line = || {
+        let start = offset;
+        while offset < bytes.len() && bytes[offset] != b'\n' {
+            offset += 1;
+        }
+        let line = String::from_utf8_lossy(&bytes[start..offset])
+            .trim()
+            .to_string();
+        offset += 1;
+        line
+    };
+
+    loop {
+        let line = next_line();
+        if line.is_empty() {
+            break;
+        }
+        if line.starts_with("FORMAT=") && line != "FORMAT=32-bit_rle_rgbe" {
+            return Err(invalid("unsupported pixel format"));
+        }
+    }
+
+    let resolution = next_line();
+    let parts: Vec<&str> = resolution.split_whitespace().collect();
+    if parts.len() != 4 || parts[0] != "-Y" || parts[2] != "+X" {
+        return Err(invalid("unsupported image orientation"));
+    }
+    let height: usize = parts[1].parse().map_err(|_| invalid("bad height"))?;
+    let width: usize = parts[3].parse().map_err(|_| invalid("bad width"))?;
+
+    let mut rgbe = vec![0u8; width * height * 4];
+    let mut scanline = vec![0u8; width * 4];
+    for row in 0..height {
+        let header = bytes
+            .get(offset..offset + 4)
+            .ok_or_else(|| invalid("truncated"))?;
+        let run_length_encoded = (8..0x8000).contains(&width)
+            && header[0] == 2
+            && header[1] == 2
+            && ((header[2] as usize) << 8 | header[3] as usize) == width;
+
+        if run_length_encoded {
+            offset += 4;
+            for channel in 0..4 {
+                let mut x = 0;
+                while x < width {
+                    let count = *bytes.get(offset).ok_or_else(|| invalid("truncated"))? as usize;
+                    offset += 1;
+
+                    if count > 128 {
+                        let count = count - 128;
+                        let value = *bytes.get(offset).ok_or_else(|| invalid("truncated"))?;
+                        offset += 1;
+                        if x + count > width {
+                            return Err(invalid("bad run length"));
+                        }
+                        for _ in 0..count {
+                            scanline[x * 4 + channel] = value;
+                            x += 1;
+                        }
+                    } else {
+                        if count == 0 || x + count > width {
+                            return Err(invalid("bad run length"));
+                        }
+                        let values = bytes
+                            .get(offset..offset + count)
+                            .ok_or_else(|| invalid("truncated"))?;
+                        offset += count;
+                        for &value in values.iter() {
+                            scanline[x * 4 + channel] = value;
+                            x += 1;
+                        }
+                    }
+                }
+            }
+        } else {
+            let values = bytes
+                .get(offset..offset + width * 4)
+                .ok_or_else(|| invalid("truncated"))?;
+            offset += width * 4;
+            scanline.copy_from_slice(values);
+        }
+
+        let destination = (height - 1 - row) * width * 4;
+        rgbe[destination..destination + width * 4].copy_from_slice(&scanline);
+    }
+
+    let pixels: Vec<f32> = rgbe
+        .chunks(4)
+        .flat_map(|pixel| {
+            let scale = if pixel[3] == 0 {
+                0.0
+            } else {
+                2f32.powi(pixel[3] as i32 - 136)
+            };
+            vec![
+                pixel[0] as f32 * scale,
+                pixel[1] as f32 * scale,
+                pixel[2] as f32 * scale,
+            ]
+        })
+        .collect();
+
+    let image = RawImage2d::from_raw_rgb(pixels, (width as u32, height as u32));
+    let texture = Texture2d::with_format(
+        display,
+        image,
+        UncompressedFloatFormat::F32F32F32,
+        MipmapsOption::NoMipmap,
+    )
+    .map_err(|error| invalid(&format!("{:?}", error)))?;
+
+    Ok(texture)
+}
+
+pub struct EnvironmentMap {
+    irradiance: Cubemap,
+    specular: Cubemap,
+    brdf_lut: Rc<Texture2d>,
+    source: Option<EnvironmentDescription>,
+}
+
+#[allow(dead_code)]
+impl EnvironmentMap {
+    pub fn with_source(mut self, source: EnvironmentDescription) -> EnvironmentMap {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn get_source(&self) -> Option<&EnvironmentDescription> {
+        self.source.as_ref()
+    }
+
+    pub fn get_irradiance(&self) -> &Cubemap {
+        &self.irradiance
+    }
+
+    pub fn get_specular(&self) -> &Cubemap {
+        &self.specular
+    }
+
+    pub fn get_brdf_lut(&self) -> &Texture2d {
+        &self.brdf_lut
+    }
+
+    pub fn get_specular_levels(&self) -> u32 {
+        self.specular.get_mipmap_levels()
+    }
+
+    /// A black 1×1 environment, bound while no real one applies so the
+    /// environment samplers never fall back to texture unit 0.
+    pub fn fallback<F: Facade>(facade: &F) -> Result<EnvironmentMap, RenderError> {
+        let black_cubemap = || -> Result<Cubemap, RenderError> {
+            let cubemap = Cubemap::empty_with_format(
+                facade,
+                UncompressedFloatFormat::F16F16F16F16,
+                MipmapsOption::NoMipmap,
+                1,
+            )?;
+            for &(layer, _, _) in CUBE_FACES.iter() {
+                SimpleFrameBuffer::new(facade, cubemap.main_level().image(layer))?
+                    .clear_color(0.0, 0.0, 0.0, 1.0);
+            }
+
+            Ok(cubemap)
+        };
+        let brdf_lut = Texture2d::with_format(
+            facade,
+            vec![vec![(0.0f32, 0.0f32)]],
+            UncompressedFloatFormat::F16F16,
+            MipmapsOption::NoMipmap,
+        )?;
+
+        Ok(EnvironmentMap {
+            irradiance: black_cubemap()?,
+            specular: black_cubemap()?,
+            brdf_lut: Rc::new(brdf_lut),
+            source: None,
+        })
+    }
+
+    fn visit_values<'a>(&'a self, visitor: &mut dyn FnMut(&str, UniformValue<'a>)) {
+        visitor(
+            "environment_irradiance",
+            UniformValue::Cubemap(
+                &self.irradiance,
+                cubemap_sampler(MinifySamplerFilter::Linear),
+            ),
+        );
+        visitor(
+            "environment_specular",
+            UniformValue::Cubemap(
+                &self.specular,
+                cubemap_sampler(MinifySamplerFilter::LinearMipmapLinear),
+            ),
+        );
+        visitor(
+            "environment_brdf",
+            UniformValue::Texture2d(
+                &self.brdf_lut,
+                Some(SamplerBehavior {
+                    wrap_function: (
+                        SamplerWrapFunction::Clamp,
+                        SamplerWrapFunction::Clamp,
+                        SamplerWrapFunction::Clamp,
+                    ),
+                    ..Default::default()
+                }),
+            ),
+        );
+        visitor(
+            "environment_specular_levels",
+            UniformValue::Float(self.get_specular_levels() as f32),
+        );
+    }
+}
+
+struct BakeMaterial<'a> {
+    program: &'a Program,
+    values: Vec<(&'static str, UniformValue<'a>)>,
+}
+
+impl<'a> Material for BakeMaterial<'a> {
+    fn get_program(&self) -> Option<&Program> {
+        Some(self.program)
+    }
+
+    fn get_draw_parameters(&self) -> DrawParameters<'_> {
+        Default::default()
+    }
+
+    fn visit_uniforms<'n>(&'n self, visitor: &mut dyn FnMut(&str, UniformValue<'n>)) {
+        for (name, value) in self.values.iter() {
+            visitor(name, *value);
+        }
+    }
+}
+
+pub struct EnvironmentBaker {
+    equirectangular_program: Rc<Program>,
+    irradiance_program: Rc<Program>,
+    prefilter_program: Rc<Program>,
+    quad: GpuGeometry,
+    camera: Camera,
+    brdf_lut: Rc<Texture2d>,
+}
+
+#[allow(dead_code)]
+impl EnvironmentBaker {
+    pub fn new(
+        display: &Display,
+        programs: &ProgramsCache,
+    ) -> Result<EnvironmentBaker, RenderError> {
+        let programs_cache = ProgramsCache::layered(
+            display,
+            programs.get_glsl_version(),
+            &[&STANDARD_CHUNKS_DIRECTORY],
+            &[&INTERNAL_MATERIALS_DIRECTORY],
+        );
+
+        let quad = Primitive::quad(
+            [
+                pos3(1.0, 1.0, 0.0),
+                pos3(-1.0, 1.0, 0.0),
+                pos3(-1.0, -1.0, 0.0),
+                pos3(1.0, -1.0, 0.0),
+            ],
+            BACKWARDS,
+        )
+        .to_gpu_geometry(display);
+        let camera = Camera::new(
+            IDENTITY,
+            Projection::perspective_fov(PI * 0.5, 1.0, 0.1, 1000.0),
+        );
+
+        let brdf_lut = Texture2d::empty_with_format(
+            display,
+            UncompressedFloatFormat::F16F16,
+            MipmapsOption::NoMipmap,
+            BRDF_LUT_SIZE,
+            BRDF_LUT_SIZE,
+        )?;
+        let brdf_program = internal_program(&programs_cache, "ibl_brdf")?;
+        let material = BakeMaterial {
+            program: &brdf_program,
+            values: vec![],
+        };
+        SimpleFrameBuffer::new(display, &brdf_lut)?
+            .draw_single(&quad, &IDENTITY, &camera, &material)?;
+
+        Ok(EnvironmentBaker {
+            equirectangular_program: internal_program(&programs_cache, "ibl_equirectangular")?,
+            irradiance_program: internal_program(&programs_cache, "ibl_irradiance")?,
+            prefilter_program: internal_program(&programs_cache, "ibl_prefilter")?,
+            quad,
+            camera,
+            brdf_lut: Rc::new(brdf_lut),
+        })
+    }
+
+    pub fn get_brdf_lut(&self) -> Rc<Texture2d> {
+        Rc::clone(&self.brdf_lut)
+    }
+
+    fn render_faces(
+        &self,
+        display: &Display,
+        target: &Cubemap,
+        level: u32,
+        program: &Program,
+        values: &[(&'static str, UniformValue<'_>)],
+    ) -> Result<(), RenderError> {
+        let mipmap = target
+            .mipmap(level)
+            .expect("cubemap was allocated with fewer mipmap levels");
+
+        for &(layer, forward, up) in CUBE_FACES.iter() {
+            let mut values = values.to_vec();
+            values.push(("face_forward", UniformValue::Vec3(forward)));
+            values.push(("face_right", UniformValue::Vec3(cross(forward, up))));
+            values.push(("face_up", UniformValue::Vec3(up)));
+            let material = BakeMaterial { program, values };
+
+            SimpleFrameBuffer::new(display, mipmap.image(layer))?.draw_single(
+                &self.quad,
+                &IDENTITY,
+                &self.camera,
+                &material,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn equirectangular_to_cubemap(
+        &self,
+        display: &Display,
+        source: &Texture2d,
+        size: u32,
+    ) -> Result<Cubemap, RenderError> {
+        let cubemap = Cubemap::empty_with_format(
+            display,
+            UncompressedFloatFormat::F16F16F16F16,
+            MipmapsOption::NoMipmap,
+            size,
+        )?;
+
+        self.render_faces(
+            display,
+            &cubemap,
+            0,
+            &self.equirectangular_program,
+            &[("environment_source", UniformValue::Texture2d(source, None))],
+        )?;
+
+        Ok(cubemap)
+    }
+
+    pub fn bake(&self, display: &Display, source: &Cubemap) -> Result<EnvironmentMap, RenderError> {
+        let source_value =
+            UniformValue::Cubemap(source, cubemap_sampler(MinifySamplerFilter::Linear));
+
+        let irradiance = Cubemap::empty_with_format(
+            display,
+            UncompressedFloatFormat::F16F16F16F16,
+            MipmapsOption::NoMipmap,
+            IRRADIANCE_SIZE,
+        )?;
+        self.render_faces(
+            display,
+            &irradiance,
+            0,
+            &self.irradiance_program,
+            &[("environment_cubemap", source_value)],
+        )?;
+
+        let specular = Cubemap::empty_with_format(
+            display,
+            UncompressedFloatFormat::F16F16F16F16,
+            MipmapsOption::EmptyMipmapsMax(SPECULAR_LEVELS - 1),
+            SPECULAR_SIZE,
+        )?;
+        for level in 0..SPECULAR_LEVELS {
+            let roughness = level as f32 / (SPECULAR_LEVELS - 1) as f32;
+            self.render_faces(
+                display,
+                &specular,
+                level,
+                &self.prefilter_program,
+                &[
+                    ("environment_cubemap", source_value),
+                    ("prefilter_roughness", UniformValue::Float(roughness)),
+                ],
+            )?;
+        }
+
+        Ok(EnvironmentMap {
+            irradiance,
+            specular,
+            brdf_lut: Rc::clone(&self.brdf_lut),
+            source: None,
+        })
+    }
+
+    pub fn bake_equirectangular(
+        &self,
+        display: &Display,
+        source: &Texture2d,
+        size: u32,
+    ) -> Result<EnvironmentMap, RenderError> {
+        let cubemap = self.equirectangular_to_cubemap(display, source, size)?;
+        self.bake(display, &cubemap)
+    }
+
+    pub fn capture(
+        &self,
+        display: &Display,
+        scene: &Scene,
+        position: Position3,
+        size: u32,
+        projection: Projection,
+    ) -> Result<Cubemap, RenderError> {
+        let cubemap = Cubemap::empty_with_format(
+            display,
+            UncompressedFloatFormat::F16F16F16F16,
+            MipmapsOption::NoMipmap,
+            size,
+        )?;
+        let depth_buffer = DepthRenderBuffer::new(display, DepthFormat::I24, size, size)?;
+
+        for &(layer, forward, up) in CUBE_FACES.iter() {
+            let camera = Camera::new(face_transform(position, forward, up), projection);
+            let mut framebuffer = SimpleFrameBuffer::with_depth_buffer(
+                display,
+                cubemap.main_level().image(layer),
+                &depth_buffer,
+            )?;
+
+            scene.draw(&mut framebuffer, &IDENTITY, &camera)?;
+        }
+
+        Ok(cubemap)
+    }
+}
+
+pub struct ReflectionProbe {
+    pub position: Position3,
+    pub radius: f32,
+    pub resolution: u32,
+    pub projection: Projection,
+    environment: RefCell<Option<Rc<EnvironmentMap>>>,
+}
+
+#[allow(dead_code)]
+impl ReflectionProbe {
+    pub fn new(position: Position3, radius: f32) -> ReflectionProbe {
+        ReflectionProbe {
+            position,
+            radius,
+            resolution: 128,
+            projection: Projection::perspective_fov(PI * 0.5, 1.0, 0.1, 1000.0),
+            environment: RefCell::new(None),
+        }
+    }
+
+    pub fn with_resolution(mut self, resolution: u32) -> ReflectionProbe {
+        self.resolution = resolution;
+        self
+    }
+
+    pub fn with_depth_range(mut self, depth_near: f32, depth_far: f32) -> ReflectionProbe {
+        self.projection = Projection::perspective_fov(PI * 0.5, 1.0, depth_near, depth_far);
+        self
+    }
+
+    pub fn get_environment(&self) -> Option<Rc<EnvironmentMap>> {
+        self.environment.borrow().clone()
+    }
+
+    pub fn set_environment(&self, environment: Option<Rc<EnvironmentMap>>) {
+        *self.environment.borrow_mut() = environment;
+    }
+
+    pub fn capture(
+        &self,
+        display: &Display,
+        baker: &EnvironmentBaker,
+        scene: &Scene,
+    ) -> Result<(), RenderError> {
+        let cubemap = baker.capture(
+            display,
+            scene,
+            self.position,
+            self.resolution,
+            self.projection,
+        )?;
+        let environment = baker.bake(display, &cubemap)?;
+
+        self.set_environment(Some(Rc::new(environment)));
+
+        Ok(())
+    }
+}
+
+pub(crate) struct EnvironmentUniforms<'d> {
+    probes: &'d [Rc<ReflectionProbe>],
+    environment: Option<Rc<EnvironmentMap>>,
+    fallback: &'d RefCell<Option<Rc<EnvironmentMap>>>,
+    nearest: Option<Rc<EnvironmentMap>>,
+    bound_fallback: Option<Rc<EnvironmentMap>>,
+}
+
+impl<'d> EnvironmentUniforms<'d> {
+    /// `fallback` caches the placeholder environment across frames; it is
+    /// created from the context of the first geometry drawn without one.
+    pub(crate) fn new(
+        probes: &'d [Rc<ReflectionProbe>],
+        environment: Option<Rc<EnvironmentMap>>,
+        fallback: &'d RefCell<Option<Rc<EnvironmentMap>>>,
+    ) -> EnvironmentUniforms<'d> {
+        EnvironmentUniforms {
+            probes,
+            environment,
+            fallback,
+            nearest: None,
+            bound_fallback: None,
+        }
+    }
+
+    fn fallback_environment(&self, geometry: &dyn GeometryBuffers) -> Option<Rc<EnvironmentMap>> {
+        if let Some(fallback) = self.fallback.borrow().as_ref() {
+            return Some(Rc::clone(fallback));
+        }
+
+        let (_, index_buffer) = geometry.get_buffers()?;
+        let fallback = Rc::new(EnvironmentMap::fallback(index_buffer.get_context()).ok()?);
+        *self.fallback.borrow_mut() = Some(Rc::clone(&fallback));
+
+        Some(fallback)
+    }
+
+    fn nearest_environment(&self, position: Position3) -> Option<Rc<EnvironmentMap>> {
+        let mut nearest: Option<(f32, Rc<EnvironmentMap>)> = None;
+
+        for probe in self.probes.iter() {
+            let distance = (probe.position - position).vector.length_squared();
+            if distance > probe.radius * probe.radius {
+                continue;
+            }
+            if let Some(environment) = probe.get_environment() {
+                let closer = match &nearest {
+                    Some((best, _)) => distance < *best,
+                    None => true,
+                };
+                if closer {
+                    nearest = Some((distance, environment));
+                }
+            }
+        }
+
+        nearest
+            .map(|(_, environment)| environment)
+            .or_else(|| self.environment.clone())
+    }
+}
+
+impl<'d> DrawerLayer for EnvironmentUniforms<'d> {
+    fn visit_uniforms<'a>(&'a self, visitor: &mut dyn FnMut(&str, UniformValue<'a>)) {
+        visitor(
+            "has_environment",
+            UniformValue::Bool(self.nearest.is_some()),
+        );
+        if let Some(environment) = self.nearest.as_ref().or(self.bound_fallback.as_ref()) {
+            environment.visit_values(visitor);
+        }
+    }
+
+    fn prepare(&mut self, geometry: &dyn GeometryBuffers, transform: &Transform) {
+        self.nearest = self.nearest_environment(*transform * ORIGIN);
+        self.bound_fallback = match self.nearest {
+            Some(_) => None,
+            None => self.fallback_environment(geometry),
+        };
+    }
+}
+
+pub(crate) type EnvironmentDrawer<'d> = LayeredDrawer<'d, EnvironmentUniforms<'d>>;
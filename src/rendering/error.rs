@@ -0,0 +1,80 @@
+use std::error::Error;
+use std::fmt;
+
+use glium::framebuffer::{RenderBufferCreationError, ValidationError};
+use glium::texture::TextureCreationError;
+use glium::{DrawError, ReadError};
+
+#[derive(Debug)]
+pub enum RenderError {
+    TextureCreation(TextureCreationError),
+    RenderBufferCreation(RenderBufferCreationError),
+    Framebuffer(ValidationError),
+    Draw(DrawError),
+    Read(ReadError),
+    MissingBuffers,
+    MissingProgram(Option<String>),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenderError::TextureCreation(error) => {
+                write!(formatter, "texture creation failed: {:?}", error)
+            }
+            RenderError::RenderBufferCreation(error) => {
+                write!(formatter, "render buffer creation failed: {:?}", error)
+            }
+            RenderError::Framebuffer(error) => {
+                write!(formatter, "framebuffer creation failed: {:?}", error)
+            }
+            RenderError::Draw(error) => write!(formatter, "draw failed: {}", error),
+            RenderError::Read(error) => write!(formatter, "read back failed: {:?}", error),
+            RenderError::MissingBuffers => {
+                write!(formatter, "geometry has no GPU buffers to draw from")
+            }
+            RenderError::MissingProgram(Some(name)) => {
+                write!(
+                    formatter,
+                    "material \"{}\" has no program to draw with",
+                    name
+                )
+            }
+            RenderError::MissingProgram(None) => {
+                write!(formatter, "material has no program to draw with")
+            }
+        }
+    }
+}
+
+impl Error for RenderError {}
+
+impl From<TextureCreationError> for RenderError {
+    fn from(error: TextureCreationError) -> RenderError {
+        RenderError::TextureCreation(error)
+    }
+}
+
+impl From<RenderBufferCreationError> for RenderError {
+    fn from(error: RenderBufferCreationError) -> RenderError {
+        RenderError::RenderBufferCreation(error)
+    }
+}
+
+impl From<ValidationError> for RenderError {
+    fn from(error: ValidationError) -> RenderError {
+        RenderError::Framebuffer(error)
+    }
+}
+
+impl From<DrawError> for RenderError {
+    fn from(error: DrawError) -> RenderError {
+        RenderError::Draw(error)
+    }
+}
+
+impl From<ReadError> for RenderError {
+    fn from(error: ReadError) -> RenderError {
+        RenderError::Read(error)
+    }
+}
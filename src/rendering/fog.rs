@@ -0,0 +1,93 @@
+use glium::uniforms::{AsUniformValue, UniformValue};
+
+use super::layer::{DrawerLayer, LayeredDrawer};
+use super::*;
+
+#[derive(Copy, Clone, Debug)]
+pub enum Fog {
+    Linear {
+        start: f32,
+        end: f32,
+    },
+    Exponential {
+        density: f32,
+    },
+    ExponentialSquared {
+        density: f32,
+    },
+    Height {
+        density: f32,
+        base_height: f32,
+        falloff: f32,
+    },
+}
+
+#[derive(Copy, Clone)]
+pub(crate) struct FogUniforms {
+    mode: i32,
+    color: [f32; 4],
+    start: f32,
+    end: f32,
+    density: f32,
+    height: f32,
+    height_falloff: f32,
+}
+
+impl FogUniforms {
+    pub(crate) fn new(fog: Option<Fog>, color: Color) -> FogUniforms {
+        let none = FogUniforms {
+            mode: 0,
+            color: color.to_array(),
+            start: 0.0,
+            end: 0.0,
+            density: 0.0,
+            height: 0.0,
+            height_falloff: 0.0,
+        };
+
+        match fog {
+            None => none,
+            Some(Fog::Linear { start, end }) => FogUniforms {
+                mode: 1,
+                start,
+                end,
+                ..none
+            },
+            Some(Fog::Exponential { density }) => FogUniforms {
+                mode: 2,
+                density,
+                ..none
+            },
+            Some(Fog::ExponentialSquared { density }) => FogUniforms {
+                mode: 3,
+                density,
+                ..none
+            },
+            Some(Fog::Height {
+                density,
+                base_height,
+                falloff,
+            }) => FogUniforms {
+                mode: 4,
+                density,
+                height: base_height,
+                height_falloff: falloff,
+                ..none
+            },
+        }
+    }
+}
+
+impl DrawerLayer for FogUniforms {
+    fn visit_uniforms<'a>(&'a self, visitor: &mut dyn FnMut(&str, UniformValue<'a>)) {
+        visitor("fog_mode", self.mode.as_uniform_value());
+        visitor("fog_color", self.color.as_uniform_value());
+        visitor("fog_start", self.start.as_uniform_value());
+        visitor("fog_end", self.end.as_uniform_value());
+        visitor("fog_density", self.density.as_uniform_value());
+        visitor("fog_height", self.height.as_uniform_value());
+        visitor("fog_height_falloff", self.height_falloff.as_uniform_value());
+    }
+}
+
+pub(crate) type FogDrawer<'d> = LayeredDrawer<'d, FogUniforms>;
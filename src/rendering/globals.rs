@@ -0,0 +1,54 @@
+use glium::uniforms::{AsUniformValue, UniformValue};
+use glium::*;
+
+use super::super::game::FrameTime;
+use super::layer::{DrawerLayer, LayeredDrawer};
+use super::*;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FrameGlobals {
+    pub time: f32,
+    pub delta_time: f32,
+    pub frame_index: u32,
+    pub viewport_size: [f32; 2],
+}
+
+#[allow(dead_code)]
+impl FrameGlobals {
+    pub fn new(frame_time: FrameTime, viewport_size: (u32, u32)) -> FrameGlobals {
+        FrameGlobals {
+            time: frame_time.time as f32,
+            delta_time: frame_time.delta_time as f32,
+            frame_index: frame_time.frame_index as u32,
+            viewport_size: [viewport_size.0 as f32, viewport_size.1 as f32],
+        }
+    }
+
+    pub fn for_surface<S: Surface>(frame_time: FrameTime, surface: &S) -> FrameGlobals {
+        FrameGlobals::new(frame_time, surface.get_dimensions())
+    }
+
+    pub fn with_viewport(self, viewport: &Viewport) -> FrameGlobals {
+        FrameGlobals {
+            viewport_size: [viewport.width as f32, viewport.height as f32],
+            ..self
+        }
+    }
+}
+
+impl DrawerLayer for FrameGlobals {
+    fn visit_uniforms<'a>(&'a self, visitor: &mut dyn FnMut(&str, UniformValue<'a>)) {
+        visitor("time", self.time.as_uniform_value());
+        visitor("delta_time", self.delta_time.as_uniform_value());
+        visitor("frame_index", self.frame_index.as_uniform_value());
+        visitor("viewport_size", self.viewport_size.as_uniform_value());
+    }
+}
+
+pub type GlobalsDrawer<'d> = LayeredDrawer<'d, FrameGlobals>;
+
+impl<'d> GlobalsDrawer<'d> {
+    pub fn get_globals(&self) -> &FrameGlobals {
+        self.get_layer()
+    }
+}
@@ -0,0 +1,649 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use super::behaviour::{Behaviour, BehaviourContext, BehaviourSlot};
+use super::traversal::{self, BreadthFirst, DepthFirst, SceneVisitor};
+use super::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    index: u32,
+    generation: u32,
+}
+
+pub struct Node {
+    transform: Transform,
+    world_transform: Cell<Transform>,
+    dirty: Cell<bool>,
+    pub drawable: Option<Rc<dyn Drawable>>,
+    pub name: String,
+    pub visible: bool,
+    tags: Vec<String>,
+    behaviours: Vec<BehaviourSlot>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+#[allow(dead_code)]
+impl Node {
+    pub fn new(name: &str) -> Node {
+        Node {
+            transform: IDENTITY,
+            world_transform: Cell::new(IDENTITY),
+            dirty: Cell::new(true),
+            drawable: None,
+            name: name.to_string(),
+            visible: true,
+            tags: vec![],
+            behaviours: vec![],
+            parent: None,
+            children: vec![],
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Node {
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_drawable(mut self, drawable: Rc<dyn Drawable>) -> Node {
+        self.drawable = Some(drawable);
+        self
+    }
+
+    pub fn with_visible(mut self, visible: bool) -> Node {
+        self.visible = visible;
+        self
+    }
+
+    pub fn get_transform(&self) -> Transform {
+        self.transform
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Node {
+        self.add_tag(tag);
+        self
+    }
+
+    pub fn add_tag(&mut self, tag: &str) {
+        if !self.has_tag(tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) {
+        self.tags.retain(|t| t != tag);
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    pub fn get_tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn with_behaviour(mut self, behaviour: Box<dyn Behaviour>) -> Node {
+        self.add_behaviour(behaviour);
+        self
+    }
+
+    pub fn add_behaviour(&mut self, behaviour: Box<dyn Behaviour>) {
+        self.behaviours.push(BehaviourSlot {
+            behaviour,
+            started: false,
+        });
+    }
+
+    pub fn behaviour_count(&self) -> usize {
+        self.behaviours.len()
+    }
+
+    pub fn get_parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn get_children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+struct Slot {
+    generation: u32,
+    node: Option<Node>,
+}
+
+pub struct SceneGraph {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    roots: Vec<NodeId>,
+    updating: Option<NodeId>,
+    pending_despawns: Vec<NodeId>,
+}
+
+#[allow(dead_code)]
+impl SceneGraph {
+    pub fn new() -> SceneGraph {
+        SceneGraph {
+            slots: vec![],
+            free: vec![],
+            roots: vec![],
+            updating: None,
+            pending_despawns: vec![],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.get(id).is_some()
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        self.slots
+            .get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_ref())
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.slots
+            .get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_mut())
+    }
+
+    pub fn get_roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    pub fn get_parent(&self, id: NodeId) -> Option<NodeId> {
+        self.get(id).and_then(|node| node.parent)
+    }
+
+    pub fn get_children(&self, id: NodeId) -> &[NodeId] {
+        self.get(id).map_or(&[], |node| &node.children)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.node.is_some())
+            .map(|(index, slot)| NodeId {
+                index: index as u32,
+                generation: slot.generation,
+            })
+    }
+
+    pub fn find_child(&self, parent: Option<NodeId>, name: &str) -> Option<NodeId> {
+        let siblings = match parent {
+            Some(parent) => self.get_children(parent),
+            None => self.get_roots(),
+        };
+
+        siblings
+            .iter()
+            .copied()
+            .find(|&id| self.get(id).map_or(false, |node| node.name == name))
+    }
+
+    pub fn find(&self, path: &str) -> Option<NodeId> {
+        self.find_from(None, path)
+    }
+
+    pub fn find_from(&self, start: Option<NodeId>, path: &str) -> Option<NodeId> {
+        let mut current = start;
+
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            current = match name {
+                "." => current,
+                ".." => self.get_parent(current?),
+                _ => Some(self.find_child(current, name)?),
+            };
+        }
+
+        current
+    }
+
+    pub fn find_by_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = NodeId> + 'a {
+        self.ids()
+            .filter(move |&id| self.get(id).map_or(false, |node| node.name == name))
+    }
+
+    pub fn find_first_by_name(&self, name: &str) -> Option<NodeId> {
+        self.find_by_name(name).next()
+    }
+
+    pub fn find_with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = NodeId> + 'a {
+        self.ids()
+            .filter(move |&id| self.get(id).map_or(false, |node| node.has_tag(tag)))
+    }
+
+    pub fn path_of(&self, id: NodeId) -> Option<String> {
+        let mut names = vec![self.get(id)?.name.as_str()];
+
+        let mut current = self.get_parent(id);
+        while let Some(parent) = current {
+            names.push(self.get(parent)?.name.as_str());
+            current = self.get_parent(parent);
+        }
+        names.reverse();
+
+        Some(names.join("/"))
+    }
+
+    fn insert(&mut self, mut node: Node, parent: Option<NodeId>) -> NodeId {
+        node.parent = parent;
+        node.children.clear();
+        node.dirty.set(true);
+
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.node = Some(node);
+
+                NodeId {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    node: Some(node),
+                });
+
+                NodeId {
+                    index: self.slots.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    pub fn spawn(&mut self, node: Node) -> NodeId {
+        let id = self.insert(node, None);
+        self.roots.push(id);
+        id
+    }
+
+    pub fn spawn_child(&mut self, parent: NodeId, node: Node) -> Option<NodeId> {
+        if !self.contains(parent) {
+            return None;
+        }
+
+        let id = self.insert(node, Some(parent));
+        self.get_mut(parent).unwrap().children.push(id);
+        Some(id)
+    }
+
+    fn unlink(&mut self, id: NodeId) {
+        match self.get(id).and_then(|node| node.parent) {
+            Some(parent) => {
+                if let Some(parent) = self.get_mut(parent) {
+                    parent.children.retain(|&child| child != id);
+                }
+            }
+            None => self.roots.retain(|&root| root != id),
+        }
+    }
+
+    pub fn is_ancestor(&self, ancestor: NodeId, id: NodeId) -> bool {
+        let mut current = self.get_parent(id);
+        while let Some(parent) = current {
+            if parent == ancestor {
+                return true;
+            }
+            current = self.get_parent(parent);
+        }
+
+        false
+    }
+
+    pub fn reparent(&mut self, id: NodeId, parent: NodeId) -> bool {
+        if !self.contains(id) || !self.contains(parent) {
+            return false;
+        }
+        if id == parent || self.is_ancestor(id, parent) {
+            return false;
+        }
+
+        self.unlink(id);
+        self.get_mut(id).unwrap().parent = Some(parent);
+        self.get_mut(parent).unwrap().children.push(id);
+        self.mark_dirty(id);
+        true
+    }
+
+    pub fn detach(&mut self, id: NodeId) -> bool {
+        match self.get(id) {
+            None => false,
+            Some(node) if node.parent.is_none() => true,
+            Some(_) => {
+                self.unlink(id);
+                self.get_mut(id).unwrap().parent = None;
+                self.roots.push(id);
+                self.mark_dirty(id);
+                true
+            }
+        }
+    }
+
+    fn subtree(&self, id: NodeId) -> Vec<NodeId> {
+        let mut ids = vec![];
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            if let Some(node) = self.get(id) {
+                ids.push(id);
+                pending.extend(node.children.iter().rev().copied());
+            }
+        }
+        ids
+    }
+
+    fn destroy_behaviours(&mut self, id: NodeId) {
+        let mut behaviours = match self.get_mut(id) {
+            Some(node) => std::mem::take(&mut node.behaviours),
+            None => return,
+        };
+
+        for slot in behaviours.iter_mut() {
+            slot.behaviour
+                .on_destroy(&mut BehaviourContext::new(self, id));
+        }
+    }
+
+    /// Removes the node and its subtree, calling `on_destroy` on their
+    /// behaviours first. A behaviour that despawns its own node, or one of
+    /// its ancestors, only takes effect once the behaviour returns, so the
+    /// node's behaviours can be put back and destroyed while it still exists.
+    pub fn despawn(&mut self, id: NodeId) -> bool {
+        if !self.contains(id) {
+            return false;
+        }
+        if let Some(updating) = self.updating {
+            if updating == id || self.is_ancestor(id, updating) {
+                if !self.pending_despawns.contains(&id) {
+                    self.pending_despawns.push(id);
+                }
+                return true;
+            }
+        }
+
+        for node in self.subtree(id) {
+            self.destroy_behaviours(node);
+        }
+        if !self.contains(id) {
+            return true;
+        }
+
+        self.unlink(id);
+
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            let slot = &mut self.slots[id.index as usize];
+            if let Some(node) = slot.node.take() {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(id.index);
+                pending.extend(node.children);
+            }
+        }
+
+        true
+    }
+
+    pub fn clear(&mut self) {
+        if self.updating.is_some() {
+            for root in self.roots.clone() {
+                self.despawn(root);
+            }
+            return;
+        }
+
+        let ids: Vec<NodeId> = self.ids().collect();
+        for id in ids {
+            self.destroy_behaviours(id);
+        }
+
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.node.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(index as u32);
+            }
+        }
+        self.roots.clear();
+    }
+
+    fn mark_dirty(&self, id: NodeId) {
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            if let Some(node) = self.get(id) {
+                if !node.dirty.replace(true) {
+                    pending.extend(node.children.iter().copied());
+                }
+            }
+        }
+    }
+
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform) -> bool {
+        match self.get_mut(id) {
+            Some(node) => {
+                node.transform = transform;
+                self.mark_dirty(id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get_transform(&self, id: NodeId) -> Option<Transform> {
+        self.get(id).map(|node| node.transform)
+    }
+
+    pub fn prepend_transform(&mut self, id: NodeId, transform: Transform) -> bool {
+        match self.get_transform(id) {
+            Some(current) => self.set_transform(id, current * transform),
+            None => false,
+        }
+    }
+
+    pub fn append_transform(&mut self, id: NodeId, transform: Transform) -> bool {
+        match self.get_transform(id) {
+            Some(current) => self.set_transform(id, transform * current),
+            None => false,
+        }
+    }
+
+    pub fn world_transform(&self, id: NodeId) -> Option<Transform> {
+        let node = self.get(id)?;
+        if !node.dirty.get() {
+            return Some(node.world_transform.get());
+        }
+
+        let world_transform = match node.parent {
+            Some(parent) => self.world_transform(parent)? * node.transform,
+            None => node.transform,
+        };
+        node.world_transform.set(world_transform);
+        node.dirty.set(false);
+
+        Some(world_transform)
+    }
+
+    fn update_node(&mut self, id: NodeId, delta_time: f32) {
+        let mut behaviours = match self.get_mut(id) {
+            Some(node) => std::mem::take(&mut node.behaviours),
+            None => return,
+        };
+
+        self.updating = Some(id);
+        for slot in behaviours.iter_mut() {
+            if !self.pending_despawns.is_empty() {
+                break;
+            }
+
+            let mut context = BehaviourContext::new(self, id);
+            if !slot.started {
+                slot.started = true;
+                slot.behaviour.start(&mut context);
+            }
+            slot.behaviour.update(&mut context, delta_time);
+        }
+
+        self.updating = None;
+
+        let node = self.get_mut(id).unwrap();
+        behaviours.append(&mut node.behaviours);
+        node.behaviours = behaviours;
+
+        for id in std::mem::take(&mut self.pending_despawns) {
+            self.despawn(id);
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        let ids: Vec<NodeId> = self
+            .roots
+            .clone()
+            .into_iter()
+            .flat_map(|root| self.subtree(root))
+            .collect();
+
+        for id in ids {
+            self.update_node(id, delta_time);
+        }
+    }
+
+    pub fn depth_first(&self) -> DepthFirst<'_> {
+        DepthFirst::new(self, IDENTITY, None)
+    }
+
+    pub fn depth_first_from(&self, id: NodeId) -> DepthFirst<'_> {
+        DepthFirst::new(self, IDENTITY, Some(id))
+    }
+
+    pub fn breadth_first(&self) -> BreadthFirst<'_> {
+        BreadthFirst::new(self, IDENTITY, None)
+    }
+
+    pub fn breadth_first_from(&self, id: NodeId) -> BreadthFirst<'_> {
+        BreadthFirst::new(self, IDENTITY, Some(id))
+    }
+
+    pub fn walk(&self, visitor: &mut dyn SceneVisitor) -> bool {
+        traversal::walk(self, &IDENTITY, None, visitor)
+    }
+
+    pub fn walk_from(&self, id: NodeId, visitor: &mut dyn SceneVisitor) -> bool {
+        traversal::walk(self, &IDENTITY, Some(id), visitor)
+    }
+
+    fn draw_node(
+        &self,
+        id: NodeId,
+        drawer: &mut dyn Drawer,
+        transform: &Transform,
+        camera: &Camera,
+    ) -> Result<(), RenderError> {
+        let node = match self.get(id) {
+            Some(node) => node,
+            None => return Ok(()),
+        };
+        if !node.visible {
+            self.cull_node(id, drawer);
+            return Ok(());
+        }
+        let multiplied_transform = *transform * self.world_transform(id).unwrap();
+
+        if let Some(drawable) = &node.drawable {
+            drawable.draw(drawer, &multiplied_transform, camera)?;
+            drawer.note_drawn();
+        }
+
+        node.children
+            .iter()
+            .try_for_each(|&child| self.draw_node(child, drawer, transform, camera))
+    }
+
+    fn cull_node(&self, id: NodeId, drawer: &mut dyn Drawer) {
+        if let Some(node) = self.get(id) {
+            if node.drawable.is_some() {
+                drawer.note_culled();
+            }
+            for &child in node.children.iter() {
+                self.cull_node(child, drawer);
+            }
+        }
+    }
+}
+
+impl Default for SceneGraph {
+    fn default() -> SceneGraph {
+        SceneGraph::new()
+    }
+}
+
+impl Drawable for SceneGraph {
+    fn draw(
+        &self,
+        drawer: &mut dyn Drawer,
+        transform: &Transform,
+        camera: &Camera,
+    ) -> Result<(), RenderError> {
+        self.roots
+            .iter()
+            .try_for_each(|&root| self.draw_node(root, drawer, transform, camera))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    struct DespawnSelf {
+        destroyed: Rc<RefCell<Vec<bool>>>,
+    }
+
+    impl Behaviour for DespawnSelf {
+        fn update(&mut self, context: &mut BehaviourContext, _delta_time: f32) {
+            let id = context.get_id();
+            context.get_graph_mut().despawn(id);
+        }
+
+        fn on_destroy(&mut self, context: &mut BehaviourContext) {
+            let alive = context.get_graph().contains(context.get_id());
+            self.destroyed.borrow_mut().push(alive);
+        }
+    }
+
+    #[test]
+    fn destroys_behaviours_despawning_their_own_node_while_it_is_alive() {
+        let destroyed = Rc::new(RefCell::new(vec![]));
+        let mut graph = SceneGraph::new();
+        let parent = graph.spawn(Node::new("parent"));
+        let child = graph
+            .spawn_child(
+                parent,
+                Node::new("child").with_behaviour(Box::new(DespawnSelf {
+                    destroyed: Rc::clone(&destroyed),
+                })),
+            )
+            .unwrap();
+
+        graph.update(0.1);
+
+        assert_eq!(*destroyed.borrow(), vec![true]);
+        assert!(!graph.contains(child));
+        assert_eq!(graph.get(parent).unwrap().get_children(), &[]);
+        assert_eq!(graph.len(), 1);
+    }
+}
@@ -0,0 +1,118 @@
+use glium::uniforms::UniformValue;
+use glium::*;
+
+use super::description::MaterialDescription;
+use super::*;
+
+/// Something a `LayeredDrawer` adds to every draw going through it: extra
+/// uniforms, or bookkeeping about what was drawn.
+pub trait DrawerLayer {
+    /// Uniforms visited before the material's own, so the material still
+    /// wins when it sets one of them itself.
+    fn visit_uniforms<'a>(&'a self, _visitor: &mut dyn FnMut(&str, UniformValue<'a>)) {}
+
+    /// Called before each draw, for layers whose uniforms depend on what is
+    /// drawn or where.
+    fn prepare(&mut self, _geometry: &dyn GeometryBuffers, _transform: &Transform) {}
+
+    /// Called after the inner drawer drew successfully.
+    fn record_draw(&mut self, _geometry: &dyn GeometryBuffers, _material: &dyn Material) {}
+
+    fn note_culled(&mut self) {}
+
+    fn note_drawn(&mut self) {}
+}
+
+pub struct LayeredMaterial<'a, L: DrawerLayer + ?Sized> {
+    material: &'a dyn Material,
+    layer: &'a L,
+}
+
+impl<'a, L: DrawerLayer + ?Sized> LayeredMaterial<'a, L> {
+    pub fn new(material: &'a dyn Material, layer: &'a L) -> LayeredMaterial<'a, L> {
+        LayeredMaterial { material, layer }
+    }
+}
+
+impl<'a, L: DrawerLayer + ?Sized> Material for LayeredMaterial<'a, L> {
+    fn get_program(&self) -> Option<&Program> {
+        self.material.get_program()
+    }
+
+    fn get_draw_parameters(&self) -> DrawParameters<'_> {
+        self.material.get_draw_parameters()
+    }
+
+    fn visit_uniforms<'n>(&'n self, visitor: &mut dyn FnMut(&str, UniformValue<'n>)) {
+        self.layer.visit_uniforms(visitor);
+        self.material.visit_uniforms(visitor);
+    }
+
+    fn get_program_name(&self) -> Option<&str> {
+        self.material.get_program_name()
+    }
+
+    fn get_gbuffer_program(&self) -> Option<&Program> {
+        self.material.get_gbuffer_program()
+    }
+
+    fn describe(&self) -> Option<MaterialDescription> {
+        self.material.describe()
+    }
+}
+
+pub struct LayeredDrawer<'d, L: DrawerLayer> {
+    drawer: &'d mut dyn Drawer,
+    layer: L,
+}
+
+impl<'d, L: DrawerLayer> LayeredDrawer<'d, L> {
+    pub fn new(drawer: &'d mut dyn Drawer, layer: L) -> LayeredDrawer<'d, L> {
+        LayeredDrawer { drawer, layer }
+    }
+
+    pub fn get_layer(&self) -> &L {
+        &self.layer
+    }
+}
+
+impl<'d, L: DrawerLayer> Drawer for LayeredDrawer<'d, L> {
+    fn clear_scene(&mut self, color: Color, depth: f32) {
+        self.drawer.clear_scene(color, depth);
+    }
+
+    fn draw_single(
+        &mut self,
+        geometry: &dyn GeometryBuffers,
+        transform: &Transform,
+        camera: &Camera,
+        material: &dyn Material,
+    ) -> Result<(), RenderError> {
+        self.layer.prepare(geometry, transform);
+
+        let layered = LayeredMaterial::new(material, &self.layer);
+        self.drawer
+            .draw_single(geometry, transform, camera, &layered)?;
+
+        self.layer.record_draw(geometry, material);
+        Ok(())
+    }
+
+    fn push_pick_handle(&mut self, handle: PickHandle) {
+        self.drawer.push_pick_handle(handle);
+    }
+
+    fn pop_pick_handle(&mut self) {
+        self.drawer.pop_pick_handle();
+    }
+
+    fn note_culled(&mut self) {
+        self.layer.note_culled();
+        self.drawer.note_culled();
+    }
+
+    fn note_drawn(&mut self) {
+        self.layer.note_drawn();
+        self.drawer.note_drawn();
+    }
+}
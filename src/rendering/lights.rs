@@ -0,0 +1,45 @@
+use glium::uniforms::{AsUniformValue, UniformValue};
+
+use super::deferred::Light;
+use super::layer::{DrawerLayer, LayeredDrawer};
+use super::*;
+
+/// Forward materials take a single directional light, so the scene feeds
+/// them its ambient color and the first directional light. Materials that
+/// set these uniforms themselves still win, since they are visited later.
+#[derive(Copy, Clone)]
+pub(crate) struct LightUniforms {
+    ambient_color: [f32; 3],
+    direction: [f32; 3],
+    color: [f32; 3],
+}
+
+impl LightUniforms {
+    pub(crate) fn new(ambient_color: Color, lights: &[Light]) -> LightUniforms {
+        let directional = lights.iter().find_map(|light| match *light {
+            Light::Directional { direction, color } => Some((direction, color)),
+            Light::Point { .. } => None,
+        });
+
+        let (direction, color) = match directional {
+            Some((direction, color)) => (direction.vector.as_array(), [color.r, color.g, color.b]),
+            None => ([0.0, -1.0, 0.0], [0.0, 0.0, 0.0]),
+        };
+
+        LightUniforms {
+            ambient_color: [ambient_color.r, ambient_color.g, ambient_color.b],
+            direction,
+            color,
+        }
+    }
+}
+
+impl DrawerLayer for LightUniforms {
+    fn visit_uniforms<'a>(&'a self, visitor: &mut dyn FnMut(&str, UniformValue<'a>)) {
+        visitor("ambient_color", self.ambient_color.as_uniform_value());
+        visitor("light_direction", self.direction.as_uniform_value());
+        visitor("light_color", self.color.as_uniform_value());
+    }
+}
+
+pub(crate) type LightsDrawer<'d> = LayeredDrawer<'d, LightUniforms>;
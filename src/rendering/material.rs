@@ -1,25 +1,64 @@
-use std::borrow::Borrow;
+use std::cell::Cell;
+use std::collections::BTreeMap;
 use std::rc::Rc;
 
-use glium::uniforms::UniformValue;
+use glium::texture::{SrgbTexture2d, Texture2d};
+use glium::uniforms::{AsUniformValue, UniformValue};
 use glium::*;
 
+use super::super::math::raw::matrix::Matrix4;
+use super::super::math::*;
 use super::super::program::ProgramsCache;
+use super::description::{MaterialDescription, ParameterDescription};
+use super::render_state::RenderState;
 
 pub trait Material {
-    fn get_program(&self) -> &Program;
+    fn get_program(&self) -> Option<&Program>;
     fn get_draw_parameters(&self) -> DrawParameters;
     fn visit_uniforms<'n>(&'n self, visitor: &mut dyn FnMut(&str, UniformValue<'n>));
+
+    fn get_program_name(&self) -> Option<&str> {
+        None
+    }
+
+    fn get_gbuffer_program(&self) -> Option<&Program> {
+        None
+    }
+
+    fn describe(&self) -> Option<MaterialDescription> {
+        None
+    }
+}
+
+pub fn gbuffer_program_name(program_name: &str) -> String {
+    format!("gbuffer_{}", program_name)
 }
 
 pub struct SimpleMaterial {
-    program: Rc<Program>,
+    program: Option<Rc<Program>>,
+    program_name: Option<String>,
+    gbuffer_program: Option<Rc<Program>>,
+    render_state: RenderState,
 }
 
 #[allow(dead_code)]
 impl SimpleMaterial {
     pub fn new(program: Rc<Program>) -> SimpleMaterial {
-        SimpleMaterial { program }
+        SimpleMaterial {
+            program: Some(program),
+            program_name: None,
+            gbuffer_program: None,
+            render_state: Default::default(),
+        }
+    }
+
+    pub fn named(program_name: &str) -> SimpleMaterial {
+        SimpleMaterial {
+            program: None,
+            program_name: Some(program_name.to_string()),
+            gbuffer_program: None,
+            render_state: Default::default(),
+        }
     }
 
     pub fn from_cache(
@@ -29,24 +68,441 @@ impl SimpleMaterial {
         let program = programs_cache.get_program(program_name);
 
         match program {
-            Some(program) => Some(SimpleMaterial { program }),
+            Some(program) => Some(SimpleMaterial {
+                program: Some(program),
+                program_name: Some(program_name.to_string()),
+                gbuffer_program: programs_cache.get_program(&gbuffer_program_name(program_name)),
+                render_state: Default::default(),
+            }),
             None => None,
         }
     }
+
+    pub fn with_gbuffer_program(mut self, gbuffer_program: Rc<Program>) -> SimpleMaterial {
+        self.gbuffer_program = Some(gbuffer_program);
+        self
+    }
+
+    pub fn set_gbuffer_program(&mut self, gbuffer_program: Option<Rc<Program>>) {
+        self.gbuffer_program = gbuffer_program;
+    }
+
+    pub fn with_render_state(mut self, render_state: RenderState) -> SimpleMaterial {
+        self.render_state = render_state;
+        self
+    }
+
+    pub fn get_render_state(&self) -> &RenderState {
+        &self.render_state
+    }
+
+    pub fn get_render_state_mut(&mut self) -> &mut RenderState {
+        &mut self.render_state
+    }
+
+    pub fn set_render_state(&mut self, render_state: RenderState) {
+        self.render_state = render_state;
+    }
 }
 
 impl Material for SimpleMaterial {
-    fn get_program(&self) -> &Program {
-        self.program.borrow()
+    fn get_program(&self) -> Option<&Program> {
+        self.program.as_deref()
     }
 
     fn get_draw_parameters(&self) -> DrawParameters {
-        DrawParameters {
-            blend: Blend::alpha_blending(),
-            backface_culling: BackfaceCullingMode::CullClockwise,
-            ..Default::default()
-        }
+        self.render_state.to_draw_parameters()
     }
 
     fn visit_uniforms<'n>(&self, _visitor: &mut dyn FnMut(&str, UniformValue<'n>)) {}
+
+    fn get_program_name(&self) -> Option<&str> {
+        self.program_name.as_deref()
+    }
+
+    fn get_gbuffer_program(&self) -> Option<&Program> {
+        self.gbuffer_program.as_deref()
+    }
+
+    fn describe(&self) -> Option<MaterialDescription> {
+        Some(MaterialDescription {
+            program: self.program_name.clone()?,
+            parameters: BTreeMap::new(),
+        })
+    }
+}
+
+/// A value that can still be written after its material is shared behind an
+/// `Rc`, so that animations can drive it from the update loop.
+#[derive(Clone, Debug)]
+pub struct SharedParameter<T: Copy> {
+    value: Rc<Cell<T>>,
+}
+
+impl<T: Copy> SharedParameter<T> {
+    pub fn new(value: T) -> SharedParameter<T> {
+        SharedParameter {
+            value: Rc::new(Cell::new(value)),
+        }
+    }
+
+    pub fn get(&self) -> T {
+        self.value.get()
+    }
+
+    pub fn set(&self, value: T) {
+        self.value.set(value);
+    }
+}
+
+#[derive(Clone)]
+pub enum Parameter {
+    Bool(bool),
+    Int(i32),
+    Float(f32),
+    Vector2([f32; 2]),
+    Vector3([f32; 3]),
+    Vector4([f32; 4]),
+    Matrix4([[f32; 4]; 4]),
+    Color(Color),
+    SharedFloat(SharedParameter<f32>),
+    SharedColor(SharedParameter<Color>),
+    Texture(Rc<Texture2d>),
+    SrgbTexture(Rc<SrgbTexture2d>),
+}
+
+impl Parameter {
+    pub fn as_uniform_value(&self) -> UniformValue<'_> {
+        match self {
+            Parameter::Bool(value) => value.as_uniform_value(),
+            Parameter::Int(value) => value.as_uniform_value(),
+            Parameter::Float(value) => value.as_uniform_value(),
+            Parameter::Vector2(value) => value.as_uniform_value(),
+            Parameter::Vector3(value) => value.as_uniform_value(),
+            Parameter::Vector4(value) => value.as_uniform_value(),
+            Parameter::Matrix4(value) => value.as_uniform_value(),
+            Parameter::Color(color) => UniformValue::Vec4(color.to_array()),
+            Parameter::SharedFloat(value) => UniformValue::Float(value.get()),
+            Parameter::SharedColor(color) => UniformValue::Vec4(color.get().to_array()),
+            Parameter::Texture(texture) => UniformValue::Texture2d(texture, None),
+            Parameter::SrgbTexture(texture) => UniformValue::SrgbTexture2d(texture, None),
+        }
+    }
+}
+
+impl From<bool> for Parameter {
+    fn from(value: bool) -> Parameter {
+        Parameter::Bool(value)
+    }
+}
+
+impl From<i32> for Parameter {
+    fn from(value: i32) -> Parameter {
+        Parameter::Int(value)
+    }
+}
+
+impl From<f32> for Parameter {
+    fn from(value: f32) -> Parameter {
+        Parameter::Float(value)
+    }
+}
+
+impl From<[f32; 2]> for Parameter {
+    fn from(value: [f32; 2]) -> Parameter {
+        Parameter::Vector2(value)
+    }
+}
+
+impl From<[f32; 3]> for Parameter {
+    fn from(value: [f32; 3]) -> Parameter {
+        Parameter::Vector3(value)
+    }
+}
+
+impl From<[f32; 4]> for Parameter {
+    fn from(value: [f32; 4]) -> Parameter {
+        Parameter::Vector4(value)
+    }
+}
+
+impl From<Position2> for Parameter {
+    fn from(value: Position2) -> Parameter {
+        Parameter::Vector2(value.vector.as_array())
+    }
+}
+
+impl From<Position3> for Parameter {
+    fn from(value: Position3) -> Parameter {
+        Parameter::Vector3(value.vector.as_array())
+    }
+}
+
+impl From<Displacement3> for Parameter {
+    fn from(value: Displacement3) -> Parameter {
+        Parameter::Vector3(value.vector.as_array())
+    }
+}
+
+impl From<Direction3> for Parameter {
+    fn from(value: Direction3) -> Parameter {
+        Parameter::Vector3(value.vector.as_array())
+    }
+}
+
+impl From<Matrix4> for Parameter {
+    fn from(value: Matrix4) -> Parameter {
+        Parameter::Matrix4(value.elements)
+    }
+}
+
+impl From<Transform> for Parameter {
+    fn from(value: Transform) -> Parameter {
+        Parameter::Matrix4(value.matrix.elements)
+    }
+}
+
+impl From<Color> for Parameter {
+    fn from(value: Color) -> Parameter {
+        Parameter::Color(value)
+    }
+}
+
+impl From<SharedParameter<f32>> for Parameter {
+    fn from(value: SharedParameter<f32>) -> Parameter {
+        Parameter::SharedFloat(value)
+    }
+}
+
+impl From<SharedParameter<Color>> for Parameter {
+    fn from(value: SharedParameter<Color>) -> Parameter {
+        Parameter::SharedColor(value)
+    }
+}
+
+impl From<Rc<Texture2d>> for Parameter {
+    fn from(value: Rc<Texture2d>) -> Parameter {
+        Parameter::Texture(value)
+    }
+}
+
+impl From<Rc<SrgbTexture2d>> for Parameter {
+    fn from(value: Rc<SrgbTexture2d>) -> Parameter {
+        Parameter::SrgbTexture(value)
+    }
+}
+
+pub struct ParameterMaterial {
+    program: Option<Rc<Program>>,
+    program_name: Option<String>,
+    parameters: BTreeMap<String, Parameter>,
+    texture_paths: BTreeMap<String, String>,
+    gbuffer_program: Option<Rc<Program>>,
+    render_state: RenderState,
+}
+
+#[allow(dead_code)]
+impl ParameterMaterial {
+    pub fn new(program: Rc<Program>) -> ParameterMaterial {
+        ParameterMaterial {
+            program: Some(program),
+            program_name: None,
+            parameters: BTreeMap::new(),
+            texture_paths: BTreeMap::new(),
+            gbuffer_program: None,
+            render_state: Default::default(),
+        }
+    }
+
+    pub fn named(program_name: &str) -> ParameterMaterial {
+        ParameterMaterial {
+            program: None,
+            program_name: Some(program_name.to_string()),
+            parameters: BTreeMap::new(),
+            texture_paths: BTreeMap::new(),
+            gbuffer_program: None,
+            render_state: Default::default(),
+        }
+    }
+
+    pub fn from_cache(
+        programs_cache: &ProgramsCache,
+        program_name: &str,
+    ) -> Option<ParameterMaterial> {
+        programs_cache
+            .get_program(program_name)
+            .map(|program| ParameterMaterial {
+                program: Some(program),
+                program_name: Some(program_name.to_string()),
+                parameters: BTreeMap::new(),
+                texture_paths: BTreeMap::new(),
+                gbuffer_program: programs_cache.get_program(&gbuffer_program_name(program_name)),
+                render_state: Default::default(),
+            })
+    }
+
+    pub fn with_gbuffer_program(mut self, gbuffer_program: Rc<Program>) -> ParameterMaterial {
+        self.gbuffer_program = Some(gbuffer_program);
+        self
+    }
+
+    pub fn set_gbuffer_program(&mut self, gbuffer_program: Option<Rc<Program>>) {
+        self.gbuffer_program = gbuffer_program;
+    }
+
+    pub fn with_render_state(mut self, render_state: RenderState) -> ParameterMaterial {
+        self.render_state = render_state;
+        self
+    }
+
+    pub fn get_render_state(&self) -> &RenderState {
+        &self.render_state
+    }
+
+    pub fn get_render_state_mut(&mut self) -> &mut RenderState {
+        &mut self.render_state
+    }
+
+    pub fn set_render_state(&mut self, render_state: RenderState) {
+        self.render_state = render_state;
+    }
+
+    pub fn with<P: Into<Parameter>>(mut self, name: &str, value: P) -> ParameterMaterial {
+        self.set(name, value);
+        self
+    }
+
+    pub fn set<P: Into<Parameter>>(&mut self, name: &str, value: P) {
+        self.texture_paths.remove(name);
+        self.parameters.insert(name.to_string(), value.into());
+    }
+
+    /// Sets a texture parameter and remembers the file it was loaded from,
+    /// so that describing the material can refer back to it.
+    pub fn set_texture<P: Into<Parameter>>(&mut self, name: &str, value: P, path: &str) {
+        self.set(name, value);
+        self.texture_paths
+            .insert(name.to_string(), path.to_string());
+    }
+
+    pub fn get_texture_path(&self, name: &str) -> Option<&str> {
+        self.texture_paths.get(name).map(String::as_str)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Parameter> {
+        self.parameters.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Parameter> {
+        self.parameters.get_mut(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Parameter> {
+        self.texture_paths.remove(name);
+        self.parameters.remove(name)
+    }
+
+    /// Turns a float parameter into a shared one, returning a handle that can
+    /// still write it once the material is behind an `Rc`.
+    pub fn share_float(&mut self, name: &str) -> Option<SharedParameter<f32>> {
+        let shared = match self.parameters.get(name)? {
+            Parameter::Float(value) => SharedParameter::new(*value),
+            Parameter::SharedFloat(shared) => return Some(shared.clone()),
+            _ => return None,
+        };
+
+        self.parameters
+            .insert(name.to_string(), Parameter::SharedFloat(shared.clone()));
+        Some(shared)
+    }
+
+    /// Turns a color parameter into a shared one, like `share_float`.
+    pub fn share_color(&mut self, name: &str) -> Option<SharedParameter<Color>> {
+        let shared = match self.parameters.get(name)? {
+            Parameter::Color(color) => SharedParameter::new(*color),
+            Parameter::SharedColor(shared) => return Some(shared.clone()),
+            _ => return None,
+        };
+
+        self.parameters
+            .insert(name.to_string(), Parameter::SharedColor(shared.clone()));
+        Some(shared)
+    }
+
+    pub fn get_parameter_names(&self) -> Vec<&String> {
+        self.parameters.keys().collect()
+    }
+
+    pub fn parameters(&self) -> impl Iterator<Item = (&String, &Parameter)> {
+        self.parameters.iter()
+    }
+
+    pub fn parameters_mut(&mut self) -> impl Iterator<Item = (&String, &mut Parameter)> {
+        self.parameters.iter_mut()
+    }
+
+    fn describe_parameter(
+        &self,
+        name: &str,
+        parameter: &Parameter,
+    ) -> Option<ParameterDescription> {
+        let texture_path = || self.get_texture_path(name).map(str::to_string);
+
+        Some(match parameter {
+            Parameter::Bool(value) => ParameterDescription::Bool(*value),
+            Parameter::Int(value) => ParameterDescription::Int(*value),
+            Parameter::Float(value) => ParameterDescription::Float(*value),
+            Parameter::Vector2(value) => ParameterDescription::Vector2(*value),
+            Parameter::Vector3(value) => ParameterDescription::Vector3(*value),
+            Parameter::Vector4(value) => ParameterDescription::Vector4(*value),
+            Parameter::Matrix4(value) => ParameterDescription::Matrix4(*value),
+            Parameter::Color(color) => ParameterDescription::Color(color.to_array()),
+            Parameter::SharedFloat(value) => ParameterDescription::Float(value.get()),
+            Parameter::SharedColor(color) => ParameterDescription::Color(color.get().to_array()),
+            Parameter::Texture(_) => ParameterDescription::Texture(texture_path()?),
+            Parameter::SrgbTexture(_) => ParameterDescription::SrgbTexture(texture_path()?),
+        })
+    }
+}
+
+impl Material for ParameterMaterial {
+    fn get_program(&self) -> Option<&Program> {
+        self.program.as_deref()
+    }
+
+    fn get_draw_parameters(&self) -> DrawParameters<'_> {
+        self.render_state.to_draw_parameters()
+    }
+
+    fn visit_uniforms<'n>(&'n self, visitor: &mut dyn FnMut(&str, UniformValue<'n>)) {
+        for (name, parameter) in self.parameters.iter() {
+            visitor(name, parameter.as_uniform_value());
+        }
+    }
+
+    fn get_program_name(&self) -> Option<&str> {
+        self.program_name.as_deref()
+    }
+
+    fn get_gbuffer_program(&self) -> Option<&Program> {
+        self.gbuffer_program.as_deref()
+    }
+
+    /// Textures that were set without a path (generated or embedded in an
+    /// imported file) cannot be referred to and are left out.
+    fn describe(&self) -> Option<MaterialDescription> {
+        let parameters = self
+            .parameters
+            .iter()
+            .filter_map(|(name, parameter)| {
+                self.describe_parameter(name, parameter)
+                    .map(|parameter| (name.clone(), parameter))
+            })
+            .collect();
+
+        Some(MaterialDescription {
+            program: self.program_name.clone()?,
+            parameters,
+        })
+    }
 }
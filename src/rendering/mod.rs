@@ -1,25 +1,74 @@
+use std::rc::Rc;
+
 use glium::uniforms::*;
 use glium::*;
 
-use super::geometry::GpuVertex;
+use super::geometry::GeometryBuffers;
 use super::math::*;
+use super::program::ProgramsCache;
+use self::description::MeshDescription;
 
+pub mod behaviour;
+pub mod deferred;
+pub mod description;
+pub mod environment;
+pub mod error;
+pub mod fog;
+pub mod globals;
+pub mod graph;
+pub mod layer;
+pub mod lights;
 pub mod material;
+pub mod particles;
+pub mod picking;
+pub mod recording;
+pub mod render_state;
 pub mod scene;
+pub mod screenshot;
+pub mod skinning;
+pub mod stats;
+pub mod texture;
+pub mod traversal;
+pub mod viewport;
 
-pub use self::material::Material;
+pub use self::behaviour::{Behaviour, BehaviourContext, Bob, Patrol, Spin};
+pub use self::deferred::{DeferredRenderer, Light};
+pub use self::description::{SceneDescription, SceneLoader};
+pub use self::environment::{EnvironmentBaker, EnvironmentMap, ReflectionProbe};
+pub use self::error::RenderError;
+pub use self::fog::Fog;
+pub use self::globals::{FrameGlobals, GlobalsDrawer};
+pub use self::graph::{Node, NodeId, SceneGraph};
+pub use self::layer::{DrawerLayer, LayeredDrawer, LayeredMaterial};
+pub use self::material::{Material, Parameter, ParameterMaterial, SharedParameter};
+pub use self::particles::{EmissionShape, ParticleEmitter, ParticleSettings};
+pub use self::picking::{pick, PickHandle, PickResult, Pickable, Picker};
+pub use self::recording::{FrameCapture, RecordingDrawer};
+pub use self::render_state::{BlendMode, CullMode, RenderState, WithRenderState};
+pub use self::screenshot::Screenshot;
+pub use self::skinning::SkinnedMesh;
+pub use self::stats::{RenderStats, StatsDrawer};
+pub use self::traversal::{BreadthFirst, DepthFirst, NodeVisit, SceneVisitor, VisitAction};
+pub use self::viewport::{Viewport, ViewportDrawer};
 
 pub trait Drawer {
     fn clear_scene(&mut self, color: Color, depth: f32);
 
     fn draw_single(
         &mut self,
-        vertex_buffer: &VertexBuffer<GpuVertex>,
-        index_buffer: &IndexBuffer<u32>,
+        geometry: &dyn GeometryBuffers,
         transform: &Transform,
         camera: &Camera,
         material: &dyn Material,
-    ) -> Result<(), DrawError>;
+    ) -> Result<(), RenderError>;
+
+    fn push_pick_handle(&mut self, _handle: PickHandle) {}
+
+    fn pop_pick_handle(&mut self) {}
+
+    fn note_culled(&mut self) {}
+
+    fn note_drawn(&mut self) {}
 }
 
 pub trait Drawable {
@@ -28,7 +77,40 @@ pub trait Drawable {
         drawer: &mut dyn Drawer,
         transform: &Transform,
         camera: &Camera,
-    ) -> Result<(), DrawError>;
+    ) -> Result<(), RenderError>;
+
+    fn describe(&self) -> Option<MeshDescription> {
+        None
+    }
+}
+
+/// Only the first `get_index_count` indices are drawn, so geometry can keep
+/// buffers larger than what it currently uses.
+fn get_buffers(
+    geometry: &dyn GeometryBuffers,
+) -> Result<(vertex::VerticesSource<'_>, index::IndexBufferSlice<'_, u32>), RenderError> {
+    let (vertex_buffer, index_buffer) =
+        geometry.get_buffers().ok_or(RenderError::MissingBuffers)?;
+    let index_buffer = index_buffer
+        .slice(0..geometry.get_index_count())
+        .ok_or(RenderError::MissingBuffers)?;
+
+    Ok((vertex_buffer, index_buffer))
+}
+
+fn get_program(material: &dyn Material) -> Result<&Program, RenderError> {
+    material.get_program().ok_or_else(|| {
+        RenderError::MissingProgram(material.get_program_name().map(|name| name.to_string()))
+    })
+}
+
+fn internal_program(
+    programs_cache: &ProgramsCache,
+    name: &str,
+) -> Result<Rc<Program>, RenderError> {
+    programs_cache
+        .get_program(name)
+        .ok_or_else(|| RenderError::MissingProgram(Some(name.to_string())))
 }
 
 struct MergedUniforms<'a> {
@@ -38,6 +120,9 @@ struct MergedUniforms<'a> {
     matrix_to_projection: [[f32; 4]; 4],
     camera_position_world: [f32; 3],
     camera_position_local: [f32; 3],
+    inverse_projection: [[f32; 4]; 4],
+    depth_near: f32,
+    depth_far: f32,
     material: &'a dyn Material,
 }
 
@@ -61,6 +146,9 @@ impl<'a> MergedUniforms<'a> {
             matrix_to_projection: matrix_to_projection.elements,
             camera_position_world: camera_position_world.vector.as_array(),
             camera_position_local: camera_position_local.vector.as_array(),
+            inverse_projection: camera.projection.inverse.elements,
+            depth_near: camera.projection.depth_near,
+            depth_far: camera.projection.depth_far,
             material,
         }
     }
@@ -85,6 +173,13 @@ impl<'u> Uniforms for MergedUniforms<'u> {
             self.camera_position_local.as_uniform_value(),
         );
 
+        visitor(
+            "inverse_projection",
+            self.inverse_projection.as_uniform_value(),
+        );
+        visitor("depth_near", self.depth_near.as_uniform_value());
+        visitor("depth_far", self.depth_far.as_uniform_value());
+
         self.material.visit_uniforms(&mut visitor);
     }
 }
@@ -99,20 +194,21 @@ where
 
     fn draw_single(
         &mut self,
-        vertex_buffer: &VertexBuffer<GpuVertex>,
-        index_buffer: &IndexBuffer<u32>,
+        geometry: &dyn GeometryBuffers,
         transform: &Transform,
         camera: &Camera,
         material: &dyn Material,
-    ) -> Result<(), DrawError> {
+    ) -> Result<(), RenderError> {
+        let (vertex_buffer, index_buffer) = get_buffers(geometry)?;
         let uniforms = MergedUniforms::new(transform, camera, material);
 
         self.draw(
             vertex_buffer,
             index_buffer,
-            material.get_program(),
+            get_program(material)?,
             &uniforms,
             &material.get_draw_parameters(),
         )
+        .map_err(RenderError::from)
     }
 }
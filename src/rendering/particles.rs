@@ -0,0 +1,434 @@
+use std::cell::{Cell, RefCell, RefMut};
+use std::f32::consts::PI;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use glium::backend::{Context, Facade};
+use glium::index::PrimitiveType;
+use glium::vertex::VerticesSource;
+use glium::*;
+
+use super::super::animation::Track;
+use super::super::geometry::GeometryBuffers;
+use super::*;
+
+#[derive(Copy, Clone, Debug)]
+pub enum EmissionShape {
+    Point,
+    Sphere { radius: f32 },
+    Cone { angle: f32, radius: f32 },
+    Box { half_extents: Displacement3 },
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Burst {
+    pub time: f32,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct ParticleSettings {
+    pub max_particles: usize,
+    pub spawn_rate: f32,
+    pub bursts: Vec<Burst>,
+    pub duration: Option<f32>,
+    pub shape: EmissionShape,
+    pub lifetime: (f32, f32),
+    pub speed: (f32, f32),
+    pub velocity_over_lifetime: Track<Displacement3>,
+    pub gravity_over_lifetime: Track<Displacement3>,
+    pub drag_over_lifetime: Track<f32>,
+    pub color_over_lifetime: Track<Color>,
+    pub size_over_lifetime: Track<f32>,
+}
+
+impl Default for ParticleSettings {
+    fn default() -> ParticleSettings {
+        ParticleSettings {
+            max_particles: 1000,
+            spawn_rate: 10.0,
+            bursts: vec![],
+            duration: None,
+            shape: EmissionShape::Point,
+            lifetime: (1.0, 1.0),
+            speed: (1.0, 1.0),
+            velocity_over_lifetime: Track::constant(dis3(0.0, 0.0, 0.0)),
+            gravity_over_lifetime: Track::constant(dis3(0.0, 0.0, 0.0)),
+            drag_over_lifetime: Track::constant(0.0),
+            color_over_lifetime: Track::constant(rgba(1.0, 1.0, 1.0, 1.0)),
+            size_over_lifetime: Track::constant(0.1),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Particle {
+    pub position: Position3,
+    pub velocity: Displacement3,
+    pub age: f32,
+    pub lifetime: f32,
+}
+
+impl Particle {
+    fn normalized_age(&self) -> f32 {
+        (self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+}
+
+static NEXT_SEED: AtomicU64 = AtomicU64::new(0x9e37_79b9_7f4a_7c15);
+
+struct Random(u64);
+
+impl Random {
+    fn new() -> Random {
+        Random(NEXT_SEED.fetch_add(0x6a09_e667_f3bc_c909, Ordering::Relaxed) | 1)
+    }
+
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        let value = self.0.wrapping_mul(0x2545_f491_4f6c_dd1d);
+
+        (value >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn range(&mut self, (min, max): (f32, f32)) -> f32 {
+        min + (max - min) * self.next()
+    }
+
+    fn unit_vector(&mut self) -> Displacement3 {
+        let z = self.range((-1.0, 1.0));
+        let angle = self.range((0.0, 2.0 * PI));
+        let radius = (1.0 - z * z).max(0.0).sqrt();
+
+        dis3(radius * angle.cos(), radius * angle.sin(), z)
+    }
+
+    fn emit(&mut self, shape: &EmissionShape) -> (Position3, Displacement3) {
+        match *shape {
+            EmissionShape::Point => (ORIGIN, self.unit_vector()),
+            EmissionShape::Sphere { radius } => {
+                let direction = self.unit_vector();
+                let distance = radius * self.next().cbrt();
+
+                (ORIGIN + direction * distance, direction)
+            }
+            EmissionShape::Cone { angle, radius } => {
+                let around = self.range((0.0, 2.0 * PI));
+                let (sin, cos) = around.sin_cos();
+
+                let distance = radius * self.next().sqrt();
+                let position = ORIGIN + dis3(cos * distance, 0.0, sin * distance);
+
+                let cos_angle = self.range((angle.cos(), 1.0));
+                let sin_angle = (1.0 - cos_angle * cos_angle).max(0.0).sqrt();
+                let direction = dis3(cos * sin_angle, cos_angle, sin * sin_angle);
+
+                (position, direction)
+            }
+            EmissionShape::Box { half_extents } => {
+                let extents = half_extents.vector;
+                let offset = dis3(
+                    self.range((-extents.x, extents.x)),
+                    self.range((-extents.y, extents.y)),
+                    self.range((-extents.z, extents.z)),
+                );
+
+                (ORIGIN + offset, UP * 1.0)
+            }
+        }
+    }
+}
+
+struct EmitterState {
+    particles: Vec<Particle>,
+    time: f32,
+    spawn_accumulator: f32,
+    random: Random,
+}
+
+#[derive(Copy, Clone)]
+pub struct GpuParticleVertex {
+    position: [f32; 3],
+    texture_coordinates: [f32; 2],
+    particle_color: [f32; 4],
+    particle_size: f32,
+}
+implement_vertex!(
+    GpuParticleVertex,
+    position,
+    texture_coordinates,
+    particle_color,
+    particle_size
+);
+
+const QUAD_CORNERS: [[f32; 2]; 4] = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+
+struct ParticleBuffers {
+    vertex_buffer: VertexBuffer<GpuParticleVertex>,
+    index_buffer: IndexBuffer<u32>,
+    capacity: usize,
+    count: usize,
+}
+
+impl ParticleBuffers {
+    fn new(context: &Rc<Context>, capacity: usize) -> ParticleBuffers {
+        let indices: Vec<u32> = (0..capacity as u32)
+            .flat_map(|quad| {
+                let base = quad * 4;
+                vec![base, base + 1, base + 2, base, base + 2, base + 3]
+            })
+            .collect();
+
+        ParticleBuffers {
+            vertex_buffer: VertexBuffer::empty_dynamic(context, capacity * 4).unwrap(),
+            index_buffer: IndexBuffer::new(context, PrimitiveType::TrianglesList, &indices)
+                .unwrap(),
+            capacity,
+            count: 0,
+        }
+    }
+}
+
+impl GeometryBuffers for ParticleBuffers {
+    fn get_buffers(&self) -> Option<(VerticesSource<'_>, &IndexBuffer<u32>)> {
+        Some(((&self.vertex_buffer).into(), &self.index_buffer))
+    }
+
+    fn get_vertex_count(&self) -> usize {
+        self.count * 4
+    }
+
+    fn get_index_count(&self) -> usize {
+        self.count * 6
+    }
+}
+
+pub struct ParticleEmitter<M: Material> {
+    context: Rc<Context>,
+    material: Rc<M>,
+    settings: RefCell<ParticleSettings>,
+    state: RefCell<EmitterState>,
+    buffers: RefCell<Option<ParticleBuffers>>,
+    emitting: Cell<bool>,
+}
+
+#[allow(dead_code)]
+impl<M: Material> ParticleEmitter<M> {
+    pub fn new<F: Facade>(
+        facade: &F,
+        material: Rc<M>,
+        settings: ParticleSettings,
+    ) -> ParticleEmitter<M> {
+        ParticleEmitter {
+            context: Rc::clone(facade.get_context()),
+            material,
+            settings: RefCell::new(settings),
+            state: RefCell::new(EmitterState {
+                particles: vec![],
+                time: 0.0,
+                spawn_accumulator: 0.0,
+                random: Random::new(),
+            }),
+            buffers: RefCell::new(None),
+            emitting: Cell::new(true),
+        }
+    }
+
+    pub fn get_material(&self) -> &M {
+        &self.material
+    }
+
+    pub fn get_material_rc(&self) -> Rc<M> {
+        Rc::clone(&self.material)
+    }
+
+    pub fn get_settings_mut(&self) -> RefMut<'_, ParticleSettings> {
+        self.settings.borrow_mut()
+    }
+
+    pub fn set_emitting(&self, emitting: bool) {
+        self.emitting.set(emitting);
+    }
+
+    pub fn is_emitting(&self) -> bool {
+        self.emitting.get()
+    }
+
+    pub fn get_particle_count(&self) -> usize {
+        self.state.borrow().particles.len()
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.is_emitting() || self.get_particle_count() > 0
+    }
+
+    pub fn restart(&self) {
+        let mut state = self.state.borrow_mut();
+        state.particles.clear();
+        state.time = 0.0;
+        state.spawn_accumulator = 0.0;
+        self.emitting.set(true);
+    }
+
+    pub fn emit(&self, count: usize) {
+        let settings = self.settings.borrow();
+        let mut state = self.state.borrow_mut();
+
+        Self::spawn(&settings, &mut state, count);
+    }
+
+    pub fn update(&self, delta_time: f32) {
+        let settings = self.settings.borrow();
+        let mut state = self.state.borrow_mut();
+
+        for particle in state.particles.iter_mut() {
+            particle.age += delta_time;
+        }
+        state
+            .particles
+            .retain(|particle| particle.age < particle.lifetime);
+
+        for particle in state.particles.iter_mut() {
+            let t = particle.normalized_age();
+
+            let gravity = settings
+                .gravity_over_lifetime
+                .sample(t)
+                .unwrap_or_else(|| dis3(0.0, 0.0, 0.0));
+            let drag = settings.drag_over_lifetime.sample(t).unwrap_or(0.0);
+            let velocity_offset = settings
+                .velocity_over_lifetime
+                .sample(t)
+                .unwrap_or_else(|| dis3(0.0, 0.0, 0.0));
+
+            particle.velocity = particle.velocity + gravity * delta_time;
+            particle.velocity = particle.velocity * (1.0 - drag * delta_time).max(0.0);
+            particle.position =
+                particle.position + (particle.velocity + velocity_offset) * delta_time;
+        }
+
+        if !self.emitting.get() {
+            return;
+        }
+
+        let previous_time = state.time;
+        state.time += delta_time;
+
+        let mut count = 0;
+        for burst in settings.bursts.iter() {
+            if burst.time >= previous_time && burst.time < state.time {
+                count += burst.count;
+            }
+        }
+
+        state.spawn_accumulator += settings.spawn_rate * delta_time;
+        let continuous = state.spawn_accumulator.floor();
+        state.spawn_accumulator -= continuous;
+        count += continuous as usize;
+
+        Self::spawn(&settings, &mut state, count);
+
+        if let Some(duration) = settings.duration {
+            if state.time >= duration {
+                self.emitting.set(false);
+            }
+        }
+    }
+
+    fn spawn(settings: &ParticleSettings, state: &mut EmitterState, count: usize) {
+        let available = settings.max_particles.saturating_sub(state.particles.len());
+
+        for _ in 0..count.min(available) {
+            let (position, direction) = state.random.emit(&settings.shape);
+            let speed = state.random.range(settings.speed);
+            let lifetime = state.random.range(settings.lifetime).max(0.0001);
+
+            state.particles.push(Particle {
+                position,
+                velocity: direction * speed,
+                age: 0.0,
+                lifetime,
+            });
+        }
+    }
+
+    fn write_buffers(&self, buffers: &mut Option<ParticleBuffers>, camera_position: Position3) {
+        let settings = self.settings.borrow();
+        let state = self.state.borrow();
+
+        let count = state.particles.len();
+        let too_small = match buffers {
+            Some(buffers) => buffers.capacity < count,
+            None => true,
+        };
+        if too_small {
+            *buffers = Some(ParticleBuffers::new(
+                &self.context,
+                count.max(settings.max_particles),
+            ));
+        }
+        let buffers = buffers.as_mut().unwrap();
+
+        let mut sorted: Vec<(f32, &Particle)> = state
+            .particles
+            .iter()
+            .map(|particle| {
+                let distance = (particle.position - camera_position)
+                    .vector
+                    .length_squared();
+                (distance, particle)
+            })
+            .collect();
+        sorted.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut vertices = Vec::with_capacity(count * 4);
+        for (_, particle) in sorted.iter() {
+            let t = particle.normalized_age();
+            let color = settings
+                .color_over_lifetime
+                .sample(t)
+                .unwrap_or_else(|| rgba(1.0, 1.0, 1.0, 1.0));
+            let size = settings.size_over_lifetime.sample(t).unwrap_or(0.0);
+
+            for &corner in QUAD_CORNERS.iter() {
+                vertices.push(GpuParticleVertex {
+                    position: particle.position.vector.as_array(),
+                    texture_coordinates: corner,
+                    particle_color: color.to_array(),
+                    particle_size: size,
+                });
+            }
+        }
+
+        if let Some(live) = buffers.vertex_buffer.slice(0..count * 4) {
+            live.write(&vertices);
+        }
+        buffers.count = count;
+    }
+}
+
+impl<M: Material> Drawable for ParticleEmitter<M> {
+    fn draw(
+        &self,
+        drawer: &mut dyn Drawer,
+        transform: &Transform,
+        camera: &Camera,
+    ) -> Result<(), RenderError> {
+        if self.get_particle_count() == 0 {
+            return Ok(());
+        }
+        let camera_position = transform.inverse() * camera.get_world_position();
+
+        let mut buffers = self.buffers.borrow_mut();
+        self.write_buffers(&mut buffers, camera_position);
+
+        drawer.draw_single(
+            buffers.as_ref().unwrap(),
+            transform,
+            camera,
+            self.material.as_ref(),
+        )
+    }
+}
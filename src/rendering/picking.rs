@@ -0,0 +1,297 @@
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use glium::framebuffer::{DepthRenderBuffer, SimpleFrameBuffer};
+use glium::texture::{DepthFormat, MipmapsOption, UncompressedUintFormat, UnsignedTexture2d};
+use glium::uniforms::{AsUniformValue, UniformValue};
+use glium::*;
+
+use super::super::program::{
+    ProgramsCache, INTERNAL_MATERIALS_DIRECTORY, STANDARD_CHUNKS_DIRECTORY,
+};
+use super::*;
+
+static NEXT_PICK_HANDLE: AtomicU32 = AtomicU32::new(1);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PickHandle(u32);
+
+impl PickHandle {
+    pub fn next() -> PickHandle {
+        PickHandle(NEXT_PICK_HANDLE.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn get_id(&self) -> u32 {
+        self.0
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PickResult {
+    pub handle: PickHandle,
+    pub depth: f32,
+    pub world_position: Position3,
+}
+
+pub struct Pickable {
+    handle: PickHandle,
+    drawable: Rc<dyn Drawable>,
+}
+
+impl Pickable {
+    pub fn new(drawable: Rc<dyn Drawable>) -> Pickable {
+        Pickable::with_handle(PickHandle::next(), drawable)
+    }
+
+    pub fn with_handle(handle: PickHandle, drawable: Rc<dyn Drawable>) -> Pickable {
+        Pickable { handle, drawable }
+    }
+
+    pub fn get_handle(&self) -> PickHandle {
+        self.handle
+    }
+
+    pub fn get_drawable(&self) -> &dyn Drawable {
+        &*self.drawable
+    }
+}
+
+impl Drawable for Pickable {
+    fn draw(
+        &self,
+        drawer: &mut dyn Drawer,
+        transform: &Transform,
+        camera: &Camera,
+    ) -> Result<(), RenderError> {
+        drawer.push_pick_handle(self.handle);
+        let result = self.drawable.draw(drawer, transform, camera);
+        drawer.pop_pick_handle();
+
+        result
+    }
+
+    fn describe(&self) -> Option<MeshDescription> {
+        self.drawable.describe()
+    }
+}
+
+struct PickingMaterial<'a> {
+    program: &'a Program,
+    pick_id: u32,
+    backface_culling: BackfaceCullingMode,
+}
+
+impl<'a> Material for PickingMaterial<'a> {
+    fn get_program(&self) -> Option<&Program> {
+        Some(self.program)
+    }
+
+    fn get_draw_parameters(&self) -> DrawParameters<'_> {
+        DrawParameters {
+            depth: Depth {
+                test: DepthTest::IfLess,
+                write: true,
+                ..Default::default()
+            },
+            backface_culling: self.backface_culling,
+            ..Default::default()
+        }
+    }
+
+    fn visit_uniforms<'n>(&'n self, visitor: &mut dyn FnMut(&str, UniformValue<'n>)) {
+        visitor("pick_id", self.pick_id.as_uniform_value());
+    }
+}
+
+fn clear_ids(ids_texture: &UnsignedTexture2d) {
+    if let Some(image) = ids_texture.main_level().first_layer().into_image(None) {
+        image.raw_clear_buffer([0u32; 4]);
+    }
+}
+
+struct PickingDrawer<'a, S: Surface> {
+    surface: &'a mut S,
+    ids_texture: &'a UnsignedTexture2d,
+    program: &'a Program,
+    handles: Vec<PickHandle>,
+}
+
+impl<'a, S: Surface> Drawer for PickingDrawer<'a, S> {
+    fn clear_scene(&mut self, _color: Color, depth: f32) {
+        clear_ids(self.ids_texture);
+        self.surface.clear_depth(depth);
+    }
+
+    fn draw_single(
+        &mut self,
+        geometry: &dyn GeometryBuffers,
+        transform: &Transform,
+        camera: &Camera,
+        material: &dyn Material,
+    ) -> Result<(), RenderError> {
+        let handle = match self.handles.last() {
+            Some(handle) => *handle,
+            None => return Ok(()),
+        };
+        let (vertex_buffer, index_buffer) = get_buffers(geometry)?;
+
+        let picking_material = PickingMaterial {
+            program: self.program,
+            pick_id: handle.get_id(),
+            backface_culling: material.get_draw_parameters().backface_culling,
+        };
+        let uniforms = MergedUniforms::new(transform, camera, &picking_material);
+
+        self.surface.draw(
+            vertex_buffer,
+            index_buffer,
+            picking_material.program,
+            &uniforms,
+            &picking_material.get_draw_parameters(),
+        )
+        .map_err(RenderError::from)
+    }
+
+    fn push_pick_handle(&mut self, handle: PickHandle) {
+        self.handles.push(handle);
+    }
+
+    fn pop_pick_handle(&mut self) {
+        self.handles.pop();
+    }
+}
+
+pub struct Picker {
+    program: Rc<Program>,
+    ids_texture: UnsignedTexture2d,
+    depth_buffer: DepthRenderBuffer,
+}
+
+impl Picker {
+    pub fn new(display: &Display, programs_cache: &ProgramsCache) -> Result<Picker, RenderError> {
+        Picker::with_glsl_version(display, programs_cache.get_glsl_version())
+    }
+
+    fn with_glsl_version(display: &Display, glsl_version: &str) -> Result<Picker, RenderError> {
+        let programs_cache = ProgramsCache::layered(
+            display,
+            glsl_version,
+            &[&STANDARD_CHUNKS_DIRECTORY],
+            &[&INTERNAL_MATERIALS_DIRECTORY],
+        );
+        let program = internal_program(&programs_cache, "picking")?;
+
+        let (width, height) = display.get_framebuffer_dimensions();
+        let (ids_texture, depth_buffer) = Picker::create_buffers(display, width, height)?;
+
+        Ok(Picker {
+            program,
+            ids_texture,
+            depth_buffer,
+        })
+    }
+
+    fn create_buffers(
+        display: &Display,
+        width: u32,
+        height: u32,
+    ) -> Result<(UnsignedTexture2d, DepthRenderBuffer), RenderError> {
+        let ids_texture = UnsignedTexture2d::empty_with_format(
+            display,
+            UncompressedUintFormat::U32U32U32U32,
+            MipmapsOption::NoMipmap,
+            width.max(1),
+            height.max(1),
+        )?;
+        let depth_buffer =
+            DepthRenderBuffer::new(display, DepthFormat::I24, width.max(1), height.max(1))?;
+
+        Ok((ids_texture, depth_buffer))
+    }
+
+    pub fn pick(
+        &mut self,
+        display: &Display,
+        drawable: &dyn Drawable,
+        camera: &Camera,
+        cursor_position: Position2,
+    ) -> Result<Option<PickResult>, RenderError> {
+        let (width, height) = display.get_framebuffer_dimensions();
+        if (width, height) != self.ids_texture.dimensions() {
+            let (ids_texture, depth_buffer) = Picker::create_buffers(display, width, height)?;
+            self.ids_texture = ids_texture;
+            self.depth_buffer = depth_buffer;
+        }
+
+        let x = cursor_position.vector.x.floor();
+        let y = cursor_position.vector.y.floor();
+        if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 {
+            return Ok(None);
+        }
+
+        let mut framebuffer =
+            SimpleFrameBuffer::with_depth_buffer(display, &self.ids_texture, &self.depth_buffer)?;
+        clear_ids(&self.ids_texture);
+        framebuffer.clear_depth(1.0);
+
+        let mut drawer = PickingDrawer {
+            surface: &mut framebuffer,
+            ids_texture: &self.ids_texture,
+            program: &self.program,
+            handles: vec![],
+        };
+        drawable.draw(&mut drawer, &IDENTITY, camera)?;
+
+        let rect = Rect {
+            left: x as u32,
+            bottom: height - 1 - y as u32,
+            width: 1,
+            height: 1,
+        };
+        let pixels: Vec<Vec<(u32, u32, u32, u32)>> = self
+            .ids_texture
+            .main_level()
+            .first_layer()
+            .into_image(None)
+            .unwrap()
+            .raw_read(&rect);
+
+        let (id, x, y, z) = pixels[0][0];
+        if id == 0 {
+            return Ok(None);
+        }
+
+        let world_position = pos3(f32::from_bits(x), f32::from_bits(y), f32::from_bits(z));
+        let view_position = camera.get_view_matrix() * world_position.vector.to_vector4(1.0);
+
+        Ok(Some(PickResult {
+            handle: PickHandle(id),
+            depth: -view_position.z,
+            world_position,
+        }))
+    }
+}
+
+fn supported_glsl_version(display: &Display) -> String {
+    match display.get_supported_glsl_version() {
+        Version(Api::GlEs, major, _) if major < 3 => "100".to_string(),
+        Version(Api::GlEs, major, minor) => format!("{}{}0 es", major, minor),
+        Version(Api::Gl, major, minor) => format!("{}{}0", major, minor),
+    }
+}
+
+/// Picks once with a throwaway `Picker`. Keep a `Picker` around instead when
+/// picking every frame, so its buffers and program are reused.
+pub fn pick(
+    display: &Display,
+    drawable: &dyn Drawable,
+    camera: &Camera,
+    cursor_position: Position2,
+) -> Result<Option<PickResult>, RenderError> {
+    Picker::with_glsl_version(display, &supported_glsl_version(display))?.pick(
+        display,
+        drawable,
+        camera,
+        cursor_position,
+    )
+}
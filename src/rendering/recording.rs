@@ -0,0 +1,435 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::path::Path;
+
+use glium::uniforms::{UniformValue, Uniforms};
+use serde::Serialize;
+
+use super::*;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum RecordedValue {
+    Bool(bool),
+    Int(i64),
+    UnsignedInt(u64),
+    Float(f64),
+    Vector(Vec<f64>),
+    Matrix(Vec<Vec<f64>>),
+    Texture(String),
+    Other(String),
+}
+
+fn vector<T: Copy + Into<f64>>(values: &[T]) -> RecordedValue {
+    RecordedValue::Vector(values.iter().map(|&v| v.into()).collect())
+}
+
+fn matrix<T: Copy + Into<f64>, R: AsRef<[T]>>(columns: &[R]) -> RecordedValue {
+    RecordedValue::Matrix(
+        columns
+            .iter()
+            .map(|column| column.as_ref().iter().map(|&v| v.into()).collect())
+            .collect(),
+    )
+}
+
+impl<'a> From<&UniformValue<'a>> for RecordedValue {
+    fn from(value: &UniformValue<'a>) -> RecordedValue {
+        match *value {
+            UniformValue::Bool(v) => RecordedValue::Bool(v),
+            UniformValue::SignedInt(v) => RecordedValue::Int(v as i64),
+            UniformValue::UnsignedInt(v) => RecordedValue::UnsignedInt(v as u64),
+            UniformValue::Int64(v) => RecordedValue::Int(v),
+            UniformValue::UnsignedInt64(v) => RecordedValue::UnsignedInt(v),
+            UniformValue::Float(v) => RecordedValue::Float(v as f64),
+            UniformValue::Double(v) => RecordedValue::Float(v),
+            UniformValue::Vec2(v) => vector(&v),
+            UniformValue::Vec3(v) => vector(&v),
+            UniformValue::Vec4(v) => vector(&v),
+            UniformValue::DoubleVec2(v) => vector(&v),
+            UniformValue::DoubleVec3(v) => vector(&v),
+            UniformValue::DoubleVec4(v) => vector(&v),
+            UniformValue::IntVec2(v) => vector(&v),
+            UniformValue::IntVec3(v) => vector(&v),
+            UniformValue::IntVec4(v) => vector(&v),
+            UniformValue::UnsignedIntVec2(v) => vector(&v),
+            UniformValue::UnsignedIntVec3(v) => vector(&v),
+            UniformValue::UnsignedIntVec4(v) => vector(&v),
+            UniformValue::Mat2(v) => matrix(&v),
+            UniformValue::Mat3(v) => matrix(&v),
+            UniformValue::Mat4(v) => matrix(&v),
+            UniformValue::DoubleMat2(v) => matrix(&v),
+            UniformValue::DoubleMat3(v) => matrix(&v),
+            UniformValue::DoubleMat4(v) => matrix(&v),
+            UniformValue::Texture2d(texture, _) => {
+                RecordedValue::Texture(format!("Texture2d {:?}", texture.dimensions()))
+            }
+            UniformValue::SrgbTexture2d(texture, _) => {
+                RecordedValue::Texture(format!("SrgbTexture2d {:?}", texture.dimensions()))
+            }
+            UniformValue::Cubemap(texture, _) => {
+                RecordedValue::Texture(format!("Cubemap {}", texture.dimensions()))
+            }
+            _ => RecordedValue::Other("unsupported".to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RecordedUniform {
+    pub name: String,
+    pub value: RecordedValue,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RecordedDraw {
+    pub geometry: usize,
+    pub vertex_count: usize,
+    pub index_count: usize,
+    pub program: Option<String>,
+    pub pick_handle: Option<u32>,
+    pub matrix_to_world: [[f32; 4]; 4],
+    pub matrix_to_projection: [[f32; 4]; 4],
+    pub camera_position_world: [f32; 3],
+    pub uniforms: Vec<RecordedUniform>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum RecordedCommand {
+    Clear { color: [f32; 4], depth: f32 },
+    Draw(Box<RecordedDraw>),
+    Culled,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct FrameCapture {
+    pub commands: Vec<RecordedCommand>,
+}
+
+#[allow(dead_code)]
+impl FrameCapture {
+    pub fn draws(&self) -> Vec<&RecordedDraw> {
+        self.commands
+            .iter()
+            .filter_map(|command| match command {
+                RecordedCommand::Draw(draw) => Some(&**draw),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, self)?;
+
+        Ok(())
+    }
+}
+
+pub struct RecordingDrawer {
+    capture: FrameCapture,
+    geometries: HashMap<*const (), usize>,
+    handles: Vec<PickHandle>,
+}
+
+#[allow(dead_code)]
+impl RecordingDrawer {
+    pub fn new() -> RecordingDrawer {
+        RecordingDrawer {
+            capture: Default::default(),
+            geometries: HashMap::new(),
+            handles: vec![],
+        }
+    }
+
+    pub fn get_capture(&self) -> &FrameCapture {
+        &self.capture
+    }
+
+    pub fn into_capture(self) -> FrameCapture {
+        self.capture
+    }
+
+    fn geometry_id(&mut self, geometry: &dyn GeometryBuffers) -> usize {
+        let next_id = self.geometries.len();
+
+        *self
+            .geometries
+            .entry(geometry as *const dyn GeometryBuffers as *const ())
+            .or_insert(next_id)
+    }
+}
+
+impl Default for RecordingDrawer {
+    fn default() -> RecordingDrawer {
+        RecordingDrawer::new()
+    }
+}
+
+impl Drawer for RecordingDrawer {
+    fn clear_scene(&mut self, color: Color, depth: f32) {
+        self.capture.commands.push(RecordedCommand::Clear {
+            color: color.to_array(),
+            depth,
+        });
+    }
+
+    fn draw_single(
+        &mut self,
+        geometry: &dyn GeometryBuffers,
+        transform: &Transform,
+        camera: &Camera,
+        material: &dyn Material,
+    ) -> Result<(), RenderError> {
+        let merged_uniforms = MergedUniforms::new(transform, camera, material);
+
+        let mut uniforms = vec![];
+        merged_uniforms.visit_values(|name, value| {
+            uniforms.push(RecordedUniform {
+                name: name.to_string(),
+                value: RecordedValue::from(&value),
+            })
+        });
+
+        let draw = RecordedDraw {
+            geometry: self.geometry_id(geometry),
+            vertex_count: geometry.get_vertex_count(),
+            index_count: geometry.get_index_count(),
+            program: material.get_program_name().map(|name| name.to_string()),
+            pick_handle: self.handles.last().map(|handle| handle.get_id()),
+            matrix_to_world: merged_uniforms.matrix_to_world,
+            matrix_to_projection: merged_uniforms.matrix_to_projection,
+            camera_position_world: merged_uniforms.camera_position_world,
+            uniforms,
+        };
+        self.capture
+            .commands
+            .push(RecordedCommand::Draw(Box::new(draw)));
+
+        Ok(())
+    }
+
+    fn push_pick_handle(&mut self, handle: PickHandle) {
+        self.handles.push(handle);
+    }
+
+    fn pop_pick_handle(&mut self) {
+        self.handles.pop();
+    }
+
+    fn note_culled(&mut self) {
+        self.capture.commands.push(RecordedCommand::Culled);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::super::super::geometry::{Geometry, Primitive};
+    use super::super::scene::{Mesh, Scene};
+    use super::*;
+
+    fn camera() -> Camera {
+        Camera::new(
+            Transform::translation(0.0, 0.0, 5.0),
+            Projection::perspective_fov(1.0, 1.0, 0.1, 100.0),
+        )
+    }
+
+    fn mesh(geometry: &Rc<Geometry>, program_name: &str, roughness: f32) -> Rc<dyn Drawable> {
+        let material = ParameterMaterial::named(program_name).with("roughness", roughness);
+
+        Rc::new(Mesh::new(Rc::clone(geometry), Rc::new(material)))
+    }
+
+    fn record(drawable: &dyn Drawable) -> FrameCapture {
+        let mut drawer = RecordingDrawer::new();
+        drawable.draw(&mut drawer, &IDENTITY, &camera()).unwrap();
+
+        drawer.into_capture()
+    }
+
+    fn uniform<'a>(draw: &'a RecordedDraw, name: &str) -> Option<&'a RecordedValue> {
+        draw.uniforms
+            .iter()
+            .find(|uniform| uniform.name == name)
+            .map(|uniform| &uniform.value)
+    }
+
+    #[test]
+    fn records_scene_graph_draws_in_order() {
+        let geometry = Rc::new(Primitive::cuboid(
+            pos3(-1.0, -1.0, -1.0),
+            pos3(1.0, 1.0, 1.0),
+        ));
+
+        let mut graph = SceneGraph::new();
+        let parent = graph.spawn(
+            Node::new("parent")
+                .with_transform(Transform::translation(1.0, 0.0, 0.0))
+                .with_drawable(mesh(&geometry, "lambert", 0.25)),
+        );
+        graph
+            .spawn_child(
+                parent,
+                Node::new("child")
+                    .with_transform(Transform::translation(0.0, 2.0, 0.0))
+                    .with_drawable(mesh(&geometry, "pbr", 0.75)),
+            )
+            .unwrap();
+
+        let capture = record(&graph);
+        let draws = capture.draws();
+
+        assert_eq!(draws.len(), 2);
+        assert_eq!(draws[0].program.as_deref(), Some("lambert"));
+        assert_eq!(draws[1].program.as_deref(), Some("pbr"));
+        assert_eq!(draws[0].geometry, draws[1].geometry);
+        assert_eq!(draws[0].vertex_count, geometry.vertices.len());
+        assert_eq!(draws[0].index_count, geometry.indices.len());
+        assert_eq!(draws[0].matrix_to_world[3][..3], [1.0, 0.0, 0.0]);
+        assert_eq!(draws[1].matrix_to_world[3][..3], [1.0, 2.0, 0.0]);
+        assert_eq!(draws[0].camera_position_world, [0.0, 0.0, 5.0]);
+        assert_eq!(
+            uniform(draws[0], "roughness"),
+            Some(&RecordedValue::Float(0.25))
+        );
+        assert_eq!(
+            uniform(draws[1], "roughness"),
+            Some(&RecordedValue::Float(0.75))
+        );
+        assert!(uniform(draws[0], "matrix_to_projection").is_some());
+    }
+
+    #[test]
+    fn records_hidden_subtrees_as_culled() {
+        let geometry = Rc::new(Primitive::cuboid(pos3(0.0, 0.0, 0.0), pos3(1.0, 1.0, 1.0)));
+
+        let mut graph = SceneGraph::new();
+        let hidden = graph.spawn(
+            Node::new("hidden")
+                .with_visible(false)
+                .with_drawable(mesh(&geometry, "lambert", 0.0)),
+        );
+        graph
+            .spawn_child(
+                hidden,
+                Node::new("child").with_drawable(mesh(&geometry, "lambert", 0.0)),
+            )
+            .unwrap();
+        graph.spawn(Node::new("shown").with_drawable(mesh(&geometry, "lambert", 0.0)));
+
+        let capture = record(&graph);
+
+        assert_eq!(capture.draws().len(), 1);
+        assert_eq!(
+            capture
+                .commands
+                .iter()
+                .filter(|command| **command == RecordedCommand::Culled)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn feeds_scene_lights_to_forward_materials() {
+        let geometry = Rc::new(Primitive::cuboid(pos3(0.0, 0.0, 0.0), pos3(1.0, 1.0, 1.0)));
+
+        let mut scene = Scene::new();
+        scene.set_ambient_color(rgba(0.1, 0.2, 0.3, 1.0));
+        scene.add_light(Light::Point {
+            position: pos3(0.0, 1.0, 0.0),
+            color: rgba(1.0, 0.0, 0.0, 1.0),
+            range: 5.0,
+        });
+        scene.add_light(Light::Directional {
+            direction: dis3(0.0, -1.0, 0.0).normalized(),
+            color: rgba(0.5, 0.5, 0.5, 1.0),
+        });
+        scene.add(mesh(&geometry, "lambert", 0.0));
+        let overridden =
+            ParameterMaterial::named("lambert").with("light_color", rgba(0.0, 1.0, 0.0, 1.0));
+        scene.add(Rc::new(Mesh::new(Rc::clone(&geometry), Rc::new(overridden))));
+
+        let capture = record(&scene);
+        let draws = capture.draws();
+
+        assert_eq!(
+            uniform(draws[0], "ambient_color"),
+            Some(&vector(&[0.1f32, 0.2, 0.3]))
+        );
+        assert_eq!(
+            uniform(draws[0], "light_direction"),
+            Some(&vector(&[0.0f32, -1.0, 0.0]))
+        );
+        assert_eq!(
+            uniform(draws[0], "light_color"),
+            Some(&vector(&[0.5f32, 0.5, 0.5]))
+        );
+        assert_eq!(
+            draws[1]
+                .uniforms
+                .iter()
+                .rev()
+                .find(|uniform| uniform.name == "light_color")
+                .map(|uniform| &uniform.value),
+            Some(&vector(&[0.0f32, 1.0, 0.0, 1.0]))
+        );
+    }
+
+    #[test]
+    fn records_pick_handles_and_clears() {
+        let geometry = Rc::new(Primitive::cuboid(pos3(0.0, 0.0, 0.0), pos3(1.0, 1.0, 1.0)));
+        let pickable = Pickable::new(mesh(&geometry, "lambert", 0.0));
+
+        let mut drawer = RecordingDrawer::new();
+        drawer.clear_scene(rgba(0.1, 0.2, 0.3, 1.0), 1.0);
+        pickable.draw(&mut drawer, &IDENTITY, &camera()).unwrap();
+        let capture = drawer.into_capture();
+
+        assert_eq!(
+            capture.commands[0],
+            RecordedCommand::Clear {
+                color: [0.1, 0.2, 0.3, 1.0],
+                depth: 1.0
+            }
+        );
+        assert_eq!(
+            capture.draws()[0].pick_handle,
+            Some(pickable.get_handle().get_id())
+        );
+    }
+
+    #[test]
+    fn dumps_capture_to_json() {
+        let geometry = Rc::new(Primitive::cuboid(pos3(0.0, 0.0, 0.0), pos3(1.0, 1.0, 1.0)));
+        let capture = record(&*mesh(&geometry, "lambert", 0.5));
+
+        let json: serde_json::Value = serde_json::from_str(&capture.to_json()).unwrap();
+        let draw = &json["commands"][0]["Draw"];
+
+        let uniforms = draw["uniforms"].as_array().unwrap();
+        let value = |name: &str| {
+            uniforms
+                .iter()
+                .find(|uniform| uniform["name"] == name)
+                .map(|uniform| uniform["value"].clone())
+        };
+
+        assert_eq!(draw["program"], "lambert");
+        assert_eq!(value("roughness"), Some(serde_json::json!({ "Float": 0.5 })));
+        assert_eq!(
+            value("camera_position_world"),
+            Some(serde_json::json!({ "Vector": [0.0, 0.0, 5.0] }))
+        );
+        assert!(value("matrix_to_world").is_some());
+        assert!(value("matrix_to_projection").is_some());
+    }
+}
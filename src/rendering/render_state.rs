@@ -0,0 +1,232 @@
+use glium::draw_parameters::Stencil;
+use glium::uniforms::UniformValue;
+use glium::*;
+
+use super::description::MaterialDescription;
+use super::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BlendMode {
+    Opaque,
+    Alpha,
+    Premultiplied,
+    Additive,
+    Multiply,
+}
+
+impl BlendMode {
+    pub fn to_blend(self) -> Blend {
+        let function = |source, destination| BlendingFunction::Addition {
+            source,
+            destination,
+        };
+
+        match self {
+            BlendMode::Opaque => Default::default(),
+            BlendMode::Alpha => Blend::alpha_blending(),
+            BlendMode::Premultiplied => Blend {
+                color: function(
+                    LinearBlendingFactor::One,
+                    LinearBlendingFactor::OneMinusSourceAlpha,
+                ),
+                alpha: function(
+                    LinearBlendingFactor::One,
+                    LinearBlendingFactor::OneMinusSourceAlpha,
+                ),
+                constant_value: (0.0, 0.0, 0.0, 0.0),
+            },
+            BlendMode::Additive => Blend {
+                color: function(LinearBlendingFactor::SourceAlpha, LinearBlendingFactor::One),
+                alpha: function(LinearBlendingFactor::Zero, LinearBlendingFactor::One),
+                constant_value: (0.0, 0.0, 0.0, 0.0),
+            },
+            BlendMode::Multiply => Blend {
+                color: function(
+                    LinearBlendingFactor::DestinationColor,
+                    LinearBlendingFactor::Zero,
+                ),
+                alpha: function(LinearBlendingFactor::Zero, LinearBlendingFactor::One),
+                constant_value: (0.0, 0.0, 0.0, 0.0),
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CullMode {
+    None,
+    Back,
+    Front,
+}
+
+impl CullMode {
+    pub fn to_backface_culling(self) -> BackfaceCullingMode {
+        match self {
+            CullMode::None => BackfaceCullingMode::CullingDisabled,
+            CullMode::Back => BackfaceCullingMode::CullClockwise,
+            CullMode::Front => BackfaceCullingMode::CullCounterClockwise,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct RenderState {
+    pub depth_test: DepthTest,
+    pub depth_write: bool,
+    pub blend: BlendMode,
+    pub cull: CullMode,
+    pub polygon_mode: PolygonMode,
+    pub line_width: Option<f32>,
+    pub color_mask: (bool, bool, bool, bool),
+    pub stencil: Stencil,
+}
+
+#[allow(dead_code)]
+impl RenderState {
+    pub fn opaque() -> RenderState {
+        RenderState {
+            depth_test: DepthTest::IfLess,
+            depth_write: true,
+            blend: BlendMode::Opaque,
+            cull: CullMode::Back,
+            polygon_mode: PolygonMode::Fill,
+            line_width: None,
+            color_mask: (true, true, true, true),
+            stencil: Default::default(),
+        }
+    }
+
+    pub fn transparent() -> RenderState {
+        RenderState {
+            depth_write: false,
+            blend: BlendMode::Alpha,
+            ..RenderState::opaque()
+        }
+    }
+
+    pub fn premultiplied() -> RenderState {
+        RenderState {
+            depth_write: false,
+            blend: BlendMode::Premultiplied,
+            ..RenderState::opaque()
+        }
+    }
+
+    pub fn additive() -> RenderState {
+        RenderState {
+            depth_write: false,
+            blend: BlendMode::Additive,
+            cull: CullMode::None,
+            ..RenderState::opaque()
+        }
+    }
+
+    pub fn wireframe(line_width: f32) -> RenderState {
+        RenderState {
+            cull: CullMode::None,
+            polygon_mode: PolygonMode::Line,
+            line_width: Some(line_width),
+            ..RenderState::opaque()
+        }
+    }
+
+    pub fn with_depth(mut self, test: DepthTest, write: bool) -> RenderState {
+        self.depth_test = test;
+        self.depth_write = write;
+        self
+    }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> RenderState {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_cull(mut self, cull: CullMode) -> RenderState {
+        self.cull = cull;
+        self
+    }
+
+    pub fn with_polygon_mode(mut self, polygon_mode: PolygonMode) -> RenderState {
+        self.polygon_mode = polygon_mode;
+        self
+    }
+
+    pub fn with_line_width(mut self, line_width: Option<f32>) -> RenderState {
+        self.line_width = line_width;
+        self
+    }
+
+    pub fn with_color_mask(mut self, color_mask: (bool, bool, bool, bool)) -> RenderState {
+        self.color_mask = color_mask;
+        self
+    }
+
+    pub fn with_stencil(mut self, stencil: Stencil) -> RenderState {
+        self.stencil = stencil;
+        self
+    }
+
+    pub fn to_draw_parameters(&self) -> DrawParameters<'static> {
+        DrawParameters {
+            depth: Depth {
+                test: self.depth_test,
+                write: self.depth_write,
+                ..Default::default()
+            },
+            blend: self.blend.to_blend(),
+            backface_culling: self.cull.to_backface_culling(),
+            polygon_mode: self.polygon_mode,
+            line_width: self.line_width,
+            color_mask: self.color_mask,
+            stencil: self.stencil,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for RenderState {
+    fn default() -> RenderState {
+        RenderState::opaque()
+    }
+}
+
+pub struct WithRenderState<M: Material> {
+    pub material: M,
+    pub render_state: RenderState,
+}
+
+#[allow(dead_code)]
+impl<M: Material> WithRenderState<M> {
+    pub fn new(material: M, render_state: RenderState) -> WithRenderState<M> {
+        WithRenderState {
+            material,
+            render_state,
+        }
+    }
+}
+
+impl<M: Material> Material for WithRenderState<M> {
+    fn get_program(&self) -> Option<&Program> {
+        self.material.get_program()
+    }
+
+    fn get_draw_parameters(&self) -> DrawParameters<'_> {
+        self.render_state.to_draw_parameters()
+    }
+
+    fn visit_uniforms<'n>(&'n self, visitor: &mut dyn FnMut(&str, UniformValue<'n>)) {
+        self.material.visit_uniforms(visitor);
+    }
+
+    fn get_program_name(&self) -> Option<&str> {
+        self.material.get_program_name()
+    }
+
+    fn get_gbuffer_program(&self) -> Option<&Program> {
+        self.material.get_gbuffer_program()
+    }
+
+    fn describe(&self) -> Option<MaterialDescription> {
+        self.material.describe()
+    }
+}
@@ -1,25 +1,52 @@
 use glium::*;
+use std::cell::RefCell;
 use std::rc::Rc;
 
-use super::super::geometry::GpuGeometry;
+use super::super::game::GameEvent;
+use super::super::geometry::{GeometryBuffers, GpuGeometry};
 use super::super::math::*;
+use super::deferred::{DeferredRenderer, Light};
+use super::description::{GeometryDescription, MaterialDescription, MeshDescription};
+use super::environment::{
+    EnvironmentBaker, EnvironmentDrawer, EnvironmentMap, EnvironmentUniforms, ReflectionProbe,
+};
+use super::fog::{FogDrawer, FogUniforms};
+use super::graph::{Node, NodeId, SceneGraph};
+use super::lights::{LightUniforms, LightsDrawer};
+use super::traversal::{self, BreadthFirst, DepthFirst, SceneVisitor};
 use super::*;
 
-pub struct Mesh<M: Material> {
-    geometry: Rc<GpuGeometry>,
+pub struct Mesh<M: Material, G: GeometryBuffers = GpuGeometry> {
+    geometry: Rc<G>,
     material: Rc<M>,
+    geometry_source: Option<GeometryDescription>,
 }
 
-impl<M: Material> Mesh<M> {
-    pub fn new(geometry: Rc<GpuGeometry>, material: Rc<M>) -> Mesh<M> {
-        Mesh { geometry, material }
+impl<M: Material, G: GeometryBuffers> Mesh<M, G> {
+    pub fn new(geometry: Rc<G>, material: Rc<M>) -> Mesh<M, G> {
+        Mesh {
+            geometry,
+            material,
+            geometry_source: None,
+        }
+    }
+
+    /// Remembers where the geometry came from, so that describing the mesh
+    /// refers to it instead of embedding every vertex.
+    pub fn with_geometry_source(mut self, source: GeometryDescription) -> Mesh<M, G> {
+        self.geometry_source = Some(source);
+        self
     }
 
-    pub fn get_geometry(&self) -> &GpuGeometry {
+    pub fn get_geometry_source(&self) -> Option<&GeometryDescription> {
+        self.geometry_source.as_ref()
+    }
+
+    pub fn get_geometry(&self) -> &G {
         &self.geometry
     }
 
-    pub fn get_geometry_rc(&self) -> Rc<GpuGeometry> {
+    pub fn get_geometry_rc(&self) -> Rc<G> {
         Rc::clone(&self.geometry)
     }
 
@@ -32,24 +59,38 @@ impl<M: Material> Mesh<M> {
     }
 }
 
-impl<M: Material> Drawable for Mesh<M> {
+impl<M: Material, G: GeometryBuffers> Drawable for Mesh<M, G> {
     fn draw(
         &self,
         drawer: &mut dyn Drawer,
         transform: &Transform,
         camera: &Camera,
-    ) -> Result<(), DrawError> {
+    ) -> Result<(), RenderError> {
         let geometry = self.get_geometry();
         let material = self.get_material();
 
         drawer.draw_single(
-            &geometry.vertex_buffer,
-            &geometry.index_buffer,
+            geometry,
             &transform,
             &camera,
             material,
         )
     }
+
+    fn describe(&self) -> Option<MeshDescription> {
+        let geometry = match &self.geometry_source {
+            Some(source) => source.clone(),
+            None => GeometryDescription::from_geometry(&self.geometry.to_geometry()?),
+        };
+
+        Some(MeshDescription {
+            geometry,
+            material: self
+                .material
+                .describe()
+                .unwrap_or_else(MaterialDescription::unnamed),
+        })
+    }
 }
 
 pub trait Transformable {
@@ -112,7 +153,7 @@ impl Drawable for Object {
         drawer: &mut dyn Drawer,
         transform: &Transform,
         camera: &Camera,
-    ) -> Result<(), DrawError> {
+    ) -> Result<(), RenderError> {
         let multiplied_transform = *transform * self.transform;
         self.children
             .iter()
@@ -123,29 +164,95 @@ impl Drawable for Object {
 
 pub struct Scene {
     background_color: Color,
-    root: Object,
+    fog: Option<Fog>,
+    ambient_color: Color,
+    lights: Vec<Light>,
+    environment: Option<Rc<EnvironmentMap>>,
+    probes: Vec<Rc<ReflectionProbe>>,
+    fallback_environment: RefCell<Option<Rc<EnvironmentMap>>>,
+    transform: Transform,
+    graph: SceneGraph,
 }
 
 #[allow(dead_code)]
 impl Scene {
     pub fn new() -> Scene {
         let background_color = rgba(0.0, 0.0, 0.0, 0.0);
-        let root = Object::new();
 
         Scene {
             background_color,
-            root,
+            fog: None,
+            ambient_color: rgba(0.0, 0.0, 0.0, 1.0),
+            lights: vec![],
+            environment: None,
+            probes: vec![],
+            fallback_environment: RefCell::new(None),
+            transform: IDENTITY,
+            graph: SceneGraph::new(),
         }
     }
 
-    pub fn add(&mut self, child: Rc<dyn Drawable>) -> &Self {
-        self.root.add(child);
-        self
+    pub fn add(&mut self, child: Rc<dyn Drawable>) -> NodeId {
+        self.graph.spawn(Node::new("").with_drawable(child))
     }
 
-    pub fn remove(&mut self, child: Rc<dyn Drawable>) -> &Self {
-        self.root.remove(child);
-        self
+    pub fn spawn(&mut self, node: Node) -> NodeId {
+        self.graph.spawn(node)
+    }
+
+    pub fn remove(&mut self, id: NodeId) -> bool {
+        self.graph.despawn(id)
+    }
+
+    pub fn find(&self, path: &str) -> Option<NodeId> {
+        self.graph.find(path)
+    }
+
+    pub fn find_by_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = NodeId> + 'a {
+        self.graph.find_by_name(name)
+    }
+
+    pub fn find_with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = NodeId> + 'a {
+        self.graph.find_with_tag(tag)
+    }
+
+    pub fn world_transform(&self, id: NodeId) -> Option<Transform> {
+        self.graph
+            .world_transform(id)
+            .map(|transform| self.transform * transform)
+    }
+
+    /// Runs the behaviours of every node. The game loop does not drive
+    /// scenes by itself: call this, or forward events to
+    /// `handle_game_event`, from the `EventHandler`.
+    pub fn update(&mut self, delta_time: f32) {
+        self.graph.update(delta_time);
+    }
+
+    pub fn handle_game_event(&mut self, event: &GameEvent) {
+        if let GameEvent::Update { delta_time, .. } = *event {
+            self.update(delta_time as f32);
+        }
+    }
+
+    pub fn depth_first(&self) -> DepthFirst<'_> {
+        DepthFirst::new(&self.graph, self.transform, None)
+    }
+
+    pub fn breadth_first(&self) -> BreadthFirst<'_> {
+        BreadthFirst::new(&self.graph, self.transform, None)
+    }
+
+    pub fn walk(&self, visitor: &mut dyn SceneVisitor) -> bool {
+        traversal::walk(&self.graph, &self.transform, None, visitor)
+    }
+
+    pub fn get_graph(&self) -> &SceneGraph {
+        &self.graph
+    }
+
+    pub fn get_graph_mut(&mut self) -> &mut SceneGraph {
+        &mut self.graph
     }
 
     pub fn set_background_color(&mut self, color: Color) {
@@ -155,15 +262,95 @@ impl Scene {
     pub fn get_background_color(&self) -> Color {
         self.background_color
     }
+
+    pub fn set_fog(&mut self, fog: Option<Fog>) {
+        self.fog = fog;
+    }
+
+    pub fn get_fog(&self) -> Option<Fog> {
+        self.fog
+    }
+
+    pub fn set_ambient_color(&mut self, color: Color) {
+        self.ambient_color = color;
+    }
+
+    pub fn get_ambient_color(&self) -> Color {
+        self.ambient_color
+    }
+
+    pub fn add_light(&mut self, light: Light) -> &Self {
+        self.lights.push(light);
+        self
+    }
+
+    pub fn get_lights(&self) -> &[Light] {
+        &self.lights
+    }
+
+    pub fn get_lights_mut(&mut self) -> &mut Vec<Light> {
+        &mut self.lights
+    }
+
+    pub fn clear_lights(&mut self) {
+        self.lights.clear();
+    }
+
+    pub fn set_environment(&mut self, environment: Option<Rc<EnvironmentMap>>) {
+        self.environment = environment;
+    }
+
+    pub fn get_environment(&self) -> Option<Rc<EnvironmentMap>> {
+        self.environment.clone()
+    }
+
+    pub fn add_probe(&mut self, probe: Rc<ReflectionProbe>) -> &Self {
+        self.probes.push(probe);
+        self
+    }
+
+    pub fn remove_probe(&mut self, probe: Rc<ReflectionProbe>) -> &Self {
+        self.probes.retain(|p| !Rc::ptr_eq(p, &probe));
+        self
+    }
+
+    pub fn get_probes(&self) -> &[Rc<ReflectionProbe>] {
+        &self.probes
+    }
+
+    pub fn capture_probes(
+        &self,
+        display: &Display,
+        baker: &EnvironmentBaker,
+    ) -> Result<(), RenderError> {
+        for probe in self.probes.iter() {
+            probe.capture(display, baker, self)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn render<S: Surface>(
+        &self,
+        display: &Display,
+        surface: &mut S,
+        renderer: &mut DeferredRenderer,
+        camera: &Camera,
+    ) -> Result<(), RenderError> {
+        match camera.render_path {
+            RenderPath::Forward => self.draw(surface, &IDENTITY, camera),
+            RenderPath::Deferred => renderer.render(display, surface, self, camera),
+        }
+    }
 }
 
 impl Transformable for Scene {
     fn set_transform(&mut self, transform: Transform) {
-        self.root.set_transform(transform);
+        self.transform = transform;
     }
 
     fn get_transform(&self) -> Transform {
-        self.root.get_transform()
+        self.transform
     }
 }
 
@@ -173,8 +360,20 @@ impl Drawable for Scene {
         drawer: &mut dyn Drawer,
         transform: &Transform,
         camera: &Camera,
-    ) -> Result<(), DrawError> {
+    ) -> Result<(), RenderError> {
         drawer.clear_scene(self.background_color, 1.0);
-        self.root.draw(drawer, transform, camera)
+
+        let fog = FogUniforms::new(self.fog, self.background_color);
+        let mut drawer = FogDrawer::new(drawer, fog);
+        let lights = LightUniforms::new(self.ambient_color, &self.lights);
+        let mut drawer = LightsDrawer::new(&mut drawer, lights);
+        let environment = EnvironmentUniforms::new(
+            &self.probes,
+            self.environment.clone(),
+            &self.fallback_environment,
+        );
+        let mut drawer = EnvironmentDrawer::new(&mut drawer, environment);
+        self.graph
+            .draw(&mut drawer, &(*transform * self.transform), camera)
     }
 }
@@ -0,0 +1,218 @@
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use glium::framebuffer::{DepthRenderBuffer, SimpleFrameBuffer};
+use glium::texture::{
+    DepthFormat, MipmapsOption, SrgbTexture2d, Texture2d, UncompressedFloatFormat,
+};
+use glium::uniforms::MagnifySamplerFilter;
+use glium::*;
+
+use super::super::math::color::{linear_to_srgb, srgb_to_linear};
+use super::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ColorEncoding {
+    Srgb,
+    Linear,
+}
+
+#[derive(Clone)]
+pub struct Screenshot {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+fn encode_channel(value: f32, encoding: ColorEncoding) -> u8 {
+    let value = match encoding {
+        ColorEncoding::Srgb => value,
+        ColorEncoding::Linear => linear_to_srgb(value.clamp(0.0, 1.0)),
+    };
+
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[allow(dead_code)]
+impl Screenshot {
+    fn from_rows_bottom_up<P, F>(rows: Vec<Vec<P>>, to_rgba: F) -> Screenshot
+    where
+        P: Copy,
+        F: Fn(P) -> [u8; 4],
+    {
+        let height = rows.len() as u32;
+        let width = rows.first().map_or(0, |row| row.len() as u32);
+
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        for row in rows.iter().rev() {
+            for pixel in row.iter() {
+                pixels.extend_from_slice(&to_rgba(*pixel));
+            }
+        }
+
+        Screenshot {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn from_srgb_texture(texture: &SrgbTexture2d) -> Screenshot {
+        let rows: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+
+        Screenshot::from_rows_bottom_up(rows, |(r, g, b, a)| [r, g, b, a])
+    }
+
+    pub fn from_texture(texture: &Texture2d, encoding: ColorEncoding) -> Screenshot {
+        let rect = Rect {
+            left: 0,
+            bottom: 0,
+            width: texture.width(),
+            height: texture.height(),
+        };
+        let rows: Vec<Vec<(f32, f32, f32, f32)>> = texture
+            .main_level()
+            .first_layer()
+            .into_image(None)
+            .unwrap()
+            .raw_read(&rect);
+
+        Screenshot::from_rows_bottom_up(rows, |(r, g, b, a)| {
+            [
+                encode_channel(r, encoding),
+                encode_channel(g, encoding),
+                encode_channel(b, encoding),
+                encode_channel(a, ColorEncoding::Srgb),
+            ]
+        })
+    }
+
+    pub fn from_surface<S: Surface>(
+        display: &Display,
+        surface: &S,
+    ) -> Result<Screenshot, RenderError> {
+        let (width, height) = surface.get_dimensions();
+        let texture = Texture2d::empty_with_format(
+            display,
+            UncompressedFloatFormat::U8U8U8U8,
+            MipmapsOption::NoMipmap,
+            width,
+            height,
+        )?;
+
+        surface.blit_whole_color_to(
+            &texture.as_surface(),
+            &BlitTarget {
+                left: 0,
+                bottom: 0,
+                width: width as i32,
+                height: height as i32,
+            },
+            MagnifySamplerFilter::Nearest,
+        );
+
+        Ok(Screenshot::from_texture(&texture, ColorEncoding::Srgb))
+    }
+
+    pub fn from_front_buffer(display: &Display) -> Result<Screenshot, RenderError> {
+        let rows: Vec<Vec<(u8, u8, u8, u8)>> = display.read_front_buffer()?;
+
+        Ok(Screenshot::from_rows_bottom_up(rows, |(r, g, b, a)| {
+            [r, g, b, a]
+        }))
+    }
+
+    pub fn capture_supersampled(
+        display: &Display,
+        drawable: &dyn Drawable,
+        camera: &Camera,
+        scale: u32,
+    ) -> Result<Screenshot, RenderError> {
+        assert!(scale >= 1);
+
+        let (width, height) = display.get_framebuffer_dimensions();
+        let (width, height) = (width * scale, height * scale);
+
+        let texture = Texture2d::empty_with_format(
+            display,
+            UncompressedFloatFormat::F16F16F16F16,
+            MipmapsOption::NoMipmap,
+            width,
+            height,
+        )?;
+        let depth_buffer = DepthRenderBuffer::new(display, DepthFormat::I24, width, height)?;
+        let mut framebuffer =
+            SimpleFrameBuffer::with_depth_buffer(display, &texture, &depth_buffer)?;
+
+        drawable.draw(&mut framebuffer, &IDENTITY, camera)?;
+
+        Ok(Screenshot::from_texture(&texture, ColorEncoding::Linear).downsampled(scale))
+    }
+
+    pub fn downsampled(&self, factor: u32) -> Screenshot {
+        assert!(factor >= 1);
+
+        let width = self.width / factor;
+        let height = self.height / factor;
+        let samples = (factor * factor) as f32;
+
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0f32; 4];
+                for sy in 0..factor {
+                    for sx in 0..factor {
+                        let source_x = x * factor + sx;
+                        let source_y = y * factor + sy;
+                        let index = ((source_y * self.width + source_x) * 4) as usize;
+
+                        for (channel, value) in sum.iter_mut().enumerate() {
+                            let byte = self.pixels[index + channel] as f32 / 255.0;
+                            *value += if channel < 3 {
+                                srgb_to_linear(byte)
+                            } else {
+                                byte
+                            };
+                        }
+                    }
+                }
+
+                pixels.push(encode_channel(sum[0] / samples, ColorEncoding::Linear));
+                pixels.push(encode_channel(sum[1] / samples, ColorEncoding::Linear));
+                pixels.push(encode_channel(sum[2] / samples, ColorEncoding::Linear));
+                pixels.push(encode_channel(sum[3] / samples, ColorEncoding::Srgb));
+            }
+        }
+
+        Screenshot {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+
+        Ok(())
+    }
+
+    pub fn default_file_name() -> String {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis());
+
+        format!("screenshot-{}.png", timestamp)
+    }
+}
@@ -0,0 +1,109 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use glium::uniforms::{AsUniformValue, UniformValue};
+
+use super::super::animation::{Pose, Skeleton};
+use super::super::geometry::GpuSkinnedGeometry;
+use super::layer::{DrawerLayer, LayeredMaterial};
+use super::*;
+
+pub const MAX_JOINTS: usize = 64;
+
+struct JointUniforms<'a> {
+    names: &'a [String],
+    joint_matrices: &'a [[[f32; 4]; 4]],
+}
+
+impl<'a> DrawerLayer for JointUniforms<'a> {
+    fn visit_uniforms<'n>(&'n self, visitor: &mut dyn FnMut(&str, UniformValue<'n>)) {
+        for (name, matrix) in self.names.iter().zip(self.joint_matrices.iter()) {
+            visitor(name, matrix.as_uniform_value());
+        }
+    }
+}
+
+pub struct SkinnedMesh<M: Material> {
+    geometry: Rc<GpuSkinnedGeometry>,
+    material: Rc<M>,
+    skeleton: Rc<Skeleton>,
+    names: Vec<String>,
+    joint_matrices: RefCell<Vec<[[f32; 4]; 4]>>,
+}
+
+#[allow(dead_code)]
+impl<M: Material> SkinnedMesh<M> {
+    pub fn new(
+        geometry: Rc<GpuSkinnedGeometry>,
+        material: Rc<M>,
+        skeleton: Rc<Skeleton>,
+    ) -> SkinnedMesh<M> {
+        let joint_count = skeleton.joint_count();
+        assert!(joint_count <= MAX_JOINTS);
+
+        let names = (0..joint_count)
+            .map(|index| format!("joint_matrices[{}]", index))
+            .collect();
+        let joint_matrices = skeleton
+            .rest_pose()
+            .joint_matrices(&skeleton)
+            .iter()
+            .map(|matrix| matrix.elements)
+            .collect();
+
+        SkinnedMesh {
+            geometry,
+            material,
+            skeleton,
+            names,
+            joint_matrices: RefCell::new(joint_matrices),
+        }
+    }
+
+    pub fn get_geometry(&self) -> &GpuSkinnedGeometry {
+        &self.geometry
+    }
+
+    pub fn get_material(&self) -> &M {
+        &self.material
+    }
+
+    pub fn get_material_rc(&self) -> Rc<M> {
+        Rc::clone(&self.material)
+    }
+
+    pub fn get_skeleton(&self) -> &Skeleton {
+        &self.skeleton
+    }
+
+    pub fn get_skeleton_rc(&self) -> Rc<Skeleton> {
+        Rc::clone(&self.skeleton)
+    }
+
+    pub fn set_pose(&self, pose: &Pose) {
+        let matrices = pose.joint_matrices(&self.skeleton);
+
+        let mut joint_matrices = self.joint_matrices.borrow_mut();
+        for (target, matrix) in joint_matrices.iter_mut().zip(matrices.iter()) {
+            *target = matrix.elements;
+        }
+    }
+}
+
+impl<M: Material> Drawable for SkinnedMesh<M> {
+    fn draw(
+        &self,
+        drawer: &mut dyn Drawer,
+        transform: &Transform,
+        camera: &Camera,
+    ) -> Result<(), RenderError> {
+        let joint_matrices = self.joint_matrices.borrow();
+        let joints = JointUniforms {
+            names: &self.names,
+            joint_matrices: &joint_matrices,
+        };
+        let material = LayeredMaterial::new(self.material.as_ref(), &joints);
+
+        drawer.draw_single(self.geometry.as_ref(), transform, camera, &material)
+    }
+}
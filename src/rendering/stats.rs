@@ -0,0 +1,311 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+use glium::*;
+
+use super::layer::{DrawerLayer, LayeredDrawer};
+use super::*;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    pub draw_calls: usize,
+    pub vertices: usize,
+    pub indices: usize,
+    pub programs: usize,
+    pub program_switches: usize,
+    pub drawn: usize,
+    pub culled: usize,
+}
+
+#[allow(dead_code)]
+impl FrameStats {
+    pub fn triangles(&self) -> usize {
+        self.indices / 3
+    }
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} draw calls, {} triangles, {} vertices, {} programs ({} switches), {} drawn, {} culled",
+            self.draw_calls,
+            self.triangles(),
+            self.vertices,
+            self.programs,
+            self.program_switches,
+            self.drawn,
+            self.culled
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct AverageFrameStats {
+    pub draw_calls: f64,
+    pub vertices: f64,
+    pub indices: f64,
+    pub programs: f64,
+    pub program_switches: f64,
+    pub drawn: f64,
+    pub culled: f64,
+}
+
+impl fmt::Display for AverageFrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.1} draw calls, {:.0} triangles, {:.0} vertices, {:.1} programs ({:.1} switches), {:.1} drawn, {:.1} culled",
+            self.draw_calls,
+            self.indices / 3.0,
+            self.vertices,
+            self.programs,
+            self.program_switches,
+            self.drawn,
+            self.culled
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum ProgramKey {
+    Handle(*const Program),
+    Name(Option<String>),
+}
+
+impl ProgramKey {
+    fn of(material: &dyn Material) -> ProgramKey {
+        match material.get_program() {
+            Some(program) => ProgramKey::Handle(program),
+            None => ProgramKey::Name(material.get_program_name().map(|name| name.to_string())),
+        }
+    }
+}
+
+pub struct RenderStats {
+    averaged_frames: usize,
+    current: FrameStats,
+    current_programs: HashSet<ProgramKey>,
+    last_program: Option<ProgramKey>,
+    history: VecDeque<FrameStats>,
+}
+
+#[allow(dead_code)]
+impl RenderStats {
+    pub fn new(averaged_frames: usize) -> RenderStats {
+        assert!(averaged_frames > 0);
+
+        RenderStats {
+            averaged_frames,
+            current: Default::default(),
+            current_programs: HashSet::new(),
+            last_program: None,
+            history: VecDeque::with_capacity(averaged_frames),
+        }
+    }
+
+    pub fn next_frame(&mut self) {
+        if self.history.len() == self.averaged_frames {
+            self.history.pop_front();
+        }
+        self.history.push_back(self.current);
+
+        self.current = Default::default();
+        self.current_programs.clear();
+        self.last_program = None;
+    }
+
+    pub fn get_current_frame(&self) -> FrameStats {
+        self.current
+    }
+
+    pub fn get_last_frame(&self) -> FrameStats {
+        self.history.back().copied().unwrap_or_default()
+    }
+
+    pub fn get_average(&self) -> AverageFrameStats {
+        let count = self.history.len() as f64;
+        if count == 0.0 {
+            return Default::default();
+        }
+
+        let mut average = AverageFrameStats::default();
+        for frame in self.history.iter() {
+            average.draw_calls += frame.draw_calls as f64;
+            average.vertices += frame.vertices as f64;
+            average.indices += frame.indices as f64;
+            average.programs += frame.programs as f64;
+            average.program_switches += frame.program_switches as f64;
+            average.drawn += frame.drawn as f64;
+            average.culled += frame.culled as f64;
+        }
+
+        AverageFrameStats {
+            draw_calls: average.draw_calls / count,
+            vertices: average.vertices / count,
+            indices: average.indices / count,
+            programs: average.programs / count,
+            program_switches: average.program_switches / count,
+            drawn: average.drawn / count,
+            culled: average.culled / count,
+        }
+    }
+
+    fn record_draw(&mut self, program: ProgramKey, vertices: usize, indices: usize) {
+        self.current.draw_calls += 1;
+        self.current.vertices += vertices;
+        self.current.indices += indices;
+
+        if self.last_program.as_ref() != Some(&program) {
+            self.current.program_switches += 1;
+            self.last_program = Some(program.clone());
+        }
+        self.current_programs.insert(program);
+        self.current.programs = self.current_programs.len();
+    }
+
+    fn record_culled(&mut self) {
+        self.current.culled += 1;
+    }
+
+    fn record_drawn(&mut self) {
+        self.current.drawn += 1;
+    }
+}
+
+impl DrawerLayer for &mut RenderStats {
+    fn record_draw(&mut self, geometry: &dyn GeometryBuffers, material: &dyn Material) {
+        RenderStats::record_draw(
+            self,
+            ProgramKey::of(material),
+            geometry.get_vertex_count(),
+            geometry.get_index_count(),
+        );
+    }
+
+    fn note_culled(&mut self) {
+        self.record_culled();
+    }
+
+    fn note_drawn(&mut self) {
+        self.record_drawn();
+    }
+}
+
+pub type StatsDrawer<'d> = LayeredDrawer<'d, &'d mut RenderStats>;
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::super::super::geometry::{Geometry, Primitive};
+    use super::super::scene::Mesh;
+    use super::*;
+
+    struct FailingDrawer;
+
+    impl Drawer for FailingDrawer {
+        fn clear_scene(&mut self, _color: Color, _depth: f32) {}
+
+        fn draw_single(
+            &mut self,
+            _geometry: &dyn GeometryBuffers,
+            _transform: &Transform,
+            _camera: &Camera,
+            _material: &dyn Material,
+        ) -> Result<(), RenderError> {
+            Err(RenderError::MissingBuffers)
+        }
+    }
+
+    fn camera() -> Camera {
+        Camera::new(IDENTITY, Projection::perspective_fov(1.0, 1.0, 0.1, 100.0))
+    }
+
+    fn mesh(geometry: &Rc<Geometry>, program_name: &str) -> Rc<dyn Drawable> {
+        let material = ParameterMaterial::named(program_name);
+
+        Rc::new(Mesh::new(Rc::clone(geometry), Rc::new(material)))
+    }
+
+    fn draw_meshes(stats: &mut RenderStats, geometry: &Rc<Geometry>, program_names: &[&str]) {
+        let mut recording = RecordingDrawer::new();
+        let mut drawer = StatsDrawer::new(&mut recording, stats);
+        for program_name in program_names {
+            mesh(geometry, program_name)
+                .draw(&mut drawer, &IDENTITY, &camera())
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn counts_draws_programs_and_visibility() {
+        let geometry = Rc::new(Primitive::cuboid(pos3(0.0, 0.0, 0.0), pos3(1.0, 1.0, 1.0)));
+        let mut graph = SceneGraph::new();
+        graph.spawn(Node::new("a").with_drawable(mesh(&geometry, "lambert")));
+        graph.spawn(Node::new("b").with_drawable(mesh(&geometry, "lambert")));
+        graph.spawn(Node::new("c").with_drawable(mesh(&geometry, "pbr")));
+        graph.spawn(
+            Node::new("hidden")
+                .with_visible(false)
+                .with_drawable(mesh(&geometry, "pbr")),
+        );
+
+        let mut stats = RenderStats::new(4);
+        let mut recording = RecordingDrawer::new();
+        graph
+            .draw(
+                &mut StatsDrawer::new(&mut recording, &mut stats),
+                &IDENTITY,
+                &camera(),
+            )
+            .unwrap();
+
+        let frame = stats.get_current_frame();
+        assert_eq!(frame.draw_calls, 3);
+        assert_eq!(recording.get_capture().draws().len(), 3);
+        assert_eq!(frame.vertices, 3 * geometry.vertices.len());
+        assert_eq!(frame.indices, 3 * geometry.indices.len());
+        assert_eq!(frame.triangles(), geometry.indices.len());
+        assert_eq!(frame.programs, 2);
+        assert_eq!(frame.program_switches, 2);
+        assert_eq!(frame.drawn, 3);
+        assert_eq!(frame.culled, 1);
+    }
+
+    #[test]
+    fn averages_over_the_last_frames() {
+        let geometry = Rc::new(Primitive::cuboid(pos3(0.0, 0.0, 0.0), pos3(1.0, 1.0, 1.0)));
+        let mut stats = RenderStats::new(2);
+
+        draw_meshes(&mut stats, &geometry, &["lambert"]);
+        stats.next_frame();
+        draw_meshes(&mut stats, &geometry, &["lambert", "pbr", "lambert"]);
+        stats.next_frame();
+
+        assert_eq!(stats.get_current_frame(), FrameStats::default());
+        assert_eq!(stats.get_last_frame().draw_calls, 3);
+        assert_eq!(stats.get_last_frame().program_switches, 3);
+        assert_eq!(stats.get_average().draw_calls, 2.0);
+
+        stats.next_frame();
+
+        assert_eq!(stats.get_last_frame(), FrameStats::default());
+        assert_eq!(stats.get_average().draw_calls, 1.5);
+    }
+
+    #[test]
+    fn does_not_count_failed_draws() {
+        let geometry = Rc::new(Primitive::cuboid(pos3(0.0, 0.0, 0.0), pos3(1.0, 1.0, 1.0)));
+        let mut stats = RenderStats::new(1);
+
+        let result = mesh(&geometry, "lambert").draw(
+            &mut StatsDrawer::new(&mut FailingDrawer, &mut stats),
+            &IDENTITY,
+            &camera(),
+        );
+
+        assert!(result.is_err());
+        assert_eq!(stats.get_current_frame(), FrameStats::default());
+    }
+}
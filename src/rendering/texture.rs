@@ -0,0 +1,58 @@
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+use glium::texture::{RawImage2d, SrgbTexture2d, Texture2d};
+use glium::Display;
+
+fn invalid<E: std::fmt::Debug>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", error))
+}
+
+pub fn read_png_rgba<P: AsRef<Path>>(path: P) -> io::Result<(Vec<u8>, (u32, u32))> {
+    decode_png_rgba(File::open(path)?)
+}
+
+pub fn decode_png_rgba<R: Read>(reader: R) -> io::Result<(Vec<u8>, (u32, u32))> {
+    let mut decoder = png::Decoder::new(reader);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    buffer.truncate(info.buffer_size());
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer
+            .chunks(3)
+            .flat_map(|pixel| vec![pixel[0], pixel[1], pixel[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buffer
+            .chunks(2)
+            .flat_map(|pixel| vec![pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect(),
+        png::ColorType::Grayscale => buffer
+            .iter()
+            .flat_map(|&value| vec![value, value, value, 255])
+            .collect(),
+        png::ColorType::Indexed => return Err(invalid("unexpanded indexed image")),
+    };
+
+    Ok((pixels, (info.width, info.height)))
+}
+
+pub fn load_png<P: AsRef<Path>>(display: &Display, path: P) -> io::Result<Texture2d> {
+    let (pixels, dimensions) = read_png_rgba(path)?;
+    let image = RawImage2d::from_raw_rgba_reversed(&pixels, dimensions);
+
+    Texture2d::new(display, image).map_err(invalid)
+}
+
+pub fn load_png_srgb<P: AsRef<Path>>(display: &Display, path: P) -> io::Result<SrgbTexture2d> {
+    let (pixels, dimensions) = read_png_rgba(path)?;
+    let image = RawImage2d::from_raw_rgba_reversed(&pixels, dimensions);
+
+    SrgbTexture2d::new(display, image).map_err(invalid)
+}
@@ -0,0 +1,184 @@
+use std::collections::VecDeque;
+
+use super::graph::{Node, NodeId, SceneGraph};
+use super::*;
+
+#[derive(Copy, Clone)]
+pub struct NodeVisit<'a> {
+    pub id: NodeId,
+    pub node: &'a Node,
+    pub world_transform: Transform,
+    pub depth: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VisitAction {
+    Continue,
+    SkipChildren,
+    Stop,
+}
+
+pub trait SceneVisitor {
+    fn visit(&mut self, visit: &NodeVisit) -> VisitAction;
+
+    fn leave(&mut self, _visit: &NodeVisit) {}
+}
+
+impl<F: FnMut(&NodeVisit) -> VisitAction> SceneVisitor for F {
+    fn visit(&mut self, visit: &NodeVisit) -> VisitAction {
+        self(visit)
+    }
+}
+
+fn make_visit<'a>(
+    graph: &'a SceneGraph,
+    transform: &Transform,
+    id: NodeId,
+    depth: usize,
+) -> Option<NodeVisit<'a>> {
+    Some(NodeVisit {
+        id,
+        node: graph.get(id)?,
+        world_transform: *transform * graph.world_transform(id)?,
+        depth,
+    })
+}
+
+fn start_nodes(graph: &SceneGraph, start: Option<NodeId>) -> Vec<NodeId> {
+    match start {
+        Some(id) if graph.contains(id) => vec![id],
+        Some(_) => vec![],
+        None => graph.get_roots().to_vec(),
+    }
+}
+
+pub struct DepthFirst<'a> {
+    graph: &'a SceneGraph,
+    transform: Transform,
+    stack: Vec<(NodeId, usize)>,
+}
+
+impl<'a> DepthFirst<'a> {
+    pub(crate) fn new(
+        graph: &'a SceneGraph,
+        transform: Transform,
+        start: Option<NodeId>,
+    ) -> DepthFirst<'a> {
+        let stack = start_nodes(graph, start)
+            .into_iter()
+            .rev()
+            .map(|id| (id, 0))
+            .collect();
+
+        DepthFirst {
+            graph,
+            transform,
+            stack,
+        }
+    }
+}
+
+impl<'a> Iterator for DepthFirst<'a> {
+    type Item = NodeVisit<'a>;
+
+    fn next(&mut self) -> Option<NodeVisit<'a>> {
+        while let Some((id, depth)) = self.stack.pop() {
+            if let Some(visit) = make_visit(self.graph, &self.transform, id, depth) {
+                self.stack.extend(
+                    visit
+                        .node
+                        .get_children()
+                        .iter()
+                        .rev()
+                        .map(|&child| (child, depth + 1)),
+                );
+                return Some(visit);
+            }
+        }
+
+        None
+    }
+}
+
+pub struct BreadthFirst<'a> {
+    graph: &'a SceneGraph,
+    transform: Transform,
+    queue: VecDeque<(NodeId, usize)>,
+}
+
+impl<'a> BreadthFirst<'a> {
+    pub(crate) fn new(
+        graph: &'a SceneGraph,
+        transform: Transform,
+        start: Option<NodeId>,
+    ) -> BreadthFirst<'a> {
+        let queue = start_nodes(graph, start)
+            .into_iter()
+            .map(|id| (id, 0))
+            .collect();
+
+        BreadthFirst {
+            graph,
+            transform,
+            queue,
+        }
+    }
+}
+
+impl<'a> Iterator for BreadthFirst<'a> {
+    type Item = NodeVisit<'a>;
+
+    fn next(&mut self) -> Option<NodeVisit<'a>> {
+        while let Some((id, depth)) = self.queue.pop_front() {
+            if let Some(visit) = make_visit(self.graph, &self.transform, id, depth) {
+                self.queue.extend(
+                    visit
+                        .node
+                        .get_children()
+                        .iter()
+                        .map(|&child| (child, depth + 1)),
+                );
+                return Some(visit);
+            }
+        }
+
+        None
+    }
+}
+
+fn walk_node(
+    graph: &SceneGraph,
+    transform: &Transform,
+    id: NodeId,
+    depth: usize,
+    visitor: &mut dyn SceneVisitor,
+) -> bool {
+    let visit = match make_visit(graph, transform, id, depth) {
+        Some(visit) => visit,
+        None => return true,
+    };
+
+    let completed = match visitor.visit(&visit) {
+        VisitAction::Stop => return false,
+        VisitAction::SkipChildren => true,
+        VisitAction::Continue => visit
+            .node
+            .get_children()
+            .iter()
+            .all(|&child| walk_node(graph, transform, child, depth + 1, visitor)),
+    };
+    visitor.leave(&visit);
+
+    completed
+}
+
+pub(crate) fn walk(
+    graph: &SceneGraph,
+    transform: &Transform,
+    start: Option<NodeId>,
+    visitor: &mut dyn SceneVisitor,
+) -> bool {
+    start_nodes(graph, start)
+        .into_iter()
+        .all(|id| walk_node(graph, transform, id, 0, visitor))
+}
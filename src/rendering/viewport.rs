@@ -0,0 +1,208 @@
+use glium::*;
+
+use super::*;
+
+#[derive(Copy, Clone, Debug)]
+pub enum ClearPolicy {
+    Scene,
+    Color(Color),
+    DepthOnly,
+    None,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Viewport {
+    pub left: u32,
+    pub bottom: u32,
+    pub width: u32,
+    pub height: u32,
+    pub clear: ClearPolicy,
+}
+
+#[allow(dead_code)]
+impl Viewport {
+    pub fn new(left: u32, bottom: u32, width: u32, height: u32) -> Viewport {
+        Viewport {
+            left,
+            bottom,
+            width,
+            height,
+            clear: ClearPolicy::Scene,
+        }
+    }
+
+    pub fn full(width: u32, height: u32) -> Viewport {
+        Viewport::new(0, 0, width, height)
+    }
+
+    pub fn for_surface<S: Surface>(surface: &S) -> Viewport {
+        let (width, height) = surface.get_dimensions();
+
+        Viewport::full(width, height)
+    }
+
+    pub fn with_clear(self, clear: ClearPolicy) -> Viewport {
+        Viewport { clear, ..self }
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        if self.height == 0 {
+            1.0
+        } else {
+            self.width as f32 / self.height as f32
+        }
+    }
+
+    pub fn as_rect(&self) -> Rect {
+        Rect {
+            left: self.left,
+            bottom: self.bottom,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub fn contains(&self, position: Position2, surface_height: u32) -> bool {
+        let x = position.vector.x;
+        let y = surface_height as f32 - position.vector.y;
+
+        x >= self.left as f32
+            && x < (self.left + self.width) as f32
+            && y >= self.bottom as f32
+            && y < (self.bottom + self.height) as f32
+    }
+
+    pub fn perspective_camera(&self, transform: Transform, fov_y: f32) -> Camera {
+        let projection = Projection::perspective_fov(fov_y, self.aspect_ratio(), 0.1, 1000.0);
+
+        Camera::new(transform, projection)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum SplitDirection {
+    Horizontal,
+    Vertical,
+}
+
+/// Lays out one to four players, returning `None` for any other count.
+pub fn split_screen(
+    width: u32,
+    height: u32,
+    players: usize,
+    direction: SplitDirection,
+) -> Option<Vec<Viewport>> {
+    if !(1..=4).contains(&players) {
+        return None;
+    }
+
+    let half_width = width / 2;
+    let half_height = height / 2;
+
+    let top_left = Viewport::new(0, height - half_height, half_width, half_height);
+    let top_right = Viewport::new(
+        half_width,
+        height - half_height,
+        width - half_width,
+        half_height,
+    );
+    let bottom_left = Viewport::new(0, 0, half_width, height - half_height);
+    let bottom_right = Viewport::new(half_width, 0, width - half_width, height - half_height);
+
+    let viewports = match (players, direction) {
+        (1, _) => vec![Viewport::full(width, height)],
+        (2, SplitDirection::Horizontal) => vec![
+            Viewport::new(0, height - half_height, width, half_height),
+            Viewport::new(0, 0, width, height - half_height),
+        ],
+        (2, SplitDirection::Vertical) => vec![
+            Viewport::new(0, 0, half_width, height),
+            Viewport::new(half_width, 0, width - half_width, height),
+        ],
+        (3, SplitDirection::Horizontal) => vec![
+            Viewport::new(0, height - half_height, width, half_height),
+            bottom_left,
+            bottom_right,
+        ],
+        (3, SplitDirection::Vertical) => vec![
+            Viewport::new(0, 0, half_width, height),
+            top_right,
+            bottom_right,
+        ],
+        _ => vec![top_left, top_right, bottom_left, bottom_right],
+    };
+
+    Some(viewports)
+}
+
+pub struct ViewportDrawer<'s, S: Surface> {
+    surface: &'s mut S,
+    viewport: Viewport,
+}
+
+impl<'s, S: Surface> ViewportDrawer<'s, S> {
+    pub fn new(surface: &'s mut S, viewport: Viewport) -> ViewportDrawer<'s, S> {
+        ViewportDrawer { surface, viewport }
+    }
+
+    pub fn get_viewport(&self) -> Viewport {
+        self.viewport
+    }
+}
+
+impl<'s, S: Surface> Drawer for ViewportDrawer<'s, S> {
+    fn clear_scene(&mut self, color: Color, depth: f32) {
+        let rect = self.viewport.as_rect();
+        let color = match self.viewport.clear {
+            ClearPolicy::Scene => Some(color),
+            ClearPolicy::Color(color) => Some(color),
+            ClearPolicy::DepthOnly => None,
+            ClearPolicy::None => return,
+        };
+
+        self.surface.clear(
+            Some(&rect),
+            color.map(|c| (c.r, c.g, c.b, c.a)),
+            false,
+            Some(depth),
+            None,
+        );
+    }
+
+    fn draw_single(
+        &mut self,
+        geometry: &dyn GeometryBuffers,
+        transform: &Transform,
+        camera: &Camera,
+        material: &dyn Material,
+    ) -> Result<(), RenderError> {
+        let (vertex_buffer, index_buffer) = get_buffers(geometry)?;
+        let rect = self.viewport.as_rect();
+        let uniforms = MergedUniforms::new(transform, camera, material);
+
+        self.surface.draw(
+            vertex_buffer,
+            index_buffer,
+            get_program(material)?,
+            &uniforms,
+            &DrawParameters {
+                viewport: Some(rect),
+                scissor: Some(rect),
+                ..material.get_draw_parameters()
+            },
+        )
+        .map_err(RenderError::from)
+    }
+}
+
+pub fn draw_viewports<S: Surface>(
+    surface: &mut S,
+    drawable: &dyn Drawable,
+    transform: &Transform,
+    views: &[(Viewport, Camera)],
+) -> Result<(), RenderError> {
+    views.iter().try_for_each(|(viewport, camera)| {
+        let mut drawer = ViewportDrawer::new(surface, *viewport);
+        drawable.draw(&mut drawer, transform, camera)
+    })
+}